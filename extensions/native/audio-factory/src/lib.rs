@@ -164,7 +164,8 @@ pub fn ExtAudioFactory(cx: Scope<Props>) -> Element {
 
     let free_space = fs2::free_space("/").unwrap_or(1);
     let total_space = fs2::total_space("/").unwrap_or(1);
-    let perc = (((total_space / free_space) as f64) * 0.1) * 100.0;
+    // the bar shows how much of the disk is in use
+    let perc = (total_space.saturating_sub(free_space) as f64 / total_space as f64) * 100.0;
     let space = format!(
         "{}/{} Free",
         format_size(free_space, DECIMAL),
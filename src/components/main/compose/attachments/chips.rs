@@ -0,0 +1,108 @@
+use dioxus::prelude::*;
+use dioxus_heroicons::{outline::Shape, Icon};
+use humansize::{format_size, DECIMAL};
+use ui_kit::button::Button;
+
+use crate::components::main::{
+    compose::attachments::Pending,
+    files::{operations::Progress, thumbnails},
+};
+
+#[derive(Props, PartialEq)]
+struct ChipProps {
+    attachment: Pending,
+}
+
+#[allow(non_snake_case)]
+fn Thumbnail(cx: Scope<ChipProps>) -> Element {
+    let path = cx.props.attachment.path.clone();
+    let thumbnail = use_future(&cx, &path, |path| async move {
+        thumbnails::instance().generate(path).await
+    });
+
+    cx.render(match thumbnail.value() {
+        Some(Some(thumbnail)) => rsx!(img {
+            class: "thumbnail",
+            src: "{thumbnail}",
+        }),
+        _ => rsx!(div {
+            class: "thumbnail",
+            Icon {
+                icon: Shape::Document,
+            }
+        }),
+    })
+}
+
+#[derive(Props)]
+pub struct Props<'a> {
+    attachments: Vec<Pending>,
+    // files still being copied out of Constellation
+    fetching: Option<Progress>,
+    on_remove: EventHandler<'a, usize>,
+}
+
+#[allow(non_snake_case)]
+pub fn AttachmentChips<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
+    if cx.props.attachments.is_empty() && cx.props.fetching.is_none() {
+        return None;
+    }
+    let total: u64 = cx.props.attachments.iter().map(|a| a.size).sum();
+    let summary = format!(
+        "{} file(s), {}",
+        cx.props.attachments.len(),
+        format_size(total, DECIMAL)
+    );
+
+    cx.render(rsx! {
+        div {
+            class: "attachment-chips",
+            cx.props.attachments.iter().enumerate().map(|(index, attachment)| {
+                let key = attachment.path.to_string_lossy().to_string();
+                let name = &attachment.name;
+                let size = format_size(attachment.size, DECIMAL);
+                rsx!(
+                    div {
+                        key: "{key}",
+                        class: "attachment-chip",
+                        title: "{key}",
+                        Thumbnail {
+                            attachment: attachment.clone(),
+                        },
+                        div {
+                            class: "details",
+                            span {
+                                class: "name",
+                                "{name}"
+                            },
+                            span {
+                                class: "size",
+                                "{size}"
+                            },
+                        },
+                        Button {
+                            icon: Shape::XMark,
+                            state: ui_kit::button::State::Transparent,
+                            on_pressed: move |_| cx.props.on_remove.call(index),
+                        },
+                    }
+                )
+            }),
+            cx.props.fetching.as_ref().map(|progress| {
+                let percent = progress.percent();
+                rsx!(
+                    div {
+                        class: "attachment-chip fetching",
+                        "Getting {progress.item} from Files… {percent}%"
+                    }
+                )
+            }),
+            (!cx.props.attachments.is_empty()).then(|| rsx!(
+                span {
+                    class: "summary",
+                    "{summary}"
+                }
+            )),
+        }
+    })
+}
@@ -0,0 +1,158 @@
+// Files waiting to be sent with the next message. They can come from the file dialog, from Constellation,
+// from an image on the clipboard or be dropped onto the chat. Files which don't already exist on disk are
+// staged under `outgoing` and removed once they've been sent or taken off the message.
+
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use arboard::Clipboard;
+use dioxus::prelude::UseRef;
+use image::RgbaImage;
+use utils::DEFAULT_PATH;
+use uuid::Uuid;
+
+use crate::{
+    components::main::files::operations::{download_items, Progress},
+    Storage,
+};
+
+pub mod chips;
+pub mod picker;
+
+// under DEFAULT_PATH
+pub const STAGING_DIR: &str = "outgoing";
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Pending {
+    pub path: PathBuf,
+    pub name: String,
+    pub size: u64,
+    // staged by us, so it's removed after sending
+    pub temporary: bool,
+}
+
+impl Pending {
+    /// the file at `path`, or nothing when it's a folder or can't be read
+    pub fn new(path: PathBuf, temporary: bool) -> Option<Self> {
+        let metadata = fs::metadata(&path)
+            .ok()
+            .filter(|metadata| metadata.is_file())?;
+        let name = path.file_name()?.to_string_lossy().to_string();
+        Some(Self {
+            path,
+            name,
+            size: metadata.len(),
+            temporary,
+        })
+    }
+
+    /// drops the staged copy, if there is one
+    pub fn discard(&self) {
+        if !self.temporary {
+            return;
+        }
+        if let Err(error) = fs::remove_file(&self.path) {
+            log::warn!("Not possible to remove {:?}: {error}", self.path);
+        }
+        // each batch has its own folder, which goes once it's empty
+        if let Some(parent) = self.path.parent() {
+            let _ = fs::remove_dir(parent);
+        }
+    }
+}
+
+fn staging_dir() -> Result<PathBuf, std::io::Error> {
+    let directory = DEFAULT_PATH
+        .read()
+        .join(STAGING_DIR)
+        .join(Uuid::new_v4().to_string());
+    fs::create_dir_all(&directory)?;
+    Ok(directory)
+}
+
+/// removes copies left staged by an earlier run, which nothing is going to send anymore
+pub fn clear_staged() {
+    let directory = DEFAULT_PATH.read().join(STAGING_DIR);
+    if !directory.exists() {
+        return;
+    }
+    if let Err(error) = fs::remove_dir_all(&directory) {
+        log::warn!("Not possible to remove {:?}: {error}", directory);
+    }
+}
+
+/// the files among `paths`, folders are left out
+pub fn from_paths(paths: Vec<PathBuf>) -> Vec<Pending> {
+    paths
+        .into_iter()
+        .filter_map(|path| Pending::new(path, false))
+        .collect()
+}
+
+/// saves the image on the clipboard as a PNG. Nothing is returned when there isn't an image, such as when
+/// text is being pasted.
+pub fn from_clipboard() -> Result<Option<Pending>, Box<dyn Error>> {
+    let image = match Clipboard::new()?.get_image() {
+        Ok(image) => image,
+        Err(arboard::Error::ContentNotAvailable) => return Ok(None),
+        Err(error) => return Err(Box::new(error)),
+    };
+    let rgba = RgbaImage::from_raw(
+        image.width as u32,
+        image.height as u32,
+        image.bytes.into_owned(),
+    )
+    .ok_or("the clipboard image is malformed")?;
+    let name = format!(
+        "pasted-{}.png",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+    let path = staging_dir()?.join(name);
+    rgba.save(&path)?;
+    Ok(Pending::new(path, true))
+}
+
+/// copies the `picked` files, each with the folder it's in, out of Constellation. Returns the copies and
+/// the names of the files which couldn't be read.
+pub async fn from_constellation(
+    storage: Storage,
+    picked: Vec<(Vec<String>, String)>,
+    progress: UseRef<Option<Progress>>,
+) -> (Vec<Pending>, Vec<String>) {
+    let mut pending = Vec::new();
+    let mut failed = Vec::new();
+    for (path, name) in picked {
+        // a folder of its own keeps names from different folders apart
+        let directory = match staging_dir() {
+            Ok(directory) => directory,
+            Err(error) => {
+                log::error!("Not possible to stage {name}: {error}");
+                failed.push(name);
+                continue;
+            }
+        };
+        let not_read = download_items(
+            storage.clone(),
+            path,
+            vec![name.clone()],
+            directory.clone(),
+            progress.clone(),
+        )
+        .await;
+        failed.extend(not_read);
+        pending.extend(staged_files(&directory));
+    }
+    (pending, failed)
+}
+
+fn staged_files(directory: &Path) -> Vec<Pending> {
+    fs::read_dir(directory)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| Pending::new(entry.path(), true))
+        .collect()
+}
@@ -0,0 +1,147 @@
+use dioxus::prelude::*;
+use dioxus_heroicons::{outline::Shape, Icon};
+use humansize::{format_size, DECIMAL};
+use ui_kit::{button::Button, popup::Popup};
+use warp::constellation::item::ItemType;
+
+use crate::{components::main::files::directory_at, Storage};
+
+#[derive(Props)]
+pub struct Props<'a> {
+    storage: Storage,
+    // the picked files, each with the folder it's in
+    on_pick: EventHandler<'a, Vec<(Vec<String>, String)>>,
+    on_cancel: EventHandler<'a, ()>,
+}
+
+#[allow(non_snake_case)]
+pub fn ConstellationPicker<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
+    let path = use_state(&cx, Vec::<String>::new);
+    let picked = use_ref(&cx, Vec::<(Vec<String>, String)>::new);
+
+    let mut items = match directory_at(&cx.props.storage, path.get()) {
+        Ok(directory) => directory.get_items(),
+        Err(error) => {
+            log::debug!("Not possible to open {:?}: {:?}", path.get(), error);
+            Vec::new()
+        }
+    };
+    items.sort_by_key(|item| (item.item_type() != ItemType::DirectoryItem, item.name()));
+    let location = match path.is_empty() {
+        true => String::from("Files"),
+        false => format!("Files / {}", path.join(" / ")),
+    };
+    let picked_count = picked.read().len();
+
+    cx.render(rsx! {
+        Popup {
+            hidden: false,
+            on_dismiss: move |_| cx.props.on_cancel.call(()),
+            children: cx.render(rsx!(
+                div {
+                    id: "constellation-picker",
+                    h2 {
+                        "Attach from Files"
+                    },
+                    div {
+                        class: "location",
+                        (!path.is_empty()).then(|| rsx!(
+                            Button {
+                                icon: Shape::ArrowLeft,
+                                state: ui_kit::button::State::Transparent,
+                                on_pressed: move |_| {
+                                    let mut parent = path.get().clone();
+                                    parent.pop();
+                                    path.set(parent);
+                                },
+                            }
+                        )),
+                        span {
+                            "{location}"
+                        },
+                    },
+                    items.is_empty().then(|| rsx!(
+                        p {
+                            class: "empty",
+                            "This folder is empty."
+                        }
+                    )),
+                    div {
+                        class: "items",
+                        items.iter().map(|item| {
+                            let name = item.name();
+                            let key = name.clone();
+                            if item.item_type() == ItemType::DirectoryItem {
+                                return rsx!(
+                                    div {
+                                        key: "{key}",
+                                        class: "item",
+                                        onclick: move |_| {
+                                            let mut inner = path.get().clone();
+                                            inner.push(name.clone());
+                                            path.set(inner);
+                                        },
+                                        Icon {
+                                            icon: Shape::Folder,
+                                        },
+                                        span {
+                                            class: "name",
+                                            "{key}"
+                                        },
+                                    }
+                                );
+                            }
+                            let entry = (path.get().clone(), name);
+                            let is_picked = picked.read().contains(&entry);
+                            let (picked_class, icon) = match is_picked {
+                                true => ("picked", Shape::Check),
+                                false => ("", Shape::Document),
+                            };
+                            let size = format_size(item.size(), DECIMAL);
+                            rsx!(
+                                div {
+                                    key: "{key}",
+                                    class: "item {picked_class}",
+                                    onclick: move |_| {
+                                        let mut picked = picked.write();
+                                        match picked.iter().position(|p| *p == entry) {
+                                            Some(index) => {
+                                                picked.remove(index);
+                                            }
+                                            None => picked.push(entry.clone()),
+                                        }
+                                    },
+                                    Icon {
+                                        icon: icon,
+                                    },
+                                    span {
+                                        class: "name",
+                                        "{key}"
+                                    },
+                                    span {
+                                        class: "size",
+                                        "{size}"
+                                    },
+                                }
+                            )
+                        })
+                    },
+                    div {
+                        class: "actions",
+                        Button {
+                            text: String::from("Cancel"),
+                            state: ui_kit::button::State::Secondary,
+                            on_pressed: move |_| cx.props.on_cancel.call(()),
+                        },
+                        Button {
+                            icon: Shape::Plus,
+                            text: format!("Attach {picked_count}"),
+                            disabled: picked_count == 0,
+                            on_pressed: move |_| cx.props.on_pick.call(picked.read().clone()),
+                        },
+                    }
+                }
+            )),
+        }
+    })
+}
@@ -4,8 +4,18 @@ use std::{
 };
 
 use crate::{
-    components::main::compose::{divider::Divider, msg::Msg, reply::Reply},
-    iutils,
+    components::main::{
+        compose::{
+            divider::Divider,
+            msg::{attachment::ConversationAttachments, Msg},
+            reply::Reply,
+        },
+        files::sharing,
+    },
+    iutils::{
+        self, ban_list,
+        config::{Config, LinkPreviewPolicy},
+    },
     state::{Actions, LastMsgSent},
     Account, Messaging, STATE,
 };
@@ -45,6 +55,8 @@ pub struct Props {
     account: Account,
     messaging: Messaging,
     users_typing: UseRef<HashMap<DID, String>>,
+    // messages from banned accounts are left out unless the user asked to see them
+    show_banned: bool,
 }
 
 #[allow(non_snake_case)]
@@ -62,7 +74,13 @@ pub fn Messages(cx: Scope<Props>) -> Element {
     // this one has a special name because of the other variable names within the use_future
     let list: UseRef<Vec<Message>> = use_ref(&cx, Vec::new).clone();
     // this one is for the rsx! macro. it is reversed for display purposes and defined here because `list` gets moved into the use_future
-    let messages: Vec<Message> = list.read().iter().cloned().collect();
+    let show_banned = cx.props.show_banned;
+    let messages: Vec<Message> = list
+        .read()
+        .iter()
+        .filter(|message| show_banned || !ban_list::is_banned(&message.sender()))
+        .cloned()
+        .collect();
 
     // this is used for reading the event stream.
     let current_chat = state
@@ -70,6 +88,19 @@ pub fn Messages(cx: Scope<Props>) -> Element {
         .selected_chat
         .and_then(|x| state.read().active_chats.get(&x).cloned());
 
+    // the setting is read when a conversation is opened rather than for every message shown
+    let opened_chat = state.read().selected_chat;
+    let read_policy = use_ref(&cx, || None::<(Option<Uuid>, LinkPreviewPolicy)>);
+    let stored_policy = *read_policy.read();
+    let link_previews = match stored_policy {
+        Some((chat, policy)) if chat == opened_chat => policy,
+        _ => {
+            let policy = Config::load_config_or_default().privacy.link_previews;
+            *read_policy.write_silent() = Some((opened_chat, policy));
+            policy
+        }
+    };
+
     let first_unread_message_id = current_chat
         .clone()
         .unwrap_or_default()
@@ -200,8 +231,9 @@ pub fn Messages(cx: Scope<Props>) -> Element {
                 None => return,
             };
 
-            if current_chat.num_unread_messages != 0 {
+            if current_chat.num_unread_messages != 0 || current_chat.num_unread_mentions != 0 {
                 current_chat.num_unread_messages = 0;
+                current_chat.num_unread_mentions = 0;
                 state
                     .write_silent()
                     .dispatch(Actions::UpdateConversation(current_chat.clone()));
@@ -229,10 +261,11 @@ pub fn Messages(cx: Scope<Props>) -> Element {
                 }
             };
 
-            let messages = rg
+            let mut messages = rg
                 .get_messages(current_chat.conversation.id(), MessageOptions::default())
                 .await
                 .unwrap_or_default();
+            messages.retain(|message| !sharing::is_control_message(message));
 
             //This is to prevent the future updating the state and causing a rerender
             if *list.read() != messages {
@@ -251,7 +284,13 @@ pub fn Messages(cx: Scope<Props>) -> Element {
                         message_id,
                     } => {
                         if current_chat.conversation.id() == conversation_id {
-                            match rg.get_message(conversation_id, message_id).await {
+                            let message = rg.get_message(conversation_id, message_id).await;
+                            // the sidebar doesn't hear about messages in the open chat
+                            if matches!(&message, Ok(message) if sharing::changes_shares(message)) {
+                                sharing::invalidate();
+                            }
+                            match message {
+                                Ok(message) if sharing::is_control_message(&message) => {}
                                 Ok(message) => {
                                     log::debug!("compose/messages streamed a new message ");
                                     // remove typing indicator
@@ -268,7 +307,7 @@ pub fn Messages(cx: Scope<Props>) -> Element {
                                     // todo: check if sidebar gets updated
                                     list.write().push(message.clone());
                                     current_chat.last_msg_sent =
-                                        Some(LastMsgSent::new(&message.value()));
+                                        Some(LastMsgSent::new(&sharing::visible_lines(&message)));
                                     state.write().dispatch(Actions::UpdateConversation(
                                         current_chat.clone(),
                                     ));
@@ -331,6 +370,11 @@ pub fn Messages(cx: Scope<Props>) -> Element {
         .map(|info| info.conversation.recipients())
         .unwrap_or_default();
     let messages_len = messages.len();
+    // lets a previewed attachment step on to the others in the conversation
+    cx.provide_context(ConversationAttachments::from_messages(
+        &cx.props.messaging,
+        &messages,
+    ));
 
     // get profile pictures for all senders in the conversation and cache them
     let mut profile_pictures = HashMap::new();
@@ -420,6 +464,7 @@ pub fn Messages(cx: Scope<Props>) -> Element {
                                 account: cx.props.account.clone(),
                                 sender: msg_sender,
                                 remote: is_remote,
+                                link_previews: link_previews,
                                 // not sure why this works. I believe the calculations for is_last and is_first are correct but for an unknown reason the time and profile picture gets displayed backwards.
                                 last:  is_last,
                                 first: is_first,
@@ -1,3 +1,4 @@
+pub mod attachments;
 pub mod divider;
 pub mod messages;
 pub mod msg;
@@ -5,23 +6,35 @@ pub mod reply;
 pub mod topbar;
 pub mod write;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use dioxus::prelude::*;
+use dioxus::{core::to_owned, desktop::wry::webview::FileDropEvent, prelude::*};
 use dioxus_heroicons::outline::Shape;
+use dioxus_toast::ToastInfo;
 use ui_kit::{button::Button, typing_indicator::TypingIndicator};
 
+use futures::StreamExt;
 use rfd::FileDialog;
-use std::path::PathBuf;
-use warp::crypto::DID;
+use uuid::Uuid;
+use warp::{crypto::DID, raygun::ConversationType};
 
 use crate::{
     components::{
-        main::compose::{messages::Messages, topbar::TopBar, write::Write},
+        main::{
+            compose::{
+                attachments::{chips::AttachmentChips, picker::ConstellationPicker, Pending},
+                messages::Messages,
+                topbar::TopBar,
+                write::Write,
+            },
+            files::{notify_failures, operations::Progress},
+        },
         media::MediaContainer,
+        reusable::{ban_warning::BanWarning, profile_modal::ProfileModal},
     },
+    iutils::ban_list,
     state::{Actions, LastMsgSent},
-    Messaging, LANGUAGE, STATE,
+    Messaging, Storage, DRAG_FILE_EVENT, LANGUAGE, STATE, TOAST_MANAGER,
 };
 use utils::Account;
 
@@ -29,6 +42,29 @@ use utils::Account;
 pub struct Props {
     account: Account,
     messaging: Messaging,
+    storage: Storage,
+}
+
+// files waiting to be sent, by the chat they're for
+type Attachments = HashMap<Uuid, Vec<Pending>>;
+
+// adds files to the message being written in `chat` without adding the same one twice
+fn add_attachments(attachments: &UseRef<Attachments>, chat: Option<Uuid>, added: Vec<Pending>) {
+    let chat = match chat {
+        Some(chat) => chat,
+        None => {
+            added.iter().for_each(Pending::discard);
+            return;
+        }
+    };
+    let mut attachments = attachments.write();
+    let attachments = attachments.entry(chat).or_default();
+    for pending in added {
+        if attachments.iter().any(|a| a.path == pending.path) {
+            continue;
+        }
+        attachments.push(pending);
+    }
 }
 
 #[allow(non_snake_case)]
@@ -43,21 +79,75 @@ pub fn Compose(cx: Scope<Props>) -> Element {
     let show_media = use_state(&cx, || false);
     let users_typing: &UseRef<HashMap<DID, String>> = use_ref(&cx, HashMap::new);
 
-    let selected_file = use_state(&cx, || -> Option<Vec<PathBuf>> { None });
-    let selected_file_str = &selected_file
-        .clone()
-        .as_ref()
-        .unwrap_or(&vec![])
-        .iter()
-        .map(|f| f.clone().into_os_string().into_string().unwrap_or_default())
-        .collect::<Vec<String>>()
-        .join(", ");
+    let toast = use_atom_ref(&cx, TOAST_MANAGER);
+    let attachments = use_ref(&cx, Attachments::new);
+    let picking = use_state(&cx, || false);
+    let fetching = use_ref(&cx, || None::<Progress>);
+    let dragging = use_state(&cx, || false);
+
+    // the window reports dropped files globally, so once something is dragged over the chat we watch for
+    // the drop that follows
+    let drop_watcher = use_coroutine(&cx, |mut rx: UnboundedReceiver<Option<Uuid>>| {
+        to_owned![attachments, dragging];
+        async move {
+            while let Some(chat) = rx.next().await {
+                // a drop made elsewhere earlier would still be there, so wait for this drag first
+                let mut hovered = false;
+                loop {
+                    let event = DRAG_FILE_EVENT.read().clone();
+                    match event {
+                        FileDropEvent::Hovered(_) => hovered = true,
+                        FileDropEvent::Dropped(paths) if hovered => {
+                            *DRAG_FILE_EVENT.write() = FileDropEvent::Cancelled;
+                            add_attachments(&attachments, chat, attachments::from_paths(paths));
+                            break;
+                        }
+                        FileDropEvent::Cancelled if hovered => break,
+                        _ => {}
+                    }
+                    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                }
+                dragging.set(false);
+            }
+        }
+    });
 
     let areTyping = !users_typing.clone().read().is_empty();
+    let chat_attachments = current_chat
+        .and_then(|chat| attachments.read().get(&chat).cloned())
+        .unwrap_or_default();
+    let chat_attachments2 = chat_attachments.clone();
+
+    // a direct chat with a banned account stays hidden until the user chooses to see it
+    let revealed = use_state(&cx, HashSet::<Uuid>::new);
+    let show_banned = current_chat.map_or(false, |id| revealed.contains(&id));
+    let my_did = cx
+        .props
+        .account
+        .get_own_identity()
+        .map(|i| i.did_key())
+        .ok();
+    let ban = current_chat
+        .and_then(|id| state.read().active_chats.get(&id).cloned())
+        .filter(|info| info.conversation.conversation_type() == ConversationType::Direct)
+        .and_then(|info| {
+            info.conversation
+                .recipients()
+                .into_iter()
+                .filter(|did| Some(did) != my_did.as_ref())
+                .find_map(|did| ban_list::check(&did))
+        })
+        .filter(|_| !show_banned);
 
     cx.render(rsx! {
         div {
             class: "compose",
+            ondragenter: move |_| {
+                if !**dragging {
+                    dragging.set(true);
+                    drop_watcher.send(current_chat);
+                }
+            },
                 rsx!(
                     TopBar {
                         account: cx.props.account.clone(),
@@ -65,6 +155,12 @@ pub fn Compose(cx: Scope<Props>) -> Element {
                             show_media.set(!show_media);
                         },
                     },
+                    ProfileModal {
+                        account: cx.props.account.clone(),
+                        messaging: cx.props.messaging.clone(),
+                        on_chat: move |_| {},
+                        on_call: move |_| show_media.set(true),
+                    },
                     (**show_warning).then(|| rsx!(
                         div {
                             class: "alpha-warning animate__animated animate__slideInDown",
@@ -86,16 +182,71 @@ pub fn Compose(cx: Scope<Props>) -> Element {
                     div {
                         id: "scroll-messages-container",
                         class: "messages-container",
-                        Messages {
-                            account: cx.props.account.clone(),
-                            messaging: cx.props.messaging.clone(),
-                            users_typing: users_typing.clone(),
+                        (**dragging).then(|| rsx!(
+                            div {
+                                class: "drop-overlay",
+                                "Drop files to attach them"
+                            }
+                        )),
+                        match ban {
+                            Some(ban) => rsx!(
+                                BanWarning {
+                                    ban: ban,
+                                    continue_text: String::from("Show Anyway"),
+                                    cancel_text: String::from("Hide Chat"),
+                                    on_continue: move |_| {
+                                        if let Some(id) = current_chat {
+                                            revealed.with_mut(|revealed| {
+                                                revealed.insert(id);
+                                            });
+                                        }
+                                    },
+                                    on_cancel: move |_| {
+                                        if let Some(id) = current_chat {
+                                            state.write().dispatch(Actions::HideConversation(id));
+                                        }
+                                    },
+                                }
+                            ),
+                            None => rsx!(
+                                Messages {
+                                    account: cx.props.account.clone(),
+                                    messaging: cx.props.messaging.clone(),
+                                    users_typing: users_typing.clone(),
+                                    show_banned: show_banned,
+                                }
+                            ),
                         }
                     },
-                    div {
-                        "{selected_file_str}"
+                    AttachmentChips {
+                        attachments: chat_attachments,
+                        fetching: fetching.read().clone(),
+                        on_remove: move |index: usize| {
+                            let removed = current_chat
+                                .and_then(|chat| attachments.write().get_mut(&chat).map(|pending| pending.remove(index)));
+                            if let Some(removed) = removed {
+                                removed.discard();
+                            }
+                        },
                     },
+                    (**picking).then(|| rsx!(
+                        ConstellationPicker {
+                            storage: cx.props.storage.clone(),
+                            on_pick: move |picked| {
+                                picking.set(false);
+                                let storage = cx.props.storage.clone();
+                                to_owned![attachments, fetching, toast];
+                                cx.spawn(async move {
+                                    let (added, failed) = attachments::from_constellation(storage, picked, fetching).await;
+                                    add_attachments(&attachments, current_chat, added);
+                                    notify_failures(&toast, "attach", &failed);
+                                });
+                            },
+                            on_cancel: move |_| picking.set(false),
+                        }
+                    )),
                     Write {
+                        account: cx.props.account.clone(),
                         messaging: cx.props.messaging.clone(),
                         on_submit: move |message: String| {
                             text.set(String::from(""));
@@ -107,7 +258,8 @@ pub fn Compose(cx: Scope<Props>) -> Element {
                                 .map(|s| s.to_string())
                                 .collect::<Vec<_>>();
 
-                            if text_as_vec.is_empty() && selected_file.is_none() {
+                            let pending = chat_attachments2.clone();
+                            if text_as_vec.is_empty() && pending.is_empty() {
                                 return;
                             }
 
@@ -122,15 +274,19 @@ pub fn Compose(cx: Scope<Props>) -> Element {
                                         .dispatch(Actions::UpdateConversation(conversation_info));
                                 }
 
-                                if selected_file.is_some() {
-                                    let attachments = selected_file.as_ref().unwrap().to_vec();
-                                    if let Err(_e) =
-                                        warp::async_block_in_place_uncheck(rg.attach(id, attachments, text_as_vec))
-                                    {
-                                        //TODO: Handle error
-                                        println!("Error: {:?}", _e);
+                                if !pending.is_empty() {
+                                    let paths = pending.iter().map(|a| a.path.clone()).collect();
+                                    match warp::async_block_in_place_uncheck(rg.attach(id, paths, text_as_vec)) {
+                                        Ok(_) => {
+                                            pending.iter().for_each(Pending::discard);
+                                            attachments.write().remove(&id);
+                                        }
+                                        Err(error) => {
+                                            // the chips stay so sending can be tried again
+                                            log::error!("Not possible to send attachments: {error}");
+                                            toast.write().popup(ToastInfo::simple("Not possible to send the attachments."));
+                                        }
                                     }
-                                    selected_file.set(None);
                                 } else if let Err(_e) =
                                     warp::async_block_in_place_uncheck(rg.send(id, None, text_as_vec))
                                 {
@@ -142,11 +298,19 @@ pub fn Compose(cx: Scope<Props>) -> Element {
                             }
                         },
                         on_upload: move |_| {
-                            let file = FileDialog::new()
-                                .set_directory("/")
-                                .pick_files();
-                            selected_file.set(file);
-                        }
+                            if let Some(paths) = FileDialog::new().set_directory("/").pick_files() {
+                                add_attachments(attachments, current_chat, attachments::from_paths(paths));
+                            }
+                        },
+                        on_attach_from_files: move |_| picking.set(true),
+                        on_paste: move |_| match attachments::from_clipboard() {
+                            Ok(Some(pasted)) => add_attachments(attachments, current_chat, vec![pasted]),
+                            Ok(None) => {}
+                            Err(error) => {
+                                log::error!("Not possible to paste the clipboard image: {error}");
+                                toast.write().popup(ToastInfo::simple("Not possible to paste that image."));
+                            }
+                        },
                     },
                     div {
                         class: "chatbar-footer",
@@ -1,3 +1,7 @@
+use std::path::PathBuf;
+
+use crate::components::reusable::file_preview::{self, FilePreview, PreviewItem, PreviewSource};
+use crate::iutils::config::Config;
 use crate::Messaging;
 use dioxus::prelude::*;
 use dioxus_heroicons::outline::Shape;
@@ -9,8 +13,33 @@ use humansize::DECIMAL;
 use rfd::FileDialog;
 use ui_kit::button;
 use warp::constellation::file::File;
+use warp::constellation::Progression;
 use warp::raygun::Message;
 
+/// the attachments of the conversation being shown, oldest first, so a preview can step through them
+#[derive(Clone)]
+pub struct ConversationAttachments(pub Vec<PreviewItem>);
+
+impl ConversationAttachments {
+    pub fn from_messages(messaging: &Messaging, messages: &[Message]) -> Self {
+        let items = messages
+            .iter()
+            .flat_map(|message| {
+                message.attachments().into_iter().map(|file| PreviewItem {
+                    name: file.name(),
+                    size: file.size(),
+                    source: PreviewSource::Attachment {
+                        messaging: messaging.clone(),
+                        conversation_id: message.conversation_id(),
+                        message_id: message.id(),
+                    },
+                })
+            })
+            .collect();
+        Self(items)
+    }
+}
+
 // Remember: owned props must implement PartialEq!
 #[derive(PartialEq, Eq, Props)]
 pub struct Props {
@@ -18,101 +47,264 @@ pub struct Props {
     message: Message,
 }
 
+#[derive(Clone, PartialEq)]
+enum Download {
+    Idle,
+    // percent written, when the size is known
+    Running(Option<usize>),
+    Done(PathBuf),
+    Failed(String),
+}
+
+// an image shown in the message itself, when it's small enough to fetch straight away
+async fn inline_image(
+    messaging: Messaging,
+    conversation_id: uuid::Uuid,
+    message_id: uuid::Uuid,
+    name: String,
+    limit: u64,
+) -> Option<String> {
+    let mime = file_preview::mime_type(&file_preview::extension(&name))
+        .filter(|mime| mime.starts_with("image/"))?;
+    let downloaded =
+        file_preview::download_attachment(&messaging, conversation_id, message_id, &name, limit)
+            .await;
+    match downloaded {
+        Ok(bytes) => Some(file_preview::data_url(mime, &bytes)),
+        Err(error) => {
+            log::warn!("Not possible to show {name} inline: {error}");
+            None
+        }
+    }
+}
+
 #[allow(non_snake_case)]
 pub fn Attachment(cx: Scope<Props>) -> Element {
     let size = format_size(cx.props.file.size(), DECIMAL);
     let name = cx.props.file.name();
 
     let rg = cx.consume_context::<Messaging>().unwrap();
+    let preview = use_state(&cx, || false);
+    let download = use_state(&cx, || Download::Idle);
+
+    let auto_download_limit = Config::load_config_or_default()
+        .attachments
+        .auto_download_limit;
+    let inline = use_future(
+        &cx,
+        (&rg, &cx.props.message.id(), &name),
+        |(rg, message_id, name)| {
+            let conversation_id = cx.props.message.conversation_id();
+            let fetch =
+                (cx.props.file.size() as u64) <= auto_download_limit && auto_download_limit > 0;
+            async move {
+                match fetch {
+                    true => {
+                        inline_image(rg, conversation_id, message_id, name, auto_download_limit)
+                            .await
+                    }
+                    false => None,
+                }
+            }
+        },
+    );
+    let inline = inline.value().cloned().flatten();
+
+    let this_item = PreviewItem {
+        name: name.clone(),
+        size: cx.props.file.size(),
+        source: PreviewSource::Attachment {
+            messaging: rg.clone(),
+            conversation_id: cx.props.message.conversation_id(),
+            message_id: cx.props.message.id(),
+        },
+    };
+    let preview_items = cx
+        .consume_context::<ConversationAttachments>()
+        .map(|attachments| attachments.0)
+        .filter(|items| items.contains(&this_item))
+        .unwrap_or_else(|| vec![this_item.clone()]);
+    let preview_start = preview_items
+        .iter()
+        .position(|item| *item == this_item)
+        .unwrap_or_default();
 
     let handle_click = move |evt: MouseEvent| {
         evt.cancel_bubble();
 
+        let path = match FileDialog::new()
+            .set_file_name(&cx.props.file.name())
+            .set_directory("/")
+            .save_file()
+        {
+            Some(path) => path,
+            None => return,
+        };
+        download.set(Download::Running(None));
+
+        // the download carries on when the message scrolls out of view
         cx.spawn_forever({
             let rg = rg.to_owned();
-            let save_path = FileDialog::new()
-                .set_file_name(&cx.props.file.name())
-                .set_directory("/")
-                .save_file();
-
+            let download = download.clone();
             let conversation_id = cx.props.message.conversation_id();
             let id = cx.props.message.id();
             let name = cx.props.file.name();
 
             async move {
-                match save_path {
-                    Some(path) => match rg.download(conversation_id, id, name, path).await {
-                        Ok(mut stream) => {
-                            while let Some(progress) = stream.next().await {
-                                match progress {
-                                    warp::constellation::Progression::CurrentProgress {
-                                        current,
-                                        total,
-                                        ..
-                                    } => {
-                                        // println!("Written {} MB for {name}", current / 1024 / 1024);
-                                        if let Some(total) = total {
-                                            println!(
-                                                "{}% completed",
-                                                (((current as f64) / (total as f64)) * 100.)
-                                                    as usize
-                                            )
-                                        }
-                                    }
-                                    warp::constellation::Progression::ProgressComplete {
-                                        name,
-                                        total,
-                                    } => {
-                                        // TODO: Actual UI upload progress bindings.
-                                        println!(
-                                            "{name} downloaded with {} bytes written",
-                                            total.unwrap_or_default()
-                                        );
-                                    }
-                                    // TODO: Actual UI upload progress bindings.
-                                    warp::constellation::Progression::ProgressFailed {
-                                        name,
-                                        error,
-                                        ..
-                                    } => {
-                                        println!("{name} failed to download with error: {error:?}")
-                                    }
-                                }
-                            }
+                let mut stream = match rg.download(conversation_id, id, name, path.clone()).await {
+                    Ok(stream) => stream,
+                    Err(error) => {
+                        log::error!("Not possible to download attachment: {error}");
+                        download.set(Download::Failed(error.to_string()));
+                        return;
+                    }
+                };
+                while let Some(progress) = stream.next().await {
+                    match progress {
+                        Progression::CurrentProgress { current, total, .. } => {
+                            let percent = total
+                                .filter(|total| *total > 0)
+                                .map(|total| (((current as f64) / (total as f64)) * 100.) as usize);
+                            download.set(Download::Running(percent));
+                        }
+                        Progression::ProgressComplete { name, total } => {
+                            log::info!(
+                                "{name} downloaded with {} bytes written",
+                                total.unwrap_or_default()
+                            );
+                            download.set(Download::Done(path.clone()));
+                        }
+                        Progression::ProgressFailed { name, error, .. } => {
+                            log::error!("{name} failed to download with error: {error:?}");
+                            download.set(Download::Failed(
+                                error.unwrap_or_else(|| String::from("download failed")),
+                            ));
                         }
-                        Err(e) => println!("Error: {:?}", e),
-                    },
-                    None => {
-                        println!("Path not provided");
                     }
                 }
             }
         });
     };
 
+    let download_button = match download.get() {
+        Download::Idle => Some((Shape::DocumentArrowDown, "Download File")),
+        Download::Failed(_) => Some((Shape::ArrowPath, "Try Again")),
+        _ => None,
+    };
+
     cx.render(rsx! {
         div {
             class: "attachment-embed",
-            div {
-                class: "embed-icon",
-                Icon {
-                    icon: Shape::Document,
-                },
-                h2 {
-                    "{name}"
+            (**preview).then(|| rsx!(
+                FilePreview {
+                    items: preview_items,
+                    start: preview_start,
+                    on_close: move |_| preview.set(false),
                 }
-            }
+            )),
+            match &inline {
+                Some(url) => rsx!(
+                    div {
+                        class: "embed-image",
+                        title: "{name}",
+                        onclick: move |evt| {
+                            evt.cancel_bubble();
+                            preview.set(true);
+                        },
+                        img {
+                            src: "{url}",
+                            alt: "{name}",
+                        }
+                    }
+                ),
+                None => rsx!(
+                    div {
+                        class: "embed-icon",
+                        title: "Preview",
+                        onclick: move |evt| {
+                            evt.cancel_bubble();
+                            preview.set(true);
+                        },
+                        Icon {
+                            icon: Shape::Document,
+                        },
+                        h2 {
+                            "{name}"
+                        }
+                    }
+                ),
+            },
             div {
                 class: "embed-details",
                 p {
                     "{size}"
                 },
-                button::Button {
-                    icon: Shape::DocumentArrowDown,
-                    text: String::from("Download File"),
-                    state: button::State::Secondary,
-                    on_pressed: handle_click
-                }
+                match download.get() {
+                    Download::Idle => rsx!(Fragment {}),
+                    Download::Running(percent) => {
+                        let (ring, label) = match percent {
+                            Some(percent) => (format!("--progress: {percent}%"), format!("{percent}%")),
+                            None => (String::new(), String::from("Starting…")),
+                        };
+                        rsx!(
+                            div {
+                                class: "download-progress",
+                                div {
+                                    class: "progress-ring",
+                                    style: "{ring}",
+                                },
+                                span {
+                                    "Downloading {label}"
+                                }
+                            }
+                        )
+                    }
+                    Download::Done(path) => {
+                        let file = path.clone();
+                        let folder = path.parent().map(|parent| parent.to_path_buf()).unwrap_or_default();
+                        rsx!(
+                            div {
+                                class: "download-done",
+                                button::Button {
+                                    icon: Shape::Eye,
+                                    text: String::from("Open"),
+                                    state: button::State::Secondary,
+                                    on_pressed: move |evt: MouseEvent| {
+                                        evt.cancel_bubble();
+                                        if let Err(error) = opener::open(&file) {
+                                            log::error!("Not possible to open {:?}: {error}", file);
+                                        }
+                                    }
+                                },
+                                button::Button {
+                                    icon: Shape::FolderOpen,
+                                    text: String::from("Show in Folder"),
+                                    state: button::State::Secondary,
+                                    on_pressed: move |evt: MouseEvent| {
+                                        evt.cancel_bubble();
+                                        if let Err(error) = opener::open(&folder) {
+                                            log::error!("Not possible to open {:?}: {error}", folder);
+                                        }
+                                    }
+                                },
+                            }
+                        )
+                    }
+                    Download::Failed(error) => rsx!(
+                        p {
+                            class: "download-failed",
+                            "Download failed: {error}"
+                        }
+                    ),
+                },
+                download_button.map(|(icon, text)| rsx!(
+                    button::Button {
+                        icon: icon,
+                        text: String::from(text),
+                        state: button::State::Secondary,
+                        on_pressed: handle_click
+                    }
+                )),
             }
         }
     })
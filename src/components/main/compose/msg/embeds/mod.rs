@@ -1,8 +1,8 @@
-use crate::iutils::get_meta::SiteMeta;
 use dioxus::prelude::*;
 use dioxus_heroicons::outline::Shape;
 use open;
 use ui_kit::button;
+use utils::link_preview::SiteMeta;
 
 // Remember: owned props must implement PartialEq!
 #[derive(PartialEq, Eq, Props)]
@@ -10,10 +10,17 @@ pub struct Props {
     meta: SiteMeta,
 }
 
+// Only images the preview service fetched and embedded are shown, the webview never loads one itself
+fn embedded(src: &str) -> Option<String> {
+    src.starts_with("data:image/").then(|| src.to_string())
+}
+
 #[allow(non_snake_case)]
 pub fn LinkEmbed(cx: Scope<Props>) -> Element {
     // Log a message to the debug output
     log::debug!("rendering LinkEmbed");
+    let icon = embedded(&cx.props.meta.icon);
+    let image = embedded(&cx.props.meta.image);
     cx.render(rsx! {
         // Check if the title is empty
         if cx.props.meta.title.is_empty() {
@@ -26,15 +33,33 @@ pub fn LinkEmbed(cx: Scope<Props>) -> Element {
                     div {
                         // The first div element has the class "embed-icon"
                         class: "embed-icon",
-                        // It contains an img element with the website's icon
-                        img {
-                            src: "{cx.props.meta.icon}"
-                        },
-                        // It also contains an h2 element with the website's title
-                        h2 {
-                            "{cx.props.meta.title}"
+                        // It contains an img element with the website's icon, if there is one
+                        icon.map(|icon| rsx!(
+                            img {
+                                src: "{icon}"
+                            }
+                        )),
+                        div {
+                            // The site name is shown above the title when the page provides one
+                            (!cx.props.meta.site_name.is_empty()).then(|| rsx!(
+                                span {
+                                    class: "embed-site-name",
+                                    "{cx.props.meta.site_name}"
+                                }
+                            )),
+                            // It also contains an h2 element with the website's title
+                            h2 {
+                                "{cx.props.meta.title}"
+                            }
                         }
                     }
+                    // The preview image from the OpenGraph or Twitter card, if there is one
+                    image.map(|image| rsx!(
+                        img {
+                            class: "embed-image",
+                            src: "{image}"
+                        }
+                    )),
                     div {
                         // The second div element has the class "embed-details"
                         class: "embed-details",
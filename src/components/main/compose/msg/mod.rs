@@ -1,8 +1,8 @@
 use dioxus::prelude::*;
 use dioxus_heroicons::outline::Shape;
+use dioxus_toast::ToastInfo;
 use embeds::LinkEmbed;
 use linkify::LinkFinder;
-use pulldown_cmark::{html, Options, Parser};
 
 use state::{Actions, STATE};
 use ui_kit::{
@@ -10,19 +10,19 @@ use ui_kit::{
     context_menu::{ContextItem, ContextMenu},
     profile_picture::PFP,
 };
-use utils::Account;
+use utils::{
+    link_preview::{self, SiteMeta},
+    markdown, Account,
+};
 use warp::{crypto::DID, raygun::Message};
 
 use crate::{
-    components::reusable::textarea::TextArea,
-    iutils::{
-        self,
-        get_meta::{get_meta, SiteMeta},
-    },
-    Messaging, LANGUAGE,
+    components::{main::files::sharing, reusable::textarea::TextArea},
+    iutils::{self, blocked, config::LinkPreviewPolicy},
+    Messaging, LANGUAGE, PROFILE_MODAL, TOAST_MANAGER,
 };
 
-mod attachment;
+pub mod attachment;
 pub mod embeds;
 use attachment::Attachment;
 
@@ -33,6 +33,7 @@ pub struct Props<'a> {
     account: Account,
     sender: DID,
     remote: bool,
+    link_previews: LinkPreviewPolicy,
     first: bool,
     middle: bool,
     last: bool,
@@ -43,17 +44,20 @@ pub struct Props<'a> {
 #[allow(non_snake_case)]
 pub fn Msg<'a>(cx: Scope<'a, Props>) -> Element<'a> {
     log::debug!("rendering compose/Msg");
-    let state = use_atom_ref(&cx, STATE).clone();
+    let state = use_atom_ref(&cx, STATE);
+    let toast = use_atom_ref(&cx, TOAST_MANAGER);
+    let profile_modal = use_atom_ref(&cx, PROFILE_MODAL);
     let finder = LinkFinder::new();
-    let content = cx.props.message.value();
+    let content = sharing::visible_lines(&cx.props.message);
     let attachments = cx.props.message.attachments();
     let joined_a = content.join("\n");
     let joined_b = joined_a.clone();
     let has_links = finder.links(&joined_b).next().is_some();
+    let fetch_previews = cx.props.link_previews.allows(cx.props.remote);
 
     // Parses links and grabs data like the title, favicon and description
     let fetch_meta = use_future(&cx, &joined_a, |content| async move {
-        if has_links {
+        if has_links && fetch_previews {
             let s = content.as_str();
 
             let links: Vec<_> = finder.links(s).collect();
@@ -62,7 +66,7 @@ pub fn Msg<'a>(cx: Scope<'a, Props>) -> Element<'a> {
                 Some(l) => l.as_str(),
                 None => "",
             };
-            get_meta(first_link).await
+            link_preview::instance().get(first_link).await
         } else {
             Ok(SiteMeta::default())
         }
@@ -79,7 +83,7 @@ pub fn Msg<'a>(cx: Scope<'a, Props>) -> Element<'a> {
     // text has been lifted from the child components into Msg so that
     // a button press can be used to clear it.
     let text = use_state(&cx, String::new);
-    let value = cx.props.message.clone().value().join("\n");
+    let value = content.join("\n");
 
     let timestamp = cx.props.message.clone().date();
     let ht = iutils::display_msg_time(timestamp);
@@ -119,15 +123,14 @@ pub fn Msg<'a>(cx: Scope<'a, Props>) -> Element<'a> {
     let profile_picture2 = cx.props.profile_picture.clone();
     let profile_picture3 = cx.props.profile_picture.clone();
 
-    // Set up options and parser. Strikethroughs are not part of the CommonMark standard
-    // and we therefore must enable it explicitly.
-    let mut options = Options::empty();
-    options.insert(Options::ENABLE_STRIKETHROUGH);
-    let parser = Parser::new_ext(&value, options);
-
-    // Write to String buffer.
-    let mut html_output: String = String::with_capacity(value.len() * 3 / 2);
-    html::push_html(&mut html_output, parser);
+    // mentions of the local user are highlighted
+    let my_username = cx
+        .props
+        .account
+        .get_own_identity()
+        .map(|i| i.username())
+        .unwrap_or_default();
+    let html_output = markdown::render_message(&value, &my_username);
 
     let (output1, output2, output3) = (
         html_output.clone(),
@@ -261,6 +264,22 @@ pub fn Msg<'a>(cx: Scope<'a, Props>) -> Element<'a> {
                                 text: String::from("Remove Friend"),
                                 danger: true,
                                 icon: Shape::XCircle,
+                            },
+                            ContextItem {
+                                onpressed: move |_| {
+                                    match blocked::block(&cx.props.account, &cx.props.sender, "") {
+                                        Ok(_) => {
+                                            toast.write().popup(ToastInfo::simple("Blocked. They're listed under Friends, Blocked."));
+                                        }
+                                        Err(e) => {
+                                            log::error!("failed to block {}: {}", &cx.props.sender, e);
+                                            toast.write().popup(ToastInfo::simple("Not possible to block them."));
+                                        }
+                                    }
+                                },
+                                text: String::from("Block User"),
+                                danger: true,
+                                icon: Shape::NoSymbol,
                             }
                         }} else {rsx!{
                             ContextItem {
@@ -297,14 +316,18 @@ pub fn Msg<'a>(cx: Scope<'a, Props>) -> Element<'a> {
                                         parent: format!("{}-pfp-message", id),
                                         items: cx.render(rsx! {
                                             ContextItem {
-                                                onpressed: move |_| {},
+                                                onpressed: move |_| *profile_modal.write() = Some(cx.props.sender.clone()),
                                                 text: String::from("View Profile"),
                                             },
                                         })
                                     },
-                                    PFP {
-                                        src: profile_picture2,
-                                        size: ui_kit::profile_picture::Size::Normal
+                                    span {
+                                        class: "pfp-button",
+                                        onclick: move |_| *profile_modal.write() = Some(cx.props.sender.clone()),
+                                        PFP {
+                                            src: profile_picture2,
+                                            size: ui_kit::profile_picture::Size::Normal
+                                        }
                                     }
                                 }
                             )
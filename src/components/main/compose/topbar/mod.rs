@@ -2,7 +2,7 @@ use crate::{
     components::reusable::toolbar,
     iutils::{self, config::Config},
     state::Actions,
-    STATE,
+    PROFILE_MODAL, STATE,
 };
 
 use dioxus::prelude::*;
@@ -26,6 +26,7 @@ pub struct Props<'a> {
 pub fn TopBar<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
     log::debug!("rendering compose/TopBar");
     let state = use_atom_ref(&cx, STATE);
+    let profile_modal = use_atom_ref(&cx, PROFILE_MODAL);
     let config = Config::load_config_or_default();
     let mut favorites = state.read().favorites.clone();
 
@@ -44,6 +45,7 @@ pub fn TopBar<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
             let (display_did, display_username) =
                 iutils::get_username_from_conversation(conversation_info, &mp);
             let profile_picture = iutils::get_pfp_from_did(display_did.clone(), &mp);
+            let profile_did = display_did.clone();
 
             let id = conversation_info.conversation.id();
 
@@ -95,7 +97,7 @@ pub fn TopBar<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
                             parent: String::from("pfp-topbar"),
                             items: cx.render(rsx! {
                                 ContextItem {
-                                    onpressed: move |_| {},
+                                    onpressed: move |_| *profile_modal.write() = Some(profile_did.clone()),
                                     text: String::from("View Profile"),
                                 },
                             })
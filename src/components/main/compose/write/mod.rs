@@ -1,5 +1,7 @@
 use crate::{
-    components::reusable::textarea::TextArea, iutils::config::Config, Messaging, LANGUAGE,
+    components::reusable::textarea::TextArea,
+    iutils::{self, config::Config, mentions},
+    Account, Messaging, LANGUAGE,
 };
 use audio_factory::AudioFactory;
 use dioxus::prelude::*;
@@ -15,9 +17,12 @@ use utils::extensions::{get_renders, BasicExtension, ExtensionType};
 
 #[derive(Props)]
 pub struct Props<'a> {
+    account: Account,
     messaging: Messaging,
     on_submit: EventHandler<'a, String>,
     on_upload: EventHandler<'a, ()>,
+    on_attach_from_files: EventHandler<'a, ()>,
+    on_paste: EventHandler<'a, ()>,
 }
 
 #[allow(non_snake_case)]
@@ -29,6 +34,32 @@ pub fn Write<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
     let l = use_atom_ref(&cx, LANGUAGE).read();
     let state = use_atom_ref(&cx, STATE).read();
     let ext_enabled = state.enabled_extensions.clone();
+    let set_text_script = include_str!("./set_text.js");
+
+    // offer to complete `@` mentions with the other participants of the conversation
+    let my_did = cx
+        .props
+        .account
+        .get_own_identity()
+        .map(|i| i.did_key())
+        .unwrap_or_default();
+    let suggestions: Vec<String> = match mentions::mention_query(text.get()) {
+        Some((_, query)) => {
+            let query = query.to_lowercase();
+            state
+                .selected_chat
+                .and_then(|id| state.active_chats.get(&id))
+                .map(|info| info.conversation.recipients())
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|did| *did != my_did)
+                .map(|did| iutils::get_username_from_did(did, &cx.props.account))
+                .filter(|name| !name.is_empty() && name.to_lowercase().starts_with(&query))
+                .take(5)
+                .collect()
+        }
+        None => Vec::new(),
+    };
 
     let exts = get_renders(
         ExtensionType::ChatbarIcon,
@@ -40,6 +71,7 @@ pub fn Write<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
         div {
             class: "write",
             id: "write",
+            onpaste: move |_| cx.props.on_paste.call(()),
             ContextMenu {
                 parent: String::from("write"),
                 items: cx.render(rsx! {
@@ -51,12 +83,41 @@ pub fn Write<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
                 })
             },
             exts,
+            (!suggestions.is_empty()).then(|| rsx!(
+                div {
+                    class: "mention-suggestions",
+                    suggestions.iter().map(|name| {
+                        let name = name.clone();
+                        rsx!(
+                            div {
+                                key: "{name}",
+                                class: "mention-suggestion",
+                                onclick: move |_| {
+                                    if let Some((at, _)) = mentions::mention_query(text.get()) {
+                                        let completed = mentions::complete_mention(text.get(), at, &name);
+                                        // the textarea isn't driven by `text`, so the DOM has to be updated as well
+                                        let js_text = serde_json::to_string(&completed).unwrap_or_default();
+                                        use_eval(&cx)(&format!("var new_text = {};\n{}", js_text, set_text_script));
+                                        text.set(completed);
+                                    }
+                                },
+                                "@{name}"
+                            }
+                        )
+                    })
+                }
+            )),
             Button {
                 icon: Shape::Plus,
                 on_pressed: move |_| {
                     let _ = &cx.props.on_upload.call(());
                 },
             },
+            Button {
+                icon: Shape::FolderOpen,
+                state: button::State::Secondary,
+                on_pressed: move |_| cx.props.on_attach_from_files.call(()),
+            },
             TextArea {
                 messaging: cx.props.messaging.clone(),
                 on_input: move |_| {}
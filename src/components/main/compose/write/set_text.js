@@ -0,0 +1,11 @@
+var input_element = document.querySelector("#write .dynamic-input")
+input_element.innerText = new_text
+input_element.focus()
+
+// move the caret to the end of the completed mention
+var range = document.createRange()
+range.selectNodeContents(input_element)
+range.collapse(false)
+var selection = window.getSelection()
+selection.removeAllRanges()
+selection.addRange(range)
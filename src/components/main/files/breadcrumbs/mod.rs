@@ -0,0 +1,42 @@
+use dioxus::prelude::*;
+use dioxus_heroicons::{outline::Shape, Icon};
+
+#[derive(Props)]
+pub struct Props<'a> {
+    path: Vec<String>,
+    on_navigate: EventHandler<'a, Vec<String>>,
+}
+
+#[allow(non_snake_case)]
+pub fn Breadcrumbs<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
+    let last = cx.props.path.len();
+
+    cx.render(rsx! {
+        div {
+            id: "breadcrumbs",
+            span {
+                class: "crumb",
+                onclick: move |_| cx.props.on_navigate.call(Vec::new()),
+                Icon {
+                    icon: Shape::Home,
+                    size: 16,
+                },
+                "Files"
+            },
+            cx.props.path.iter().enumerate().map(|(index, name)| {
+                let target = cx.props.path[..=index].to_vec();
+                let key = target.join("/");
+                let current = if index + 1 == last { "current" } else { "" };
+                rsx!(
+                    span {
+                        key: "{key}",
+                        class: "crumb {current}",
+                        onclick: move |_| cx.props.on_navigate.call(target.clone()),
+                        span { class: "separator", "/" },
+                        "{name}"
+                    }
+                )
+            })
+        }
+    })
+}
@@ -1,30 +1,136 @@
-use std::{collections::HashSet, time::Duration};
+use std::{cell::Cell, collections::HashSet, rc::Rc, time::Duration};
 
-use dioxus::prelude::*;
+use dioxus::{
+    core::to_owned,
+    desktop::use_window,
+    events::{KeyCode, MouseData},
+    prelude::*,
+};
+use dioxus_heroicons::outline::Shape;
+use rfd::FileDialog;
 
-use crate::Storage;
-use ui_kit::{file::File, folder::State, new_folder::NewFolder};
-use warp::constellation::item::ItemType;
+use crate::{
+    components::{
+        main::files::{
+            directory_at, notify_failures,
+            operations::{copy_items, delete_items, download_items, switch_path, Progress},
+            sharing::{self, dialog::ShareDialog},
+            thumbnails, DraggedItems, COPIED_ITEMS, DRAGGED_ITEMS,
+        },
+        reusable::file_preview::{FilePreview, PreviewItem, PreviewSource},
+    },
+    Messaging, Storage, TOAST_MANAGER,
+};
+use ui_kit::{
+    button::Button,
+    file::File,
+    folder::{Folder, State},
+    new_folder::NewFolder,
+};
+use warp::{constellation::item::ItemType, crypto::DID};
+
+// how long a delete can be undone for before it goes through
+const UNDO_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, PartialEq)]
+enum DeleteState {
+    Waiting,
+    Undone,
+    Deleting,
+}
 
-#[derive(Props, PartialEq)]
-pub struct Props {
+// items which look deleted while they can still be undeleted, and then until they're gone
+struct PendingDelete {
+    path: Vec<String>,
+    names: Vec<String>,
+    state: Rc<Cell<DeleteState>>,
+}
+
+#[derive(Props)]
+pub struct Props<'a> {
     account: crate::Account,
     storage: Storage,
+    messaging: Messaging,
+    path: Vec<String>,
     show_new_folder: bool,
+    progress: UseRef<Option<Progress>>,
+    on_navigate: EventHandler<'a, Vec<String>>,
+    on_new_folder_closed: EventHandler<'a, ()>,
+    on_drop: EventHandler<'a, Vec<String>>,
+}
+
+// click: select only this item, ctrl/cmd click: add or remove it, shift click: select everything between
+// the last item clicked and this one
+fn select(
+    selected: &UseRef<HashSet<String>>,
+    anchor: &UseRef<Option<usize>>,
+    names: &[String],
+    index: usize,
+    evt: &MouseData,
+) {
+    let toggle = evt.ctrl_key || evt.meta_key;
+    let range_start = (*anchor.read()).filter(|_| evt.shift_key);
+    let mut selected = selected.write();
+    match range_start {
+        Some(range_start) => {
+            if !toggle {
+                selected.clear();
+            }
+            let range_start = range_start.min(names.len() - 1);
+            let (first, last) = (range_start.min(index), range_start.max(index));
+            selected.extend(names[first..=last].iter().cloned());
+            return;
+        }
+        None if toggle => {
+            if !selected.remove(&names[index]) {
+                selected.insert(names[index].clone());
+            }
+        }
+        None => {
+            selected.clear();
+            selected.insert(names[index].clone());
+        }
+    }
+    *anchor.write() = Some(index);
 }
 
 #[allow(non_snake_case)]
-pub fn FileBrowser(cx: Scope<Props>) -> Element {
+pub fn FileBrowser<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
     let file_storage = cx.props.storage.clone();
     let files = use_ref(&cx, HashSet::new);
     let files_sorted = use_state(&cx, Vec::new);
+    let selected = use_ref(&cx, HashSet::<String>::new);
+    let anchor = use_ref(&cx, || None::<usize>);
+    let shown_path = use_ref(&cx, || cx.props.path.clone());
+    let pending_deletes = use_ref(&cx, Vec::<PendingDelete>::new);
+    // the file being previewed, by its position among the files shown
+    let preview = use_state(&cx, || None::<usize>);
+    // the items the share dialog is open for
+    let share_dialog = use_state(&cx, || None::<Vec<String>>);
+    let toast = use_atom_ref(&cx, TOAST_MANAGER);
+
+    let window = use_window(&cx);
+    cx.use_hook(|_| window.eval(include_str!("./rubber_band.js")));
+
+    // the selection belongs to the folder it was made in
+    if *shown_path.read() != cx.props.path {
+        selected.write_silent().clear();
+        *anchor.write_silent() = None;
+        *shown_path.write_silent() = cx.props.path.clone();
+    }
 
     use_future(
         &cx,
-        (files, files_sorted, &file_storage.root_directory()),
-        |(files, files_sorted, root_directory)| async move {
+        (files, files_sorted, &file_storage, &cx.props.path),
+        |(files, files_sorted, file_storage, path)| async move {
             loop {
-                let files_updated: HashSet<_> = HashSet::from_iter(root_directory.get_items());
+                let files_updated: HashSet<_> = match directory_at(&file_storage, &path) {
+                    Ok(directory) => HashSet::from_iter(directory.get_items()),
+                    Err(error) => {
+                        log::debug!("Not possible to open {:?}: {:?}", path, error);
+                        HashSet::new()
+                    }
+                };
 
                 if *files.read() != files_updated {
                     log::debug!("updating files list");
@@ -39,18 +145,368 @@ pub fn FileBrowser(cx: Scope<Props>) -> Element {
         },
     );
 
+    let hidden: HashSet<String> = pending_deletes
+        .read()
+        .iter()
+        .filter(|pending| pending.path == cx.props.path)
+        .flat_map(|pending| pending.names.clone())
+        .collect();
+    let folders: Vec<_> = files_sorted
+        .iter()
+        .filter(|item| {
+            item.item_type() == ItemType::DirectoryItem && !hidden.contains(&item.name())
+        })
+        .cloned()
+        .collect();
+    let file_items: Vec<_> = files_sorted
+        .iter()
+        .filter(|item| item.item_type() == ItemType::FileItem && !hidden.contains(&item.name()))
+        .cloned()
+        .collect();
+    // in the order they're shown, which shift click ranges follow
+    let names: Rc<Vec<String>> = Rc::new(
+        folders
+            .iter()
+            .chain(file_items.iter())
+            .map(|item| item.name())
+            .collect(),
+    );
+    let selection: Vec<String> = names
+        .iter()
+        .filter(|name| selected.read().contains(*name))
+        .cloned()
+        .collect();
+
+    let delete = move |names: Vec<String>| {
+        if names.is_empty() {
+            return;
+        }
+        let state = Rc::new(Cell::new(DeleteState::Waiting));
+        pending_deletes.write().push(PendingDelete {
+            path: cx.props.path.clone(),
+            names: names.clone(),
+            state: state.clone(),
+        });
+        selected.write().clear();
+
+        let storage = cx.props.storage.clone();
+        let path = cx.props.path.clone();
+        let progress = cx.props.progress.clone();
+        to_owned![pending_deletes, toast];
+        // the delete still goes through when the user leaves the page before it does
+        cx.spawn_forever(async move {
+            tokio::time::sleep(UNDO_DELAY).await;
+            if state.get() == DeleteState::Waiting {
+                state.set(DeleteState::Deleting);
+                // hides the undo toast
+                pending_deletes.needs_update();
+                let failed = delete_items(storage, path, names, progress).await;
+                notify_failures(&toast, "delete", &failed);
+            }
+            pending_deletes
+                .write()
+                .retain(|pending| !Rc::ptr_eq(&pending.state, &state));
+        });
+    };
+
+    let copy = move |names: Vec<String>, to: Vec<String>| {
+        let storage = cx.props.storage.clone();
+        let from = cx.props.path.clone();
+        let progress = cx.props.progress.clone();
+        let toast = toast.clone();
+        cx.spawn(async move {
+            let failed = copy_items(storage, from, names, to, progress).await;
+            notify_failures(&toast, "copy", &failed);
+        });
+    };
+
+    let download = move |names: Vec<String>| {
+        let storage = cx.props.storage.clone();
+        let from = cx.props.path.clone();
+        let progress = cx.props.progress.clone();
+        let toast = toast.clone();
+        cx.spawn(async move {
+            let destination = match FileDialog::new().set_directory(".").pick_folder() {
+                Some(path) => path,
+                None => return,
+            };
+            let failed = download_items(storage, from, names, destination, progress).await;
+            notify_failures(&toast, "download", &failed);
+        });
+    };
+
+    let rename = move |name: String, new_name: String| {
+        let storage = cx.props.storage.clone();
+        let path = cx.props.path.clone();
+        let toast = toast.clone();
+        cx.spawn(async move {
+            let switched = switch_path(&storage, &path).await;
+            let result = storage.rename(&name, &new_name).await;
+            drop(switched);
+            match result {
+                Ok(_) => log::info!("{name} renamed to {new_name}"),
+                Err(error) => {
+                    log::error!("Error renaming {name}: {error}");
+                    notify_failures(&toast, "rename", &[name]);
+                }
+            }
+        });
+    };
+
+    // every item is shared on its own, so a folder arrives as a folder
+    let share = move |names: Vec<String>, friends: Vec<DID>| {
+        share_dialog.set(None);
+        let storage = cx.props.storage.clone();
+        let messaging = cx.props.messaging.clone();
+        let path = cx.props.path.clone();
+        let progress = cx.props.progress.clone();
+        let toast = toast.clone();
+        let account = cx.props.account.clone();
+        cx.spawn(async move {
+            let mut failed = Vec::new();
+            for name in names {
+                let result = sharing::share(
+                    storage.clone(),
+                    messaging.clone(),
+                    path.clone(),
+                    name.clone(),
+                    friends.clone(),
+                    progress.clone(),
+                )
+                .await;
+                match result {
+                    Ok(not_shared) if not_shared.is_empty() => {}
+                    Ok(not_shared) => {
+                        let friends: Vec<String> = not_shared
+                            .into_iter()
+                            .map(|did| crate::iutils::get_username_from_did(did, &account))
+                            .collect();
+                        failed.push(format!("{name} with {}", friends.join(", ")));
+                    }
+                    Err(error) => {
+                        log::error!("Not possible to share {name}: {error}");
+                        failed.push(name);
+                    }
+                }
+            }
+            notify_failures(&toast, "share", &failed);
+        });
+    };
+
+    let copy_to_clipboard = move |names: Vec<String>| {
+        *COPIED_ITEMS.write() = Some(DraggedItems {
+            from: cx.props.path.clone(),
+            names,
+        });
+        cx.needs_update();
+    };
+
+    // dragging an item that's part of the selection drags the whole selection
+    let start_drag = move |name: String, selection: &[String]| {
+        let names = match selection.contains(&name) {
+            true => selection.to_vec(),
+            false => vec![name],
+        };
+        *DRAGGED_ITEMS.write() = Some(DraggedItems {
+            from: cx.props.path.clone(),
+            names,
+        });
+    };
+
+    let copied = COPIED_ITEMS.read().clone();
+    let selection_count = selection.len();
+    let pending_count = pending_deletes
+        .read()
+        .iter()
+        .rev()
+        .find(|pending| pending.state.get() == DeleteState::Waiting)
+        .map(|pending| pending.names.len())
+        .unwrap_or_default();
+    let preview_items: Vec<PreviewItem> = file_items
+        .iter()
+        .map(|file| PreviewItem {
+            name: file.name(),
+            size: file.size(),
+            source: PreviewSource::Constellation {
+                storage: cx.props.storage.clone(),
+                path: cx.props.path.clone(),
+            },
+        })
+        .collect();
+    let keyboard_selection = selection.clone();
+    let all_names = names.clone();
+
     cx.render(rsx! {
+        (selection_count > 0 || copied.is_some()).then(|| {
+            let selection = selection.clone();
+            rsx!(
+                div {
+                    class: "selection-bar",
+                    (selection_count > 0).then(|| rsx!(
+                        span {
+                            class: "count",
+                            "{selection_count} selected"
+                        },
+                        Button {
+                            icon: Shape::DocumentArrowDown,
+                            text: String::from("Download"),
+                            state: ui_kit::button::State::Secondary,
+                            on_pressed: {
+                                let selection = selection.clone();
+                                move |_| download(selection.clone())
+                            },
+                        },
+                        Button {
+                            icon: Shape::ClipboardDocument,
+                            text: String::from("Copy"),
+                            state: ui_kit::button::State::Secondary,
+                            on_pressed: {
+                                let selection = selection.clone();
+                                move |_| copy_to_clipboard(selection.clone())
+                            },
+                        },
+                        Button {
+                            icon: Shape::DocumentDuplicate,
+                            text: String::from("Duplicate"),
+                            state: ui_kit::button::State::Secondary,
+                            on_pressed: {
+                                let selection = selection.clone();
+                                move |_| copy(selection.clone(), cx.props.path.clone())
+                            },
+                        },
+                        Button {
+                            icon: Shape::Share,
+                            text: String::from("Share"),
+                            state: ui_kit::button::State::Secondary,
+                            on_pressed: {
+                                let selection = selection.clone();
+                                move |_| share_dialog.set(Some(selection.clone()))
+                            },
+                        },
+                        Button {
+                            icon: Shape::Trash,
+                            text: String::from("Delete"),
+                            state: ui_kit::button::State::Danger,
+                            on_pressed: {
+                                let selection = selection.clone();
+                                move |_| delete(selection.clone())
+                            },
+                        },
+                    )),
+                    copied.as_ref().map(|copied| {
+                        let count = copied.names.len();
+                        rsx!(
+                            Button {
+                                icon: Shape::ClipboardDocumentList,
+                                text: format!("Paste {count}"),
+                                state: ui_kit::button::State::Secondary,
+                                on_pressed: move |_| {
+                                    if let Some(copied) = COPIED_ITEMS.write().take() {
+                                        let storage = cx.props.storage.clone();
+                                        let to = cx.props.path.clone();
+                                        let progress = cx.props.progress.clone();
+                                        let toast = toast.clone();
+                                        cx.spawn(async move {
+                                            let failed = copy_items(storage, copied.from, copied.names, to, progress).await;
+                                            notify_failures(&toast, "copy", &failed);
+                                        });
+                                    }
+                                },
+                            },
+                        )
+                    }),
+                    Button {
+                        icon: Shape::XMark,
+                        text: String::from("Clear"),
+                        state: ui_kit::button::State::Transparent,
+                        on_pressed: move |_| {
+                            selected.write().clear();
+                            *COPIED_ITEMS.write() = None;
+                        },
+                    },
+                }
+            )
+        }),
         div {
-         id: "browser",
+            id: "browser",
+            tabindex: "0",
+            // clicking empty space clears the selection
+            onclick: move |_| selected.write().clear(),
+            onkeydown: move |evt| match evt.key_code {
+                KeyCode::Delete | KeyCode::Backspace => delete(keyboard_selection.clone()),
+                KeyCode::Escape => selected.write().clear(),
+                KeyCode::A if evt.ctrl_key || evt.meta_key => {
+                    selected.write().extend(all_names.iter().cloned());
+                }
+                _ => {}
+            },
             (cx.props.show_new_folder).then(|| rsx!(
                 div {
                     class: "item file",
+                    // typing a name mustn't delete or select anything
+                    onkeydown: |evt| evt.cancel_bubble(),
                     NewFolder {
-                        state: State::Primary
+                        state: State::Primary,
+                        on_create: move |name: String| {
+                            let mut file_storage = cx.props.storage.clone();
+                            let path = cx.props.path.clone();
+                            cx.spawn(async move {
+                                let switched = switch_path(&file_storage, &path).await;
+                                let result = file_storage.create_directory(&name, true).await;
+                                drop(switched);
+                                match result {
+                                    Ok(_) => log::info!("{name} folder created."),
+                                    Err(error) => log::error!("Error creating folder {name}: {error}"),
+                                };
+                            });
+                            cx.props.on_new_folder_closed.call(());
+                        },
+                        on_cancel: move |_| cx.props.on_new_folder_closed.call(()),
                     }
                 }
             )),
-            files_sorted.iter().filter(|item| item.item_type() == ItemType::FileItem).map(|file| {
+            folders.iter().enumerate().map(|(index, folder)| {
+                let name = folder.name();
+                let key = folder.id();
+                let children = folder
+                    .get_directory()
+                    .map(|directory| directory.get_items().len() as u32)
+                    .unwrap_or_default();
+                let mut target = cx.props.path.clone();
+                target.push(name.clone());
+                let drop_target = target.clone();
+                let selected_class = if selected.read().contains(&name) { "selected" } else { "" };
+                let names = names.clone();
+                let selection = selection.clone();
+                let drag_name = name.clone();
+
+                rsx!(
+                    div {
+                        key: "{key}",
+                        class: "item folder-item selectable {selected_class}",
+                        draggable: "true",
+                        onclick: move |evt| {
+                            evt.cancel_bubble();
+                            select(selected, anchor, &names, index, &evt.data);
+                        },
+                        ondblclick: move |_| cx.props.on_navigate.call(target.clone()),
+                        // keys only reach here from the rename input, which handles them itself
+                        onkeydown: |evt| evt.cancel_bubble(),
+                        ondragstart: move |_| start_drag(drag_name.clone(), &selection),
+                        // dropping items on a folder moves them into it
+                        prevent_default: "ondragover",
+                        ondragover: |_| {},
+                        ondrop: move |_| cx.props.on_drop.call(drop_target.clone()),
+                        Folder {
+                            name: name,
+                            state: State::Secondary,
+                            children: children,
+                        }
+                    }
+                )
+            }),
+            file_items.iter().enumerate().map(|(file_index, file)| {
+                let index = folders.len() + file_index;
                 let file_extension = std::path::Path::new(&file.name())
                     .extension()
                     .unwrap_or_else(|| std::ffi::OsStr::new(""))
@@ -59,20 +515,88 @@ pub fn FileBrowser(cx: Scope<Props>) -> Element {
                     .to_string();
 
                 let key = file.id();
+                let name = file.name();
+                let selected_class = if selected.read().contains(&name) { "selected" } else { "" };
+                // actions from the context menu apply to the whole selection when the file is part of it
+                let targets = match selection.contains(&name) {
+                    true => selection.clone(),
+                    false => vec![name.clone()],
+                };
+                let (download_targets, delete_targets, copy_targets, duplicate_targets, share_targets) =
+                    (targets.clone(), targets.clone(), targets.clone(), targets.clone(), targets);
+                let rename_target = name.clone();
+                let names = names.clone();
+                let selection = selection.clone();
+                let drag_name = name.clone();
 
                 rsx!(
-                    File {
+                    div {
                         key: "{key}",
-                        name: file.name(),
-                        state: State::Secondary,
-                        id: key.to_string(),
-                        kind: file_extension,
-                        size: file.size(),
-                        thumbnail: file.thumbnail(),
-                        storage: cx.props.storage.clone(),
+                        class: "selectable {selected_class}",
+                        draggable: "true",
+                        onclick: move |evt| {
+                            evt.cancel_bubble();
+                            select(selected, anchor, &names, index, &evt.data);
+                        },
+                        ondblclick: move |_| preview.set(Some(file_index)),
+                        onkeydown: |evt| evt.cancel_bubble(),
+                        ondragstart: move |_| start_drag(drag_name.clone(), &selection),
+                        File {
+                            name: name,
+                            state: State::Secondary,
+                            id: key.to_string(),
+                            kind: file_extension,
+                            size: file.size(),
+                            thumbnail: thumbnails::instance().get(file),
+                            on_rename: move |new_name: String| rename(rename_target.clone(), new_name),
+                            on_download: move |_| download(download_targets.clone()),
+                            on_delete: move |_| delete(delete_targets.clone()),
+                            on_copy: move |_| copy_to_clipboard(copy_targets.clone()),
+                            on_duplicate: move |_| copy(duplicate_targets.clone(), cx.props.path.clone()),
+                            on_share: move |_| share_dialog.set(Some(share_targets.clone())),
+                        }
                     }
                 )
             })
-        }
+        },
+        share_dialog.get().as_ref().map(|names| {
+            let share_names = names.clone();
+            rsx!(
+                ShareDialog {
+                    account: cx.props.account.clone(),
+                    names: names.clone(),
+                    on_share: move |friends| share(share_names.clone(), friends),
+                    on_cancel: move |_| share_dialog.set(None),
+                }
+            )
+        }),
+        preview.map(|start| rsx!(
+            FilePreview {
+                items: preview_items,
+                start: start,
+                on_close: move |_| preview.set(None),
+            }
+        )),
+        (pending_count > 0).then(|| rsx!(
+            div {
+                class: "undo-toast",
+                span {
+                    "Deleted {pending_count} item(s)"
+                },
+                Button {
+                    text: String::from("Undo"),
+                    state: ui_kit::button::State::Secondary,
+                    on_pressed: move |_| {
+                        let mut pending_deletes = pending_deletes.write();
+                        let undone = pending_deletes
+                            .iter()
+                            .rposition(|pending| pending.state.get() == DeleteState::Waiting);
+                        if let Some(index) = undone {
+                            pending_deletes.remove(index).state.set(DeleteState::Undone);
+                        }
+                    },
+                },
+            }
+        )),
     })
 }
@@ -0,0 +1,69 @@
+// Rubber band selection for the file browser. Dragging across empty space draws a band and, on release,
+// every item it touches is ctrl-clicked, so the selection itself stays with the browser component.
+(function () {
+  if (window.uplinkRubberBand) return;
+  window.uplinkRubberBand = true;
+
+  let start = null;
+  let band = null;
+  let browser = null;
+  let swallowClick = false;
+
+  document.addEventListener("mousedown", (e) => {
+    browser = e.target.closest && e.target.closest("#browser");
+    if (!browser || e.button !== 0) return;
+    if (e.target.closest(".selectable, .selection-bar, .undo-toast, input, button")) return;
+    start = { x: e.clientX, y: e.clientY, additive: e.ctrlKey || e.metaKey || e.shiftKey };
+  });
+
+  document.addEventListener("mousemove", (e) => {
+    if (!start) return;
+    if (!band) {
+      if (Math.abs(e.clientX - start.x) + Math.abs(e.clientY - start.y) < 5) return;
+      band = document.createElement("div");
+      band.className = "rubber-band";
+      document.body.appendChild(band);
+    }
+    band.style.left = Math.min(start.x, e.clientX) + "px";
+    band.style.top = Math.min(start.y, e.clientY) + "px";
+    band.style.width = Math.abs(e.clientX - start.x) + "px";
+    band.style.height = Math.abs(e.clientY - start.y) + "px";
+  });
+
+  document.addEventListener("mouseup", () => {
+    if (!start) return;
+    const additive = start.additive;
+    start = null;
+    if (!band) return;
+
+    const area = band.getBoundingClientRect();
+    band.remove();
+    band = null;
+
+    // a plain click on empty space clears the selection
+    if (!additive) browser.dispatchEvent(new MouseEvent("click", { bubbles: true }));
+    browser.querySelectorAll(".selectable").forEach((item) => {
+      const r = item.getBoundingClientRect();
+      const touched = r.left < area.right && r.right > area.left && r.top < area.bottom && r.bottom > area.top;
+      // ctrl-click toggles, so items that are already part of the selection are left alone
+      if (touched && (!additive || !item.classList.contains("selected"))) {
+        item.dispatchEvent(new MouseEvent("click", { bubbles: true, ctrlKey: true }));
+      }
+    });
+
+    // the click that ends the band would clear the selection again
+    swallowClick = true;
+    setTimeout(() => (swallowClick = false), 0);
+  });
+
+  document.addEventListener(
+    "click",
+    (e) => {
+      if (swallowClick) {
+        swallowClick = false;
+        e.stopPropagation();
+      }
+    },
+    true
+  );
+})();
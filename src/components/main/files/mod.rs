@@ -1,12 +1,23 @@
-use dioxus::prelude::*;
+use std::{ffi::OsStr, path::PathBuf};
+
+use dioxus::{fermi::UseAtomRef, prelude::*};
+use dioxus_toast::{ToastInfo, ToastManager};
+use once_cell::sync::Lazy;
+use warp::{constellation::directory::Directory, error::Error, sync::RwLock};
 
 // use crate::components::main::files::sidebar::usage::{Usage, UsageStats};
 use crate::{
     components::main::files::{
-        browser::FileBrowser, sidebar::Sidebar, toolbar::Toolbar, upload::Upload,
+        breadcrumbs::Breadcrumbs,
+        browser::FileBrowser,
+        operations::{self, move_items, Progress},
+        sharing::shares::SharesPanel,
+        sidebar::Sidebar,
+        toolbar::Toolbar,
+        upload::{queue::UploadQueue, Upload},
     },
     components::reusable::nav::Nav,
-    STATE,
+    Storage, STATE, TOAST_MANAGER,
 };
 
 #[cfg(target_os = "windows")]
@@ -14,11 +25,37 @@ use crate::DRAG_FILE_EVENT;
 #[cfg(target_os = "windows")]
 use dioxus::desktop::wry::webview::FileDropEvent;
 
+pub mod breadcrumbs;
 pub mod browser;
+pub mod operations;
+pub mod sharing;
 pub mod sidebar;
+pub mod thumbnails;
 pub mod toolbar;
 pub mod upload;
 
+// items picked up in the browser, until they're dropped on a folder
+#[derive(Clone)]
+pub struct DraggedItems {
+    pub from: Vec<String>,
+    pub names: Vec<String>,
+}
+
+static DRAGGED_ITEMS: Lazy<RwLock<Option<DraggedItems>>> = Lazy::new(|| RwLock::new(None));
+
+// items copied in the browser, which can be pasted into any folder
+static COPIED_ITEMS: Lazy<RwLock<Option<DraggedItems>>> = Lazy::new(|| RwLock::new(None));
+
+/// forgets what the Files page keeps between visits, which belongs to the profile being closed. Uploads
+/// still queued would otherwise go on with its storage.
+pub fn reset() {
+    *DRAGGED_ITEMS.write() = None;
+    *COPIED_ITEMS.write() = None;
+    operations::forget_shown_path();
+    upload::manager::instance().clear();
+    sharing::invalidate();
+}
+
 #[derive(Props, PartialEq)]
 pub struct Props {
     account: crate::Account,
@@ -26,10 +63,73 @@ pub struct Props {
     messaging: crate::Messaging,
 }
 
+// Constellation resolves names (renames, new folders...) relative to its current path, so the directory
+// being browsed is stored there. `path` is the list of folder names leading to it from the root.
+fn navigate(storage: &Storage, current_path: &UseState<Vec<String>>, path: Vec<String>) {
+    operations::show_path(storage, &path);
+    current_path.set(path);
+}
+
+/// looks up the directory at `path` starting from the root
+pub fn directory_at(storage: &Storage, path: &[String]) -> Result<Directory, Error> {
+    let mut directory = storage.root_directory();
+    for name in path {
+        directory = directory.get_item(name)?.get_directory()?;
+    }
+    Ok(directory)
+}
+
+/// returns `name` if `taken` says it's free, otherwise the first free name out of `name (1)`,
+/// `name (2)`... with the extension kept at the end.
+pub fn unique_name(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    let file = PathBuf::from(name);
+    let file_stem = file.file_stem().and_then(OsStr::to_str);
+    let file_extension = file.extension().and_then(OsStr::to_str);
+
+    let mut candidate = name.to_string();
+    let mut count_index_for_duplicate_filename = 1;
+    while taken(&candidate) {
+        candidate = match (file_stem, file_extension) {
+            (Some(file_stem), Some(file_extension)) => {
+                format!("{file_stem} ({count_index_for_duplicate_filename}).{file_extension}")
+            }
+            _ => format!("{name} ({count_index_for_duplicate_filename})"),
+        };
+        count_index_for_duplicate_filename += 1;
+    }
+    candidate
+}
+
+/// tells the user which items an operation couldn't handle
+pub fn notify_failures(toast: &UseAtomRef<ToastManager>, action: &str, failed: &[String]) {
+    let message = match failed {
+        [] => return,
+        [name] => format!("Couldn't {action} {name}"),
+        _ => format!("Couldn't {action} {} items", failed.len()),
+    };
+    toast.write().popup(ToastInfo::simple(&message));
+}
+
 #[allow(non_snake_case)]
 pub fn Files(cx: Scope<Props>) -> Element {
     let show_new_folder = use_state(&cx, || false);
     let show_upload = use_state(&cx, || false);
+    let current_path = use_state(&cx, operations::shown_path);
+    let progress = use_ref(&cx, || None::<Progress>);
+    let toast = use_atom_ref(&cx, TOAST_MANAGER);
+
+    // moves whatever is being dragged into the folder at `to`
+    let drop_into = move |to: Vec<String>| {
+        if let Some(dragged) = DRAGGED_ITEMS.write().take() {
+            let storage = cx.props.storage.clone();
+            let progress = progress.clone();
+            let toast = toast.clone();
+            cx.spawn(async move {
+                let failed = move_items(storage, dragged.from, dragged.names, to, progress).await;
+                notify_failures(&toast, "move", &failed);
+            });
+        }
+    };
 
     let st = use_atom_ref(&cx, STATE).clone();
     let sidebar_visibility = match st.read().hide_sidebar {
@@ -37,6 +137,8 @@ pub fn Files(cx: Scope<Props>) -> Element {
         true => "sidebar-hidden",
     };
 
+    let current_progress = progress.read().clone();
+
     cx.render(rsx! {
         div {
             id: "files",
@@ -49,13 +151,24 @@ pub fn Files(cx: Scope<Props>) -> Element {
                 }
             },
             class: "{sidebar_visibility}",
-            Sidebar { account: cx.props.account.clone(), messaging: cx.props.messaging.clone() },
+            Sidebar {
+                account: cx.props.account.clone(),
+                messaging: cx.props.messaging.clone(),
+                storage: cx.props.storage.clone(),
+                path: current_path.get().clone(),
+                on_navigate: move |path| {
+                    show_new_folder.set(false);
+                    navigate(&cx.props.storage, current_path, path);
+                },
+                on_drop: move |path| drop_into(path),
+            },
             div {
                 id: "content",
                 rsx!(
                     div {
                         class: "flex-row top-container",
                         Toolbar {
+                            storage: cx.props.storage.clone(),
                             on_new_folder: move |_| {
                                 show_new_folder.set(true);
                             },
@@ -69,11 +182,51 @@ pub fn Files(cx: Scope<Props>) -> Element {
                             on_hide: move |_| show_upload.set(false),
                         },
                     },
+                    Breadcrumbs {
+                        path: current_path.get().clone(),
+                        on_navigate: move |path| {
+                            show_new_folder.set(false);
+                            navigate(&cx.props.storage, current_path, path);
+                        },
+                    },
                     FileBrowser {
                         account: cx.props.account.clone(),
                         storage: cx.props.storage.clone(),
-                        show_new_folder: **show_new_folder
+                        messaging: cx.props.messaging.clone(),
+                        path: current_path.get().clone(),
+                        show_new_folder: **show_new_folder,
+                        on_navigate: move |path| {
+                            show_new_folder.set(false);
+                            navigate(&cx.props.storage, current_path, path);
+                        },
+                        on_new_folder_closed: move |_| show_new_folder.set(false),
+                        on_drop: move |path| drop_into(path),
+                        progress: progress.clone(),
                     }
+                    SharesPanel {
+                        account: cx.props.account.clone(),
+                        messaging: cx.props.messaging.clone(),
+                    },
+                    UploadQueue {},
+                    current_progress.map(|progress| {
+                        let percent = progress.percent();
+                        let step = format!("{} of {}", progress.done + 1, progress.total);
+                        rsx!(
+                            div {
+                                class: "operation-progress",
+                                span {
+                                    "{progress.action} {progress.item} ({step})"
+                                },
+                                div {
+                                    class: "bar",
+                                    div {
+                                        class: "fill",
+                                        style: "width: {percent}%;",
+                                    }
+                                }
+                            }
+                        )
+                    }),
                     span {
                         class: "hidden-on-desktop mobile-nav",
                         Nav {
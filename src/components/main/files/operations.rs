@@ -0,0 +1,382 @@
+// Bulk operations on the items of the Files page. Every operation goes through Constellation, reports
+// how far along it is through `progress` and carries on past items that fail; the names of those are
+// returned so the page can tell the user.
+
+use std::path::{Path, PathBuf};
+
+use dioxus::prelude::UseRef;
+use futures::{stream, StreamExt};
+use once_cell::sync::Lazy;
+use tokio::sync::{Mutex, MutexGuard};
+use warp::{
+    constellation::{item::ItemType, Progression},
+    sync::RwLock,
+};
+
+use crate::{
+    components::main::files::{directory_at, thumbnails, unique_name},
+    Storage,
+};
+
+#[derive(Clone, PartialEq)]
+pub struct Progress {
+    // e.g. "Copying"
+    pub action: &'static str,
+    pub item: String,
+    pub done: usize,
+    pub total: usize,
+    // how much of the current item has been written, when it's known
+    pub item_percent: Option<usize>,
+}
+
+impl Progress {
+    pub fn percent(&self) -> usize {
+        if self.total == 0 {
+            return 0;
+        }
+        let item = self.item_percent.unwrap_or_default() as f64 / 100.;
+        (((self.done as f64 + item) / self.total as f64) * 100.) as usize
+    }
+}
+
+fn report(
+    progress: &UseRef<Option<Progress>>,
+    action: &'static str,
+    item: &str,
+    done: usize,
+    total: usize,
+) {
+    *progress.write() = Some(Progress {
+        action,
+        item: item.to_string(),
+        done,
+        total,
+        item_percent: None,
+    });
+}
+
+// Constellation resolves names against its current path, which is shared by everything using the storage:
+// the Files page keeps it on the folder being shown, while uploads, copies and previews running in the
+// background need other folders. A call on another folder holds `PATH_LOCK` while the path is switched,
+// so two of them can't interleave, and puts the shown folder back once it's done.
+static PATH_LOCK: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+static SHOWN_PATH: Lazy<RwLock<Vec<String>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+/// Constellation's current path switched to another folder, until this is dropped
+pub struct SwitchedPath {
+    storage: Storage,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Drop for SwitchedPath {
+    fn drop(&mut self) {
+        self.storage
+            .set_path(SHOWN_PATH.read().iter().collect::<PathBuf>());
+    }
+}
+
+pub async fn switch_path(storage: &Storage, path: &[String]) -> SwitchedPath {
+    let lock = PATH_LOCK.lock().await;
+    let mut storage = storage.clone();
+    storage.set_path(path.iter().collect::<PathBuf>());
+    SwitchedPath {
+        storage,
+        _lock: lock,
+    }
+}
+
+/// the folder the Files page shows
+pub fn shown_path() -> Vec<String> {
+    SHOWN_PATH.read().clone()
+}
+
+/// back to the root, for the next profile opened
+pub fn forget_shown_path() {
+    SHOWN_PATH.write().clear();
+}
+
+/// moves the Files page to `path`. When a call on another folder is running, it'll switch to it when done.
+pub fn show_path(storage: &Storage, path: &[String]) {
+    *SHOWN_PATH.write() = path.to_vec();
+    if let Ok(_lock) = PATH_LOCK.try_lock() {
+        storage.clone().set_path(path.iter().collect::<PathBuf>());
+    }
+}
+
+fn child_path(path: &[String], name: &str) -> Vec<String> {
+    let mut child = path.to_vec();
+    child.push(name.to_string());
+    child
+}
+
+pub async fn delete_items(
+    mut storage: Storage,
+    path: Vec<String>,
+    names: Vec<String>,
+    progress: UseRef<Option<Progress>>,
+) -> Vec<String> {
+    let mut failed = Vec::new();
+    for (done, name) in names.iter().enumerate() {
+        report(&progress, "Deleting", name, done, names.len());
+        let switched = switch_path(&storage, &path).await;
+        let result = storage.remove(name, true).await;
+        drop(switched);
+        match result {
+            Ok(_) => log::info!("{name} was deleted."),
+            Err(error) => {
+                log::error!("Error deleting {name}: {error}");
+                failed.push(name.clone());
+            }
+        }
+    }
+    *progress.write() = None;
+    failed
+}
+
+pub async fn move_items(
+    mut storage: Storage,
+    from: Vec<String>,
+    names: Vec<String>,
+    to: Vec<String>,
+    progress: UseRef<Option<Progress>>,
+) -> Vec<String> {
+    let mut failed = Vec::new();
+    if from == to {
+        return failed;
+    }
+    for (done, name) in names.iter().enumerate() {
+        report(&progress, "Moving", name, done, names.len());
+        let source = child_path(&from, name);
+        // a folder can't be moved into itself
+        if to.starts_with(&source) {
+            log::warn!("Not moving {name} into itself");
+            failed.push(name.clone());
+            continue;
+        }
+        if matches!(directory_at(&storage, &to), Ok(directory) if directory.has_item(name)) {
+            log::error!("Error moving {name}: the destination already has an item with that name");
+            failed.push(name.clone());
+            continue;
+        }
+        // both paths are given in full, from the root
+        let switched = switch_path(&storage, &[]).await;
+        let result = storage
+            .move_item(&source.join("/"), &format!("/{}", to.join("/")))
+            .await;
+        drop(switched);
+        match result {
+            Ok(_) => log::info!("{name} moved to /{}", to.join("/")),
+            Err(error) => {
+                log::error!("Error moving {name}: {error}");
+                failed.push(name.clone());
+            }
+        }
+    }
+    *progress.write() = None;
+    failed
+}
+
+async fn copy_file(
+    storage: &mut Storage,
+    from: &[String],
+    name: &str,
+    to: &[String],
+    new_name: &str,
+    progress: &UseRef<Option<Progress>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let thumbnail = thumbnails::instance().get(&directory_at(storage, from)?.get_item(name)?);
+
+    let switched = switch_path(storage, from).await;
+    let buffer = storage.get_buffer(name).await;
+    drop(switched);
+    let buffer = buffer?;
+
+    let size = buffer.len();
+    let switched = switch_path(storage, to).await;
+    let upload = storage
+        .put_stream(
+            new_name,
+            Some(size),
+            stream::once(async move { buffer }).boxed(),
+        )
+        .await;
+    drop(switched);
+
+    let mut upload = upload?;
+    while let Some(upload_progress) = upload.next().await {
+        match upload_progress {
+            Progression::CurrentProgress { current, total, .. } => {
+                if let Some(total) = total.filter(|total| *total > 0) {
+                    if let Some(progress) = progress.write().as_mut() {
+                        progress.item_percent = Some(current * 100 / total);
+                    }
+                }
+            }
+            Progression::ProgressComplete { .. } => {}
+            Progression::ProgressFailed { error, .. } => {
+                return Err(Box::from(error.unwrap_or_default()));
+            }
+        }
+    }
+
+    if !thumbnail.is_empty() {
+        let copy = directory_at(storage, to)?.get_item(new_name)?;
+        thumbnails::instance().store(&copy, &thumbnail);
+    }
+    Ok(())
+}
+
+/// copies `names` from the folder at `from` into the one at `to`, folders included. Copies that would
+/// replace an item get a new name the same way uploads do, so copying into the same folder duplicates.
+pub async fn copy_items(
+    mut storage: Storage,
+    from: Vec<String>,
+    names: Vec<String>,
+    to: Vec<String>,
+    progress: UseRef<Option<Progress>>,
+) -> Vec<String> {
+    let mut failed = Vec::new();
+    let destination = match directory_at(&storage, &to) {
+        Ok(directory) => directory,
+        Err(error) => {
+            log::error!("Not possible to open {:?}: {error}", to);
+            return names;
+        }
+    };
+
+    // (source folder, name, destination folder, new name)
+    let mut pending: Vec<(Vec<String>, String, Vec<String>, String)> = Vec::new();
+    for name in names.iter().rev() {
+        let new_name = unique_name(name, |name| {
+            destination.has_item(name) || pending.iter().any(|(.., taken)| taken == name)
+        });
+        pending.push((from.clone(), name.clone(), to.clone(), new_name));
+    }
+    let mut done = 0;
+
+    while let Some((source, name, target, new_name)) = pending.pop() {
+        report(&progress, "Copying", &name, done, done + pending.len() + 1);
+        done += 1;
+
+        let item_type = directory_at(&storage, &source)
+            .and_then(|directory| directory.get_item(&name))
+            .map(|item| item.item_type());
+        let result: Result<(), Box<dyn std::error::Error>> = match item_type {
+            Ok(ItemType::DirectoryItem) => {
+                let source_folder = child_path(&source, &name);
+                if target.starts_with(&source_folder) {
+                    log::warn!("Not copying {name} into itself");
+                    failed.push(name);
+                    continue;
+                }
+                let switched = switch_path(&storage, &target).await;
+                let result = storage.create_directory(&new_name, true).await;
+                drop(switched);
+
+                if result.is_ok() {
+                    let target_folder = child_path(&target, &new_name);
+                    if let Ok(directory) = directory_at(&storage, &source_folder) {
+                        for child in directory.get_items() {
+                            pending.push((
+                                source_folder.clone(),
+                                child.name(),
+                                target_folder.clone(),
+                                child.name(),
+                            ));
+                        }
+                    }
+                }
+                result.map_err(Box::from)
+            }
+            Ok(_) => copy_file(&mut storage, &source, &name, &target, &new_name, &progress).await,
+            Err(error) => Err(Box::from(error)),
+        };
+        match result {
+            Ok(_) => log::info!("{name} copied as {new_name}"),
+            Err(error) => {
+                log::error!("Error copying {name}: {error}");
+                failed.push(name);
+            }
+        }
+    }
+    *progress.write() = None;
+    failed
+}
+
+// every file under `name` in the folder at `path`, with where it should be saved
+fn files_to_download(
+    storage: &Storage,
+    path: &[String],
+    name: &str,
+    local: &Path,
+    files: &mut Vec<(Vec<String>, String, PathBuf)>,
+) {
+    let item = match directory_at(storage, path).and_then(|directory| directory.get_item(name)) {
+        Ok(item) => item,
+        Err(error) => {
+            log::error!("Not possible to find {name}: {error}");
+            return;
+        }
+    };
+    match item.get_directory() {
+        Ok(directory) => {
+            let folder = child_path(path, name);
+            for child in directory.get_items() {
+                files_to_download(
+                    storage,
+                    &folder,
+                    &child.name(),
+                    &local.join(child.name()),
+                    files,
+                );
+            }
+        }
+        Err(_) => files.push((path.to_vec(), name.to_string(), local.to_path_buf())),
+    }
+}
+
+/// saves `names` from the folder at `from` into the local folder `destination`, folders included.
+/// Nothing on disk is overwritten; clashing names are numbered the same way uploads are.
+pub async fn download_items(
+    storage: Storage,
+    from: Vec<String>,
+    names: Vec<String>,
+    destination: PathBuf,
+    progress: UseRef<Option<Progress>>,
+) -> Vec<String> {
+    let mut files = Vec::new();
+    for name in &names {
+        let local_name = unique_name(name, |name| destination.join(name).exists());
+        files_to_download(
+            &storage,
+            &from,
+            name,
+            &destination.join(local_name),
+            &mut files,
+        );
+    }
+
+    let mut failed = Vec::new();
+    for (done, (path, name, local)) in files.iter().enumerate() {
+        report(&progress, "Downloading", name, done, files.len());
+        if let Some(parent) = local.parent() {
+            if let Err(error) = std::fs::create_dir_all(parent) {
+                log::error!("Error creating {:?}: {error}", parent);
+                failed.push(name.clone());
+                continue;
+            }
+        }
+        let switched = switch_path(&storage, path).await;
+        let result = storage.get(name, &local.to_string_lossy()).await;
+        drop(switched);
+        match result {
+            Ok(_) => log::info!("{name} downloaded."),
+            Err(error) => {
+                log::error!("Error downloading file {name}: {error}");
+                failed.push(name.clone());
+            }
+        }
+    }
+    *progress.write() = None;
+    failed
+}
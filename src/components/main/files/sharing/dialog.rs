@@ -0,0 +1,109 @@
+use std::collections::HashSet;
+
+use dioxus::prelude::*;
+use dioxus_heroicons::{outline::Shape, Icon};
+use ui_kit::{button::Button, popup::Popup};
+use warp::crypto::DID;
+
+use crate::{iutils, Account};
+
+#[derive(Props)]
+pub struct Props<'a> {
+    account: Account,
+    // the items being shared
+    names: Vec<String>,
+    on_share: EventHandler<'a, Vec<DID>>,
+    on_cancel: EventHandler<'a, ()>,
+}
+
+#[allow(non_snake_case)]
+pub fn ShareDialog<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
+    let picked = use_ref(&cx, HashSet::<DID>::new);
+    let friends = use_state(&cx, || {
+        let mut friends: Vec<(DID, String)> = cx
+            .props
+            .account
+            .list_friends()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|did| {
+                let username = iutils::get_username_from_did(did.clone(), &cx.props.account);
+                (did, username)
+            })
+            .collect();
+        friends.sort_by_key(|(_, username)| username.to_lowercase());
+        friends
+    });
+
+    let what = match cx.props.names.as_slice() {
+        [name] => name.clone(),
+        names => format!("{} items", names.len()),
+    };
+    let picked_count = picked.read().len();
+
+    cx.render(rsx! {
+        Popup {
+            hidden: false,
+            on_dismiss: move |_| cx.props.on_cancel.call(()),
+            children: cx.render(rsx!(
+                div {
+                    id: "share-dialog",
+                    h2 {
+                        "Share {what}"
+                    },
+                    friends.is_empty().then(|| rsx!(
+                        p {
+                            class: "empty",
+                            "Add some friends to share files with them."
+                        }
+                    )),
+                    div {
+                        class: "friends",
+                        friends.iter().map(|(did, username)| {
+                            let key = did.to_string();
+                            let is_picked = picked.read().contains(did);
+                            let picked_class = if is_picked { "picked" } else { "" };
+                            let did = did.clone();
+                            rsx!(
+                                div {
+                                    key: "{key}",
+                                    class: "friend {picked_class}",
+                                    onclick: move |_| {
+                                        let mut picked = picked.write();
+                                        if !picked.remove(&did) {
+                                            picked.insert(did.clone());
+                                        }
+                                    },
+                                    is_picked.then(|| rsx!(
+                                        Icon {
+                                            icon: Shape::Check,
+                                        }
+                                    )),
+                                    span {
+                                        "{username}"
+                                    }
+                                }
+                            )
+                        })
+                    },
+                    div {
+                        class: "actions",
+                        Button {
+                            text: String::from("Cancel"),
+                            state: ui_kit::button::State::Secondary,
+                            on_pressed: move |_| cx.props.on_cancel.call(()),
+                        },
+                        Button {
+                            icon: Shape::Share,
+                            text: format!("Share with {picked_count}"),
+                            disabled: picked_count == 0,
+                            on_pressed: move |_| {
+                                cx.props.on_share.call(picked.read().iter().cloned().collect());
+                            },
+                        },
+                    }
+                }
+            )),
+        }
+    })
+}
@@ -0,0 +1,393 @@
+// Sharing files and folders with friends. A share is a RayGun message in the direct conversation with
+// each friend, carrying the files as attachments and a line describing the share. The friend's
+// Constellation is never touched: they browse the share from their Files page and fetch files the same
+// way attachments are fetched. Opening a shared file sends a hidden message back, which is how the owner
+// sees who opened what, and revoking deletes the share message and tells the friend to drop it.
+//
+// Everything is worked out from the conversations, so there is no list of shares to keep in sync. Going
+// through every message takes a while, so the result is kept until a message about a share comes in, or
+// for a short while where nothing is watching for those messages.
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use dioxus::prelude::UseRef;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use utils::DEFAULT_PATH;
+use uuid::Uuid;
+use warp::{
+    crypto::DID,
+    raygun::{Message, MessageOptions},
+    sync::RwLock,
+};
+
+use crate::{
+    components::main::files::{
+        operations::{download_items, Progress},
+        unique_name,
+    },
+    Messaging, Storage,
+};
+
+pub mod dialog;
+pub mod shares;
+
+// the line of a share message which describes the share, followed by it as JSON
+const SHARE_PREFIX: &str = "uplink-share:";
+// hidden messages sent back to the owner, followed by the id of the share
+const ACCESS_PREFIX: &str = "uplink-share-access:";
+const REVOKE_PREFIX: &str = "uplink-share-revoke:";
+// under DEFAULT_PATH, where shared files are copied to until RayGun has taken them in
+pub const STAGING_DIR: &str = "shares";
+
+// how long listed shares are reused, the Files page and the open chat don't see every share message
+const LISTED_FOR: Duration = Duration::from_secs(30);
+
+// the last shares listed, with the DID of the account they were listed for and when
+static LISTED: Lazy<RwLock<Option<(DID, Instant, Shares)>>> = Lazy::new(|| RwLock::new(None));
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct SharedFile {
+    // the name of the attachment, which is unique within the share
+    pub attachment: String,
+    // where the file is inside the shared folder, or its name when a single file is shared
+    pub path: String,
+    pub size: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct ShareNotice {
+    pub id: Uuid,
+    pub name: String,
+    pub folder: bool,
+    pub files: Vec<SharedFile>,
+}
+
+/// one friend's copy of a share
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ShareMessage {
+    pub conversation_id: Uuid,
+    pub message_id: Uuid,
+    // the friend it's shared with, or the owner when it was shared with us
+    pub peer: DID,
+    pub date: DateTime<Utc>,
+    // when the friend opened files from it, oldest first
+    pub accesses: Vec<DateTime<Utc>>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Share {
+    pub notice: ShareNotice,
+    pub messages: Vec<ShareMessage>,
+}
+
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct Shares {
+    pub incoming: Vec<Share>,
+    pub outgoing: Vec<Share>,
+}
+
+fn notice(message: &Message) -> Option<ShareNotice> {
+    message
+        .value()
+        .iter()
+        .find_map(|line| line.strip_prefix(SHARE_PREFIX))
+        .and_then(|json| serde_json::from_str(json).ok())
+}
+
+fn control(message: &Message, prefix: &str) -> Option<Uuid> {
+    message
+        .value()
+        .first()
+        .and_then(|line| line.strip_prefix(prefix))
+        .and_then(|id| Uuid::parse_str(id).ok())
+}
+
+/// messages which only exist to keep shares up to date, and aren't shown in the chat
+pub fn is_control_message(message: &Message) -> bool {
+    control(message, ACCESS_PREFIX).is_some() || control(message, REVOKE_PREFIX).is_some()
+}
+
+/// whether `message` shares something or updates a share, which means listing shares again
+pub fn changes_shares(message: &Message) -> bool {
+    is_control_message(message) || notice(message).is_some()
+}
+
+/// makes the next `list` go through the conversations again
+pub fn invalidate() {
+    *LISTED.write() = None;
+}
+
+/// the lines of a message meant to be read, without the description of a share
+pub fn visible_lines(message: &Message) -> Vec<String> {
+    message
+        .value()
+        .into_iter()
+        .filter(|line| !line.starts_with(SHARE_PREFIX))
+        .collect()
+}
+
+/// every share in the conversations we're in, newest first
+pub async fn list(messaging: &Messaging, own: &DID) -> Shares {
+    if let Some((listed_for, listed_at, shares)) = LISTED.read().as_ref() {
+        if listed_for == own && listed_at.elapsed() < LISTED_FOR {
+            return shares.clone();
+        }
+    }
+    let shares = load(messaging, own).await;
+    *LISTED.write() = Some((own.clone(), Instant::now(), shares.clone()));
+    shares
+}
+
+async fn load(messaging: &Messaging, own: &DID) -> Shares {
+    let conversations = match messaging.list_conversations().await {
+        Ok(conversations) => conversations,
+        Err(error) => {
+            log::error!("Not possible to list conversations for shares: {error}");
+            return Shares::default();
+        }
+    };
+
+    let mut incoming = Vec::new();
+    let mut outgoing: HashMap<Uuid, Share> = HashMap::new();
+    for conversation in conversations {
+        let peer = match conversation.recipients().into_iter().find(|did| did != own) {
+            Some(peer) => peer,
+            None => continue,
+        };
+        let messages = messaging
+            .get_messages(conversation.id(), MessageOptions::default())
+            .await
+            .unwrap_or_default();
+
+        let mut accesses: HashMap<Uuid, Vec<DateTime<Utc>>> = HashMap::new();
+        let mut revoked = Vec::new();
+        for message in &messages {
+            if let Some(id) = control(message, ACCESS_PREFIX).filter(|_| message.sender() == peer) {
+                accesses.entry(id).or_default().push(message.date());
+            }
+            if let Some(id) = control(message, REVOKE_PREFIX).filter(|_| message.sender() == peer) {
+                revoked.push(id);
+            }
+        }
+
+        for message in &messages {
+            let notice = match notice(message) {
+                Some(notice) => notice,
+                None => continue,
+            };
+            let share_message = ShareMessage {
+                conversation_id: conversation.id(),
+                message_id: message.id(),
+                peer: peer.clone(),
+                date: message.date(),
+                accesses: accesses.remove(&notice.id).unwrap_or_default(),
+            };
+            if message.sender() == *own {
+                outgoing
+                    .entry(notice.id)
+                    .or_insert_with(|| Share {
+                        notice,
+                        messages: Vec::new(),
+                    })
+                    .messages
+                    .push(share_message);
+            } else if message.sender() == peer && !revoked.contains(&notice.id) {
+                incoming.push(Share {
+                    notice,
+                    messages: vec![share_message],
+                });
+            }
+        }
+    }
+
+    let newest = |share: &Share| share.messages.iter().map(|m| m.date).max();
+    let mut outgoing: Vec<Share> = outgoing.into_values().collect();
+    outgoing.sort_by_key(|share| std::cmp::Reverse(newest(share)));
+    incoming.sort_by_key(|share| std::cmp::Reverse(newest(share)));
+    Shares { incoming, outgoing }
+}
+
+// the files of a downloaded item, with their paths inside it
+fn local_files(root: &Path, relative: &Path, files: &mut Vec<(PathBuf, String)>) {
+    let path = root.join(relative);
+    if path.is_dir() {
+        for entry in fs::read_dir(&path).into_iter().flatten().flatten() {
+            local_files(root, &relative.join(entry.file_name()), files);
+        }
+    } else {
+        files.push((path, relative.to_string_lossy().to_string()));
+    }
+}
+
+// copies `name` from Constellation into `directory` and lays it out to be attached. Attachments are
+// identified by their name, so files of a folder are given unique ones.
+async fn prepare(
+    storage: &Storage,
+    path: &[String],
+    name: &str,
+    directory: &Path,
+    progress: UseRef<Option<Progress>>,
+) -> Result<(Vec<PathBuf>, Vec<SharedFile>), Box<dyn Error>> {
+    let downloaded = directory.join("downloaded");
+    let attached = directory.join("attached");
+    fs::create_dir_all(&downloaded)?;
+    fs::create_dir_all(&attached)?;
+    let failed = download_items(
+        storage.clone(),
+        path.to_vec(),
+        vec![name.to_string()],
+        downloaded.clone(),
+        progress,
+    )
+    .await;
+    if !failed.is_empty() {
+        return Err(Box::from(format!(
+            "{} could not be read",
+            failed.join(", ")
+        )));
+    }
+
+    let mut files = Vec::new();
+    local_files(&downloaded, Path::new(name), &mut files);
+    if files.is_empty() {
+        return Err(Box::from(format!("{name} is empty")));
+    }
+    let mut paths = Vec::new();
+    let mut shared = Vec::new();
+    for (local, relative) in files {
+        let file_name = local
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let attachment = unique_name(&file_name, |name| attached.join(name).exists());
+        let destination = attached.join(&attachment);
+        let size = fs::metadata(&local)?.len() as usize;
+        fs::rename(&local, &destination)?;
+        paths.push(destination);
+        // the folder itself isn't part of the paths inside it
+        let path = match Path::new(&relative).strip_prefix(name) {
+            Ok(inner) if !inner.as_os_str().is_empty() => inner.to_string_lossy().to_string(),
+            _ => relative,
+        };
+        shared.push(SharedFile {
+            attachment,
+            path,
+            size,
+        });
+    }
+    Ok((paths, shared))
+}
+
+async fn conversation_with(
+    messaging: &mut Messaging,
+    friend: &DID,
+) -> Result<Uuid, warp::error::Error> {
+    match messaging.create_conversation(friend).await {
+        Ok(conversation) => Ok(conversation.id()),
+        Err(warp::error::Error::ConversationExist { conversation }) => Ok(conversation.id()),
+        Err(error) => Err(error),
+    }
+}
+
+/// shares `name` from the folder at `path` with `friends`. Returns the friends it couldn't be shared with.
+pub async fn share(
+    storage: Storage,
+    mut messaging: Messaging,
+    path: Vec<String>,
+    name: String,
+    friends: Vec<DID>,
+    progress: UseRef<Option<Progress>>,
+) -> Result<Vec<DID>, Box<dyn Error>> {
+    let id = Uuid::new_v4();
+    let directory = DEFAULT_PATH.read().join(STAGING_DIR).join(id.to_string());
+    let prepared = prepare(&storage, &path, &name, &directory, progress.clone()).await;
+    let (paths, files) = match prepared {
+        Ok(prepared) => prepared,
+        Err(error) => {
+            let _ = fs::remove_dir_all(&directory);
+            return Err(error);
+        }
+    };
+    let notice = ShareNotice {
+        id,
+        folder: files.len() != 1 || files[0].path != name,
+        name,
+        files,
+    };
+    let lines = vec![
+        format!("Shared {} with you.", notice.name),
+        format!("{SHARE_PREFIX}{}", serde_json::to_string(&notice)?),
+    ];
+
+    let mut failed = Vec::new();
+    for (done, friend) in friends.iter().enumerate() {
+        *progress.write() = Some(Progress {
+            action: "Sharing",
+            item: notice.name.clone(),
+            done,
+            total: friends.len(),
+            item_percent: None,
+        });
+        let result = match conversation_with(&mut messaging, friend).await {
+            Ok(conversation_id) => {
+                messaging
+                    .attach(conversation_id, paths.clone(), lines.clone())
+                    .await
+            }
+            Err(error) => Err(error),
+        };
+        if let Err(error) = result {
+            log::error!(
+                "Not possible to share {} with {friend}: {error}",
+                notice.name
+            );
+            failed.push(friend.clone());
+        }
+    }
+    *progress.write() = None;
+
+    invalidate();
+    // the files were copied in by RayGun
+    if let Err(error) = fs::remove_dir_all(&directory) {
+        log::warn!("Not possible to remove {:?}: {error}", directory);
+    }
+    Ok(failed)
+}
+
+/// stops sharing with the friend `message` was sent to
+pub async fn revoke(
+    mut messaging: Messaging,
+    id: Uuid,
+    message: ShareMessage,
+) -> Result<(), warp::error::Error> {
+    messaging
+        .send(
+            message.conversation_id,
+            None,
+            vec![format!("{REVOKE_PREFIX}{id}")],
+        )
+        .await?;
+    let deleted = messaging
+        .delete(message.conversation_id, Some(message.message_id))
+        .await;
+    invalidate();
+    deleted
+}
+
+/// lets the owner of a share know it was opened
+pub async fn record_access(mut messaging: Messaging, id: Uuid, conversation_id: Uuid) {
+    let result = messaging
+        .send(conversation_id, None, vec![format!("{ACCESS_PREFIX}{id}")])
+        .await;
+    if let Err(error) = result {
+        log::warn!("Not possible to record the access to share {id}: {error}");
+    }
+}
@@ -0,0 +1,355 @@
+use std::{
+    path::{Component, Path, PathBuf},
+    time::Duration,
+};
+
+use dioxus::{core::to_owned, prelude::*};
+use dioxus_heroicons::outline::Shape;
+use dioxus_toast::ToastInfo;
+use futures::StreamExt;
+use humansize::{format_size, DECIMAL};
+use rfd::FileDialog;
+use ui_kit::button::Button;
+use warp::constellation::Progression;
+
+use crate::{
+    components::{
+        main::files::{
+            sharing::{self, Share, Shares},
+            unique_name,
+        },
+        reusable::file_preview::{FilePreview, PreviewItem, PreviewSource},
+    },
+    iutils, Account, Messaging, TOAST_MANAGER,
+};
+
+#[derive(Props, PartialEq)]
+pub struct Props {
+    account: Account,
+    messaging: Messaging,
+}
+
+fn preview_items(messaging: &Messaging, share: &Share) -> Vec<PreviewItem> {
+    let message = &share.messages[0];
+    share
+        .notice
+        .files
+        .iter()
+        .map(|file| PreviewItem {
+            name: file.attachment.clone(),
+            size: file.size,
+            source: PreviewSource::Attachment {
+                messaging: messaging.clone(),
+                conversation_id: message.conversation_id,
+                message_id: message.message_id,
+            },
+        })
+        .collect()
+}
+
+// a path from a share notice, rebuilt from its plain parts. the notice comes from a peer, so anything
+// that could point outside the folder it's saved to (`..`, a root or a drive) is refused.
+fn relative_path(path: &str) -> Option<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            _ => return None,
+        }
+    }
+    match relative.as_os_str().is_empty() {
+        true => None,
+        false => Some(relative),
+    }
+}
+
+// saves every file of a share we were given into `destination`, folders recreated
+async fn download(messaging: Messaging, share: Share, destination: PathBuf) -> Vec<String> {
+    let message = &share.messages[0];
+    let root = match share.notice.folder {
+        true => {
+            let name = match relative_path(&share.notice.name) {
+                Some(name) if name.components().count() == 1 => name,
+                _ => {
+                    log::error!("Refusing to save a share named {:?}", share.notice.name);
+                    return share
+                        .notice
+                        .files
+                        .iter()
+                        .map(|file| file.path.clone())
+                        .collect();
+                }
+            };
+            let name = name.to_string_lossy().to_string();
+            destination.join(unique_name(&name, |name| destination.join(name).exists()))
+        }
+        false => destination,
+    };
+    let mut failed = Vec::new();
+    for file in &share.notice.files {
+        let mut local = match relative_path(&file.path) {
+            Some(path) => root.join(path),
+            None => {
+                log::error!("Refusing to save a shared file at {:?}", file.path);
+                failed.push(file.path.clone());
+                continue;
+            }
+        };
+        if local.exists() {
+            let file_name = local
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let name = unique_name(&file_name, |name| local.with_file_name(name).exists());
+            local = local.with_file_name(name);
+        }
+        if let Some(parent) = local.parent() {
+            if let Err(error) = std::fs::create_dir_all(parent) {
+                log::error!("Error creating {:?}: {error}", parent);
+                failed.push(file.path.clone());
+                continue;
+            }
+        }
+        let progress = messaging
+            .download(
+                message.conversation_id,
+                message.message_id,
+                file.attachment.clone(),
+                local,
+            )
+            .await;
+        let ok = match progress {
+            Ok(mut progress) => {
+                let mut ok = true;
+                while let Some(progress) = progress.next().await {
+                    if let Progression::ProgressFailed { error, .. } = progress {
+                        log::error!("Error downloading {}: {:?}", file.path, error);
+                        ok = false;
+                    }
+                }
+                ok
+            }
+            Err(error) => {
+                log::error!("Error downloading {}: {error}", file.path);
+                false
+            }
+        };
+        if !ok {
+            failed.push(file.path.clone());
+        }
+    }
+    failed
+}
+
+fn accesses_text(share_message: &sharing::ShareMessage) -> String {
+    match share_message.accesses.last() {
+        Some(last) => format!(
+            "opened {} time(s), last {}",
+            share_message.accesses.len(),
+            iutils::display_msg_time(*last)
+        ),
+        None => String::from("not opened yet"),
+    }
+}
+
+#[allow(non_snake_case)]
+pub fn SharesPanel(cx: Scope<Props>) -> Element {
+    let shares = use_state(&cx, Shares::default);
+    let collapsed = use_state(&cx, || false);
+    // the share being previewed and the file to start at
+    let preview = use_state(&cx, || None::<(Vec<PreviewItem>, usize)>);
+    let toast = use_atom_ref(&cx, TOAST_MANAGER);
+    let own = cx
+        .props
+        .account
+        .get_own_identity()
+        .map(|identity| identity.did_key())
+        .unwrap_or_default();
+
+    use_future(
+        &cx,
+        (shares, &cx.props.messaging, &own),
+        |(shares, messaging, own)| async move {
+            loop {
+                let updated = sharing::list(&messaging, &own).await;
+                if *shares.current() != updated {
+                    shares.set(updated);
+                }
+                tokio::time::sleep(Duration::from_secs(10)).await;
+            }
+        },
+    );
+
+    if shares.incoming.is_empty() && shares.outgoing.is_empty() {
+        return None;
+    }
+
+    let title = format!(
+        "Shared with me ({}) · Shared by me ({})",
+        shares.incoming.len(),
+        shares.outgoing.len()
+    );
+    let chevron = match **collapsed {
+        true => Shape::ChevronUp,
+        false => Shape::ChevronDown,
+    };
+
+    cx.render(rsx! {
+        div {
+            id: "shares",
+            div {
+                class: "header",
+                span {
+                    onclick: move |_| collapsed.set(!**collapsed),
+                    "{title}"
+                },
+                Button {
+                    icon: chevron,
+                    state: ui_kit::button::State::Transparent,
+                    on_pressed: move |_| collapsed.set(!**collapsed),
+                },
+            },
+            (!**collapsed).then(|| rsx!(
+                div {
+                    class: "share-list",
+                    shares.incoming.iter().map(|share| {
+                        let message = &share.messages[0];
+                        let key = message.message_id;
+                        let name = &share.notice.name;
+                        let from = iutils::get_username_from_did(message.peer.clone(), &cx.props.account);
+                        let when = iutils::display_msg_time(message.date);
+                        let size: usize = share.notice.files.iter().map(|file| file.size).sum();
+                        let details = format!(
+                            "from {from} · {} file(s), {} · {when}",
+                            share.notice.files.len(),
+                            format_size(size, DECIMAL)
+                        );
+                        let items = preview_items(&cx.props.messaging, share);
+                        let download_share = share.clone();
+                        let (id, conversation_id) = (share.notice.id, message.conversation_id);
+
+                        rsx!(
+                            div {
+                                key: "{key}",
+                                class: "share incoming",
+                                div {
+                                    class: "details",
+                                    span {
+                                        class: "name",
+                                        title: "{name}",
+                                        "{name}"
+                                    },
+                                    span {
+                                        class: "status",
+                                        "{details}"
+                                    },
+                                },
+                                Button {
+                                    icon: Shape::Eye,
+                                    text: String::from("Open"),
+                                    state: ui_kit::button::State::Secondary,
+                                    on_pressed: move |_| {
+                                        preview.set(Some((items.clone(), 0)));
+                                        cx.spawn(sharing::record_access(cx.props.messaging.clone(), id, conversation_id));
+                                    },
+                                },
+                                Button {
+                                    icon: Shape::DocumentArrowDown,
+                                    text: String::from("Download"),
+                                    state: ui_kit::button::State::Secondary,
+                                    on_pressed: move |_| {
+                                        let destination = match FileDialog::new().set_directory(".").pick_folder() {
+                                            Some(destination) => destination,
+                                            None => return,
+                                        };
+                                        let messaging = cx.props.messaging.clone();
+                                        let share = download_share.clone();
+                                        let toast = toast.clone();
+                                        cx.spawn(async move {
+                                            let failed = download(messaging.clone(), share, destination).await;
+                                            if !failed.is_empty() {
+                                                let text = format!("Not possible to download {}", failed.join(", "));
+                                                toast.write().popup(ToastInfo::simple(&text));
+                                            }
+                                            sharing::record_access(messaging, id, conversation_id).await;
+                                        });
+                                    },
+                                },
+                            }
+                        )
+                    }),
+                    shares.outgoing.iter().map(|share| {
+                        let key = share.notice.id;
+                        let name = &share.notice.name;
+                        let id = share.notice.id;
+
+                        rsx!(
+                            div {
+                                key: "{key}",
+                                class: "share outgoing",
+                                div {
+                                    class: "details",
+                                    span {
+                                        class: "name",
+                                        title: "{name}",
+                                        "{name}"
+                                    },
+                                },
+                                share.messages.iter().map(|share_message| {
+                                    let key = share_message.message_id;
+                                    let friend = iutils::get_username_from_did(share_message.peer.clone(), &cx.props.account);
+                                    let status = format!(
+                                        "shared {}, {}",
+                                        iutils::display_msg_time(share_message.date),
+                                        accesses_text(share_message)
+                                    );
+                                    let share_message = share_message.clone();
+                                    let own = own.clone();
+                                    rsx!(
+                                        div {
+                                            key: "{key}",
+                                            class: "recipient",
+                                            span {
+                                                class: "friend",
+                                                "{friend}"
+                                            },
+                                            span {
+                                                class: "status",
+                                                "{status}"
+                                            },
+                                            Button {
+                                                icon: Shape::NoSymbol,
+                                                text: String::from("Revoke"),
+                                                state: ui_kit::button::State::Secondary,
+                                                on_pressed: move |_| {
+                                                    let messaging = cx.props.messaging.clone();
+                                                    let share_message = share_message.clone();
+                                                    let own = own.clone();
+                                                    to_owned![shares, toast];
+                                                    cx.spawn(async move {
+                                                        if let Err(error) = sharing::revoke(messaging.clone(), id, share_message).await {
+                                                            log::error!("Not possible to revoke share {id}: {error}");
+                                                            toast.write().popup(ToastInfo::simple("Not possible to stop sharing."));
+                                                        }
+                                                        shares.set(sharing::list(&messaging, &own).await);
+                                                    });
+                                                },
+                                            },
+                                        }
+                                    )
+                                })
+                            }
+                        )
+                    })
+                }
+            )),
+            preview.get().as_ref().map(|(items, start)| rsx!(
+                FilePreview {
+                    items: items.clone(),
+                    start: *start,
+                    on_close: move |_| preview.set(None),
+                }
+            )),
+        }
+    })
+}
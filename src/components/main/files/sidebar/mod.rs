@@ -1,19 +1,26 @@
 #![allow(clippy::derive_partial_eq_without_eq)]
 
+use std::time::Duration;
+
 use dioxus::prelude::*;
 use dioxus_heroicons::{outline::Shape, Icon};
+use warp::constellation::{directory, item::ItemType};
 
-use crate::{state::Actions, Account, Messaging, STATE};
+use crate::{state::Actions, Account, Messaging, Storage, STATE};
 
 #[derive(Eq, PartialEq, Clone)]
 pub struct Directory {
     pub name: String,
+    // folder names leading to this directory from the root, including its own
+    pub path: Vec<String>,
     pub contents: Vec<DirItem>,
 }
 
 #[derive(Eq, PartialEq, Clone)]
 pub struct File {
     pub name: String,
+    // path of the directory containing the file
+    pub parent: Vec<String>,
 }
 
 #[derive(Eq, PartialEq, Clone)]
@@ -28,9 +35,42 @@ pub enum FolderDisplay {
     Closed,
 }
 
+// mirrors a Constellation directory. folders come before files and each are sorted by name.
+fn build_tree(dir: &directory::Directory, path: &[String]) -> Vec<DirItem> {
+    let mut items = dir.get_items();
+    items.sort_by_key(|item| (item.item_type() != ItemType::DirectoryItem, item.name()));
+    items
+        .iter()
+        .filter_map(|item| match item.item_type() {
+            ItemType::DirectoryItem => {
+                let mut child_path = path.to_vec();
+                child_path.push(item.name());
+                let contents = match item.get_directory() {
+                    Ok(child) => build_tree(&child, &child_path),
+                    Err(_) => Vec::new(),
+                };
+                Some(DirItem::Directory(Directory {
+                    name: item.name(),
+                    path: child_path,
+                    contents,
+                }))
+            }
+            ItemType::FileItem => Some(DirItem::File(File {
+                name: item.name(),
+                parent: path.to_vec(),
+            })),
+            _ => None,
+        })
+        .collect()
+}
+
 #[inline_props]
 #[allow(non_snake_case)]
-pub fn FileElem(cx: Scope, file: File) -> Element {
+pub fn FileElem<'a>(
+    cx: Scope,
+    file: File,
+    on_navigate: EventHandler<'a, Vec<String>>,
+) -> Element<'a> {
     let name = file.name.clone();
     let state = use_atom_ref(&cx, STATE).clone();
 
@@ -40,6 +80,7 @@ pub fn FileElem(cx: Scope, file: File) -> Element {
             div {
                 class: "row",
                 onclick: move |_| {
+                    on_navigate.call(file.parent.clone());
                     state.write().dispatch(Actions::HideSidebar(true));
                 },
                 Icon {
@@ -53,23 +94,51 @@ pub fn FileElem(cx: Scope, file: File) -> Element {
 
 #[inline_props]
 #[allow(non_snake_case)]
-pub fn Folder(cx: Scope, dir: Directory) -> Element {
-    let display = use_state(&cx, || FolderDisplay::Closed);
+pub fn Folder<'a>(
+    cx: Scope,
+    dir: Directory,
+    current: Vec<String>,
+    on_navigate: EventHandler<'a, Vec<String>>,
+    on_drop: EventHandler<'a, Vec<String>>,
+) -> Element<'a> {
+    // folders leading to the current directory start open
+    let display = use_state(&cx, || match current.starts_with(&dir.path) {
+        true => FolderDisplay::Open,
+        false => FolderDisplay::Closed,
+    });
     let folder_icon: Shape = match *display.current() {
         FolderDisplay::Open => Shape::FolderOpen,
         FolderDisplay::Closed => Shape::Folder,
     };
+    let active = if *current == dir.path { "active" } else { "" };
+    let drop_over = use_state(&cx, || false);
+    let drop_target = if **drop_over { "drop-target" } else { "" };
 
     let folder_name = &dir.name;
     cx.render(rsx! {
         div {
             class: "tree-item",
             div {
-                class: "row",
+                class: "row {active} {drop_target}",
+                // items dragged from the browser are moved into the folder they're dropped on
+                prevent_default: "ondragover",
+                ondragover: |_| {},
+                ondragenter: move |_| drop_over.set(true),
+                ondragleave: move |_| drop_over.set(false),
+                ondrop: move |_| {
+                    drop_over.set(false);
+                    on_drop.call(dir.path.clone());
+                },
                 onclick: move |_| {
-                    match *display.current() {
-                        FolderDisplay::Open => display.set(FolderDisplay::Closed),
-                        FolderDisplay::Closed => display.set(FolderDisplay::Open),
+                    // the first click opens the folder, clicking it again while it's shown collapses it
+                    if *current == dir.path {
+                        match *display.current() {
+                            FolderDisplay::Open => display.set(FolderDisplay::Closed),
+                            FolderDisplay::Closed => display.set(FolderDisplay::Open),
+                        }
+                    } else {
+                        display.set(FolderDisplay::Open);
+                        on_navigate.call(dir.path.clone());
                     }
                 },
                 Icon {
@@ -87,8 +156,16 @@ pub fn Folder(cx: Scope, dir: Directory) -> Element {
                     cx.render(rsx!(
                         dir.contents.iter().map(|item| {
                             match item {
-                                DirItem::File(f) => cx.render(rsx!(FileElem { file: f.clone() })),
-                                DirItem::Directory(d) => cx.render(rsx!(Folder { dir: d.clone() })),
+                                DirItem::File(f) => cx.render(rsx!(FileElem {
+                                    file: f.clone(),
+                                    on_navigate: move |path| on_navigate.call(path),
+                                })),
+                                DirItem::Directory(d) => cx.render(rsx!(Folder {
+                                    dir: d.clone(),
+                                    current: current.clone(),
+                                    on_navigate: move |path| on_navigate.call(path),
+                                    on_drop: move |path| on_drop.call(path),
+                                })),
                             }
                         })
                     ))
@@ -99,41 +176,41 @@ pub fn Folder(cx: Scope, dir: Directory) -> Element {
     })
 }
 
-#[derive(Props, PartialEq)]
-pub struct Props {
+#[derive(Props)]
+pub struct Props<'a> {
     account: Account,
     messaging: Messaging,
+    storage: Storage,
+    path: Vec<String>,
+    on_navigate: EventHandler<'a, Vec<String>>,
+    on_drop: EventHandler<'a, Vec<String>>,
 }
 
 #[allow(non_snake_case)]
-pub fn Sidebar(cx: Scope<Props>) -> Element {
-    // TODO: This should be generated based on actual content later.
-    // We will create reusable components for a folder and just pass children as data and render
-    // recursively automatically.
-    // This is just to work on css
-    let subdir_1 = Directory {
-        name: "Subdir1".into(),
-        contents: vec![DirItem::File(File { name: "f1".into() })],
+pub fn Sidebar<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
+    let tree = use_state(&cx, Vec::new);
+    let root_active = if cx.props.path.is_empty() {
+        "active"
+    } else {
+        ""
     };
 
-    let subdir_3 = Directory {
-        name: "Subdir3".into(),
-        contents: vec![DirItem::File(File { name: "f2".into() })],
-    };
-    let subdir_2 = Directory {
-        name: "Subdir2".into(),
-        contents: vec![DirItem::Directory(subdir_3)],
-    };
-    let directory = Directory {
-        name: "Folder 1".into(),
-        contents: vec![
-            DirItem::Directory(subdir_1),
-            DirItem::Directory(subdir_2),
-            DirItem::File(File { name: "f3".into() }),
-        ],
-    };
+    // picks up changes made anywhere, including by other devices
+    use_future(
+        &cx,
+        (tree, &cx.props.storage),
+        |(tree, storage)| async move {
+            loop {
+                let updated = build_tree(&storage.root_directory(), &[]);
+                if *tree.current() != updated {
+                    log::debug!("updating files tree");
+                    tree.set(updated);
+                }
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        },
+    );
 
-    // if multiple folders are desired under `Files`, this could render a list of `DirEntry`
     cx.render(rsx! {
         crate::components::reusable::sidebar::Sidebar {
         account: cx.props.account.clone(),
@@ -144,9 +221,34 @@ pub fn Sidebar(cx: Scope<Props>) -> Element {
             },
             div {
                 class: "tree-container",
-                Folder {
-                    dir: directory
+                div {
+                    class: "tree-item",
+                    div {
+                        class: "row {root_active}",
+                        prevent_default: "ondragover",
+                        ondragover: |_| {},
+                        ondrop: move |_| cx.props.on_drop.call(Vec::new()),
+                        onclick: move |_| cx.props.on_navigate.call(Vec::new()),
+                        Icon {
+                            icon: Shape::Home,
+                        },
+                        "Home"
+                    }
                 },
+                tree.iter().map(|item| {
+                    match item {
+                        DirItem::File(f) => cx.render(rsx!(FileElem {
+                            file: f.clone(),
+                            on_navigate: move |path| cx.props.on_navigate.call(path),
+                        })),
+                        DirItem::Directory(d) => cx.render(rsx!(Folder {
+                            dir: d.clone(),
+                            current: cx.props.path.clone(),
+                            on_navigate: move |path| cx.props.on_navigate.call(path),
+                            on_drop: move |path| cx.props.on_drop.call(path),
+                        })),
+                    }
+                })
             }
         }
     })
@@ -0,0 +1,299 @@
+// Thumbnails for the file browser. Images are decoded and shrunk on a small pool of blocking workers so
+// big files never hold up the UI; videos and PDFs get one too when ffmpeg or pdftoppm are installed.
+// Every thumbnail is stored on the item and also kept on disk by item id.
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{Cursor, Read},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+    time::{Duration, Instant},
+};
+
+use image::{io::Reader as ImageReader, DynamicImage, ImageOutputFormat};
+use once_cell::sync::Lazy;
+use tokio::sync::Semaphore;
+use utils::DEFAULT_PATH;
+use warp::constellation::{directory::Directory, item::Item};
+
+// under DEFAULT_PATH
+pub const CACHE_DIR: &str = "thumbnails";
+const THUMBNAIL_SIZE: u32 = 160;
+const WORKERS: usize = 2;
+// svgs are kept as they are, they scale and are usually small
+const MAX_SVG_BYTES: u64 = 256 * 1024;
+// ffmpeg and pdftoppm can hang on a broken file, they're killed after this
+const RENDER_TIMEOUT: Duration = Duration::from_secs(15);
+// how many thumbnails read from disk are kept in memory, the least recently shown ones go first
+const MAX_LOADED: usize = 500;
+
+const IMAGE_EXTENSIONS: [&str; 9] = [
+    "png", "jpg", "jpeg", "gif", "webp", "bmp", "ico", "tif", "tiff",
+];
+const VIDEO_EXTENSIONS: [&str; 7] = ["mp4", "m4v", "mov", "mkv", "webm", "avi", "wmv"];
+
+static THUMBNAILS: Lazy<Thumbnails> = Lazy::new(Thumbnails::new);
+
+pub fn instance() -> &'static Thumbnails {
+    &THUMBNAILS
+}
+
+enum Source {
+    Image,
+    Svg,
+    Video,
+    Pdf,
+}
+
+fn source(path: &Path) -> Option<Source> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    match extension.as_str() {
+        "svg" => Some(Source::Svg),
+        "pdf" => Some(Source::Pdf),
+        e if IMAGE_EXTENSIONS.contains(&e) => Some(Source::Image),
+        e if VIDEO_EXTENSIONS.contains(&e) => Some(Source::Video),
+        _ => None,
+    }
+}
+
+fn shrink(image: DynamicImage) -> Result<String, image::ImageError> {
+    let thumbnail = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+    let mut png = Cursor::new(Vec::new());
+    thumbnail.write_to(&mut png, ImageOutputFormat::Png)?;
+    Ok(format!(
+        "data:image/png;base64,{}",
+        base64::encode(png.into_inner())
+    ))
+}
+
+fn decode(bytes: &[u8]) -> Option<DynamicImage> {
+    ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?
+        .decode()
+        .ok()
+}
+
+// runs an external decoder which writes a PNG to stdout. Missing tools just mean no thumbnail.
+fn render_with(program: &str, args: &[&str]) -> Option<DynamicImage> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    // read on its own thread, or a full pipe would keep the decoder from finishing
+    let mut stdout = child.stdout.take()?;
+    let reader = thread::spawn(move || {
+        let mut bytes = Vec::new();
+        stdout.read_to_end(&mut bytes).map(|_| bytes)
+    });
+
+    let deadline = Instant::now() + RENDER_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(50)),
+            Ok(None) => {
+                log::warn!("{program} took too long to make a thumbnail, stopping it");
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+            Err(error) => {
+                log::warn!("Error waiting for {program}: {error}");
+                let _ = child.kill();
+                return None;
+            }
+        }
+    };
+    let bytes = reader.join().ok()?.ok()?;
+    if !status.success() {
+        return None;
+    }
+    decode(&bytes)
+}
+
+// blocking, this is what the workers run
+fn make_thumbnail(path: &Path) -> Option<String> {
+    let path_str = path.to_str()?;
+    let image = match source(path)? {
+        Source::Svg => {
+            if fs::metadata(path).ok()?.len() > MAX_SVG_BYTES {
+                return None;
+            }
+            let svg = fs::read(path).ok()?;
+            return Some(format!("data:image/svg+xml;base64,{}", base64::encode(svg)));
+        }
+        Source::Image => decode(&fs::read(path).ok()?)?,
+        // a frame a second in, the very first one is often black
+        Source::Video => render_with(
+            "ffmpeg",
+            &[
+                "-loglevel",
+                "quiet",
+                "-ss",
+                "1",
+                "-i",
+                path_str,
+                "-frames:v",
+                "1",
+                "-f",
+                "image2pipe",
+                "-vcodec",
+                "png",
+                "-",
+            ],
+        )?,
+        Source::Pdf => render_with(
+            "pdftoppm",
+            &[
+                "-png",
+                "-f",
+                "1",
+                "-l",
+                "1",
+                "-scale-to",
+                &THUMBNAIL_SIZE.to_string(),
+                path_str,
+            ],
+        )?,
+    };
+    match shrink(image) {
+        Ok(thumbnail) => Some(thumbnail),
+        Err(error) => {
+            log::error!("Error creating thumbnail for {:?}: {error}", path);
+            None
+        }
+    }
+}
+
+// thumbnails read from disk, by item id, with when they were last used. an empty string means there
+// isn't one.
+#[derive(Default)]
+struct Loaded {
+    // the profile they were read for
+    root: PathBuf,
+    thumbnails: HashMap<String, (String, u64)>,
+    uses: u64,
+}
+
+impl Loaded {
+    fn get_or_insert_with(&mut self, id: String, load: impl FnOnce(&str) -> String) -> String {
+        self.uses += 1;
+        let uses = self.uses;
+        if let Some((thumbnail, last_used)) = self.thumbnails.get_mut(&id) {
+            *last_used = uses;
+            return thumbnail.clone();
+        }
+        let thumbnail = load(&id);
+        self.insert(id, thumbnail.clone());
+        thumbnail
+    }
+
+    fn insert(&mut self, id: String, thumbnail: String) {
+        self.uses += 1;
+        self.thumbnails.insert(id, (thumbnail, self.uses));
+        if self.thumbnails.len() > MAX_LOADED {
+            let oldest = self
+                .thumbnails
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(id, _)| id.clone());
+            if let Some(oldest) = oldest {
+                self.thumbnails.remove(&oldest);
+            }
+        }
+    }
+}
+
+pub struct Thumbnails {
+    loaded: Mutex<Loaded>,
+    workers: Arc<Semaphore>,
+}
+
+// in the folder of the profile in use, which changes when the user switches profiles
+fn cache_dir() -> PathBuf {
+    DEFAULT_PATH.read().join(CACHE_DIR)
+}
+
+impl Thumbnails {
+    fn new() -> Self {
+        Self {
+            loaded: Mutex::new(Loaded::default()),
+            workers: Arc::new(Semaphore::new(WORKERS)),
+        }
+    }
+
+    // forgets what was read for another profile
+    fn loaded(&self) -> MutexGuard<'_, Loaded> {
+        let root = DEFAULT_PATH.read().clone();
+        let mut loaded = self.loaded.lock().unwrap();
+        if loaded.root != root {
+            *loaded = Loaded {
+                root,
+                ..Default::default()
+            };
+        }
+        loaded
+    }
+
+    /// makes a thumbnail from the local copy of a file, if it's a kind that can have one
+    pub async fn generate(&self, local_path: PathBuf) -> Option<String> {
+        source(&local_path)?;
+        let _worker = self.workers.clone().acquire_owned().await.ok()?;
+        tokio::task::spawn_blocking(move || make_thumbnail(&local_path))
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// sets the thumbnail of `item` and keeps a copy on disk
+    pub fn store(&self, item: &Item, thumbnail: &str) {
+        item.set_thumbnail(thumbnail);
+        let id = item.id().to_string();
+        let cache_dir = cache_dir();
+        let result =
+            fs::create_dir_all(&cache_dir).and_then(|_| fs::write(cache_dir.join(&id), thumbnail));
+        if let Err(error) = result {
+            log::warn!(
+                "Not possible to cache the thumbnail of {}: {error}",
+                item.name()
+            );
+        }
+        self.loaded().insert(id, thumbnail.to_string());
+    }
+
+    /// the thumbnail of `item`, falling back to the one cached on disk
+    pub fn get(&self, item: &Item) -> String {
+        let thumbnail = item.thumbnail();
+        if !thumbnail.is_empty() {
+            return thumbnail;
+        }
+        let id = item.id().to_string();
+        let cache_dir = cache_dir();
+        self.loaded().get_or_insert_with(id, |id| {
+            fs::read_to_string(cache_dir.join(id)).unwrap_or_default()
+        })
+    }
+}
+
+/// creates the thumbnail of a file that was just uploaded from `local_path`. The item is looked up in
+/// the directory the upload started in, since the user may have moved on to another one.
+pub async fn create_for_upload(directory: &Directory, filename: &str, local_path: &Path) {
+    let thumbnail = match instance().generate(local_path.to_path_buf()).await {
+        Some(thumbnail) => thumbnail,
+        None => return,
+    };
+    match directory.get_item(filename) {
+        Ok(item) => {
+            instance().store(&item, &thumbnail);
+            log::info!("{} thumbnail updated with success!", item.name());
+        }
+        Err(error) => log::error!("Error on update thumbnail: {:?}", error),
+    }
+}
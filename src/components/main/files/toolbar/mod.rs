@@ -1,22 +1,65 @@
+use std::time::Duration;
+
 use dioxus::{events::MouseEvent, prelude::*};
 use dioxus_heroicons::outline::Shape;
+use dioxus_toast::ToastInfo;
 use ui_kit::button::Button;
 
-use crate::components::{
-    main::files::toolbar::usage::{Usage, UsageStats},
-    reusable::toolbar,
+use crate::{
+    components::{main::files::toolbar::usage::Usage, reusable::toolbar},
+    iutils::{
+        config::Config,
+        storage_usage::{constellation_usage, ConstellationUsage, QuotaWarning},
+    },
+    Storage, TOAST_MANAGER,
 };
 
 pub mod usage;
 
 #[derive(Props)]
 pub struct Props<'a> {
+    storage: Storage,
     on_new_folder: EventHandler<'a, MouseEvent>,
     on_show_upload: EventHandler<'a, MouseEvent>,
 }
 
 #[allow(non_snake_case)]
 pub fn Toolbar<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
+    let usage = use_state(&cx, ConstellationUsage::default);
+    let warning = use_state(&cx, || QuotaWarning::Ok);
+    let toast = use_atom_ref(&cx, TOAST_MANAGER);
+
+    use_future(
+        &cx,
+        (usage, warning, &cx.props.storage),
+        |(usage, warning, storage)| {
+            let toast = toast.clone();
+            async move {
+                loop {
+                    // the quota is read every time, it can be changed in the developer settings
+                    let quota = Config::load_config_or_default().storage;
+                    let updated = constellation_usage(&storage, &quota);
+                    let updated_warning = updated.warning(&quota);
+                    // only getting closer to the quota is worth a toast, not every check while there
+                    if updated_warning > *warning.current() {
+                        let text = match updated_warning {
+                            QuotaWarning::Full => String::from("Your storage is full."),
+                            _ => format!("{}% of your storage is used.", updated.percent_used()),
+                        };
+                        toast.write().popup(ToastInfo::simple(&text));
+                    }
+                    if updated_warning != *warning.current() {
+                        warning.set(updated_warning);
+                    }
+                    if *usage.current() != updated {
+                        usage.set(updated);
+                    }
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                }
+            }
+        },
+    );
+
     cx.render(rsx! {
         toolbar::Toolbar {
             controls: cx.render(rsx! {
@@ -40,12 +83,8 @@ pub fn Toolbar<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
                 div {
                     class: "usage-container",
                     Usage {
-                        usage: UsageStats {
-                            available: 1256,
-                            total: 123456,
-                            used: 122200,
-                            percent_free: 75,
-                        }
+                        usage: (**usage).clone(),
+                        warning: **warning,
                     },
                 },
             },
@@ -2,10 +2,12 @@ use dioxus::prelude::*;
 use humansize::format_size;
 use humansize::DECIMAL;
 
+use crate::iutils::storage_usage::{ConstellationUsage, QuotaWarning};
+
 #[derive(Props, PartialEq, Eq)]
 pub struct UsageContentProps {
     space: String,
-    available: u128,
+    available: String,
 }
 
 #[allow(non_snake_case)]
@@ -26,36 +28,37 @@ pub fn UsageContent(cx: Scope<UsageContentProps>) -> Element {
 
 #[derive(Props, PartialEq, Eq)]
 pub struct Props {
-    usage: UsageStats,
-}
-
-#[derive(Props, PartialEq, Eq)]
-pub struct UsageStats {
-    pub available: u128,
-    pub total: u128,
-    pub used: u128,
-    pub percent_free: u16,
+    usage: ConstellationUsage,
+    warning: QuotaWarning,
 }
 
 #[allow(non_snake_case)]
 pub fn Usage(cx: Scope<Props>) -> Element {
-    let free_space = fs2::free_space("/").unwrap_or(1);
-    let total_space = fs2::total_space("/").unwrap_or(1);
-    let perc = (((total_space / free_space) as f64) * 0.1) * 100.0;
+    let usage = &cx.props.usage;
+    // the coloured bar underneath shows through where the top one is clipped, which is the used part
+    let perc = usage.percent_used();
+    let available = format_size(usage.available(), DECIMAL);
     let space = format!(
         "{} / {}",
-        format_size(free_space, DECIMAL),
-        format_size(total_space, DECIMAL)
+        format_size(usage.used, DECIMAL),
+        format_size(usage.max, DECIMAL)
     );
+    let (warning_class, title) = match cx.props.warning {
+        QuotaWarning::Ok => ("", String::new()),
+        QuotaWarning::NearlyFull => ("nearly-full", format!("{perc}% of your storage is used")),
+        QuotaWarning::Full => ("full", String::from("Your storage is full")),
+    };
 
     cx.render(rsx! {
         div {
             id: "usage",
+            class: "{warning_class}",
+            title: "{title}",
             div {
                 id: "usage_bar_bg",
                 UsageContent {
                     space: space.clone(),
-                    available: cx.props.usage.available
+                    available: available.clone(),
                 }
             },
             div {
@@ -63,7 +66,7 @@ pub fn Usage(cx: Scope<Props>) -> Element {
                 style: "-webkit-clip-path: inset(0 0 0 {perc}%);",
                 UsageContent {
                     space: space.clone(),
-                    available: cx.props.usage.available
+                    available: available,
                 }
             },
         },
@@ -0,0 +1,231 @@
+// Uploading folders. A dropped or picked folder is walked before anything is sent so the user can see
+// what will be uploaded and narrow it down with include/exclude patterns; the folder structure is then
+// recreated in Constellation and every file goes through the upload queue.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use glob::{MatchOptions, Pattern, PatternError};
+
+use crate::{
+    components::main::files::{
+        directory_at, operations::switch_path, unique_name, upload::manager,
+    },
+    Storage,
+};
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: false,
+    require_literal_leading_dot: false,
+};
+
+#[derive(Default)]
+pub struct Filters {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+fn parse_patterns(patterns: &str) -> Result<Vec<Pattern>, PatternError> {
+    patterns
+        .split(',')
+        .map(str::trim)
+        .filter(|pattern| !pattern.is_empty())
+        .map(Pattern::new)
+        .collect()
+}
+
+impl Filters {
+    /// both lists are comma separated glob patterns, e.g. `*.jpg, *.png`
+    pub fn parse(include: &str, exclude: &str) -> Result<Self, PatternError> {
+        Ok(Self {
+            include: parse_patterns(include)?,
+            exclude: parse_patterns(exclude)?,
+        })
+    }
+
+    // patterns are matched against the name and against the path inside the uploaded folder, so both
+    // `*.log` and `build/*` work
+    fn matches(patterns: &[Pattern], relative: &Path) -> bool {
+        let name = relative.file_name().map(Path::new).unwrap_or(relative);
+        patterns.iter().any(|pattern| {
+            pattern.matches_path_with(name, MATCH_OPTIONS)
+                || pattern.matches_path_with(relative, MATCH_OPTIONS)
+        })
+    }
+
+    // excluded folders aren't walked at all
+    fn skips_folder(&self, relative: &Path) -> bool {
+        Self::matches(&self.exclude, relative)
+    }
+
+    fn allows_file(&self, relative: &Path) -> bool {
+        (self.include.is_empty() || Self::matches(&self.include, relative))
+            && !Self::matches(&self.exclude, relative)
+    }
+}
+
+pub struct PlannedFile {
+    pub local_path: PathBuf,
+    // which of the uploaded paths the file is in
+    pub root: usize,
+    // folders leading to the file from the folder it's uploaded into, starting with the dropped one
+    pub folders: Vec<String>,
+    pub size: u64,
+}
+
+pub struct PlannedFolder {
+    // which of the uploaded paths the folder is in, dropped folders can share a name
+    pub root: usize,
+    // starting with the dropped folder
+    pub path: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct UploadPlan {
+    // every folder to create, parents before their children
+    pub folders: Vec<PlannedFolder>,
+    pub files: Vec<PlannedFile>,
+    // files left out by the filters
+    pub skipped: usize,
+}
+
+impl UploadPlan {
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(|file| file.size).sum()
+    }
+}
+
+fn walk(
+    local: &Path,
+    root: usize,
+    folders: &[String],
+    relative: &Path,
+    filters: &Filters,
+    plan: &mut UploadPlan,
+) {
+    let entries = match fs::read_dir(local) {
+        Ok(entries) => entries,
+        Err(error) => {
+            log::error!("Not possible to read {:?}: {error}", local);
+            return;
+        }
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = entry.path();
+        let relative = relative.join(&name);
+        // symlinks are skipped so a link back up the tree can't make the walk endless
+        let metadata = match fs::symlink_metadata(&path) {
+            Ok(metadata) if !metadata.file_type().is_symlink() => metadata,
+            _ => continue,
+        };
+        if metadata.is_dir() {
+            if filters.skips_folder(&relative) {
+                continue;
+            }
+            let mut child = folders.to_vec();
+            child.push(name);
+            plan.folders.push(PlannedFolder {
+                root,
+                path: child.clone(),
+            });
+            walk(&path, root, &child, &relative, filters, plan);
+        } else if filters.allows_file(&relative) {
+            plan.files.push(PlannedFile {
+                local_path: path,
+                root,
+                folders: folders.to_vec(),
+                size: metadata.len(),
+            });
+        } else {
+            plan.skipped += 1;
+        }
+    }
+}
+
+/// works out what uploading `paths` would do. Files given directly are always uploaded, the filters
+/// only apply to what's inside folders.
+pub fn plan(paths: &[PathBuf], filters: &Filters) -> UploadPlan {
+    let mut plan = UploadPlan::default();
+    for (root, path) in paths.iter().enumerate() {
+        let name = match path.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => continue,
+        };
+        if path.is_dir() {
+            plan.folders.push(PlannedFolder {
+                root,
+                path: vec![name.clone()],
+            });
+            walk(path, root, &[name], Path::new(""), filters, &mut plan);
+        } else {
+            plan.files.push(PlannedFile {
+                local_path: path.clone(),
+                root,
+                folders: Vec::new(),
+                size: fs::metadata(path).map(|m| m.len()).unwrap_or_default(),
+            });
+        }
+    }
+    plan
+}
+
+/// creates the folders of `paths` inside the folder at `destination` and queues their files. A dropped
+/// folder whose name is taken gets a new one, the same way files do.
+pub async fn start(
+    mut storage: Storage,
+    paths: Vec<PathBuf>,
+    filters: Filters,
+    destination: Vec<String>,
+) {
+    let plan = match tokio::task::spawn_blocking(move || plan(&paths, &filters)).await {
+        Ok(plan) => plan,
+        Err(error) => {
+            log::error!("Error looking through the folders to upload: {error}");
+            return;
+        }
+    };
+    // the name each dropped folder got, by its index in `paths`
+    let mut renamed: HashMap<usize, String> = HashMap::new();
+    for planned in &plan.folders {
+        let mut folder = planned.path.clone();
+        if folder.len() == 1 {
+            let taken = |name: &str| matches!(directory_at(&storage, &destination), Ok(directory) if directory.has_item(name));
+            let new_name = unique_name(&folder[0], taken);
+            renamed.insert(planned.root, new_name.clone());
+            folder[0] = new_name;
+        } else if let Some(new_name) = renamed.get(&planned.root) {
+            folder[0] = new_name.clone();
+        }
+
+        let (name, parent) = match folder.split_last() {
+            Some(split) => split,
+            None => continue,
+        };
+        let mut parent_path = destination.clone();
+        parent_path.extend(parent.iter().cloned());
+
+        let switched = switch_path(&storage, &parent_path).await;
+        let result = storage.create_directory(name, true).await;
+        drop(switched);
+        if let Err(error) = result {
+            log::error!("Error creating folder {name}: {error}");
+        }
+    }
+
+    for file in plan.files {
+        let mut folders = file.folders;
+        if let Some(first) = folders.first_mut() {
+            if let Some(new_name) = renamed.get(&file.root) {
+                *first = new_name.clone();
+            }
+        }
+        let mut file_destination = destination.clone();
+        file_destination.extend(folders);
+        manager::instance().enqueue(storage.clone(), file.local_path, file_destination);
+    }
+}
@@ -0,0 +1,398 @@
+// Keeps the queue of uploads. Uploads run in the background, a few at a time, and carry on while the
+// user is on other pages; the Files page only shows the queue and lets uploads be paused, cancelled or
+// retried. A paused upload stops reading its file until it's resumed, and keeps its place meanwhile.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use futures::StreamExt;
+use once_cell::sync::Lazy;
+use tokio::{
+    sync::{watch, Semaphore},
+    task::JoinHandle,
+};
+use tokio_util::io::ReaderStream;
+use warp::{constellation::Progression, error::Error};
+
+use crate::{
+    components::main::files::{directory_at, operations::switch_path, thumbnails, unique_name},
+    Storage,
+};
+
+const MAX_CONCURRENT_UPLOADS: usize = 3;
+
+static UPLOADS: Lazy<Uploads> = Lazy::new(Uploads::new);
+
+pub fn instance() -> &'static Uploads {
+    &UPLOADS
+}
+
+pub type UploadId = u64;
+
+#[derive(Clone, PartialEq, Eq)]
+pub enum UploadStatus {
+    Queued,
+    Uploading {
+        current: usize,
+        total: Option<usize>,
+    },
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+impl UploadStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            UploadStatus::Done | UploadStatus::Failed(_) | UploadStatus::Cancelled
+        )
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Upload {
+    pub id: UploadId,
+    pub local_path: PathBuf,
+    // the name it's saved under, which changes if the folder already has an item with the same name
+    pub name: String,
+    // folder names leading to the folder it's uploaded to
+    pub destination: Vec<String>,
+    pub status: UploadStatus,
+    pub paused: bool,
+}
+
+impl Upload {
+    pub fn percent(&self) -> usize {
+        match &self.status {
+            UploadStatus::Uploading {
+                current,
+                total: Some(total),
+            } if *total > 0 => current * 100 / total,
+            UploadStatus::Done => 100,
+            _ => 0,
+        }
+    }
+}
+
+struct Job {
+    storage: Storage,
+    handle: Option<JoinHandle<()>>,
+    // whether the upload is paused, watched by the stream reading its file
+    paused: (watch::Sender<bool>, watch::Receiver<bool>),
+}
+
+#[derive(Default)]
+struct Queue {
+    uploads: Vec<Upload>,
+    jobs: HashMap<UploadId, Job>,
+    // names picked by uploads which haven't reached Constellation yet, by destination
+    reserved: HashSet<(Vec<String>, String)>,
+    next_id: UploadId,
+}
+
+pub struct Uploads {
+    queue: Arc<Mutex<Queue>>,
+    slots: Arc<Semaphore>,
+}
+
+impl Uploads {
+    fn new() -> Self {
+        Self {
+            queue: Arc::new(Mutex::new(Queue::default())),
+            slots: Arc::new(Semaphore::new(MAX_CONCURRENT_UPLOADS)),
+        }
+    }
+
+    /// queues `local_path` to be uploaded into the folder at `destination`
+    pub fn enqueue(&self, storage: Storage, local_path: PathBuf, destination: Vec<String>) {
+        let name = match local_path.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => return,
+        };
+        let id = {
+            let mut queue = self.queue.lock().unwrap();
+            queue.next_id += 1;
+            let id = queue.next_id;
+            queue.uploads.push(Upload {
+                id,
+                local_path,
+                name,
+                destination,
+                status: UploadStatus::Queued,
+                paused: false,
+            });
+            queue.jobs.insert(
+                id,
+                Job {
+                    storage,
+                    handle: None,
+                    paused: watch::channel(false),
+                },
+            );
+            id
+        };
+        self.start(id);
+    }
+
+    fn start(&self, id: UploadId) {
+        let queue = self.queue.clone();
+        let slots = self.slots.clone();
+        let handle = tokio::spawn(async move {
+            let _slot = match slots.acquire_owned().await {
+                Ok(slot) => slot,
+                Err(_) => return,
+            };
+            let status = match upload(&queue, id).await {
+                Ok(_) => UploadStatus::Done,
+                Err(error) => UploadStatus::Failed(error.to_string()),
+            };
+            finish(&queue, id, status);
+        });
+        if let Some(job) = self.queue.lock().unwrap().jobs.get_mut(&id) {
+            job.handle = Some(handle);
+        }
+    }
+
+    /// every upload since the queue was last cleared, oldest first
+    pub fn list(&self) -> Vec<Upload> {
+        self.queue.lock().unwrap().uploads.clone()
+    }
+
+    pub fn cancel(&self, id: UploadId) {
+        let handle = {
+            let mut queue = self.queue.lock().unwrap();
+            let running = matches!(
+                queue.uploads.iter().find(|upload| upload.id == id),
+                Some(upload) if !upload.status.is_finished()
+            );
+            queue
+                .jobs
+                .get_mut(&id)
+                .and_then(|job| job.handle.take())
+                .filter(|_| running)
+        };
+        if let Some(handle) = handle {
+            handle.abort();
+            finish(&self.queue, id, UploadStatus::Cancelled);
+        }
+    }
+
+    pub fn pause(&self, id: UploadId) {
+        self.set_paused(id, true);
+    }
+
+    pub fn resume(&self, id: UploadId) {
+        self.set_paused(id, false);
+    }
+
+    fn set_paused(&self, id: UploadId, paused: bool) {
+        let mut queue = self.queue.lock().unwrap();
+        match queue.uploads.iter_mut().find(|upload| upload.id == id) {
+            Some(upload) if !upload.status.is_finished() => upload.paused = paused,
+            _ => return,
+        }
+        if let Some(job) = queue.jobs.get(&id) {
+            let _ = job.paused.0.send(paused);
+        }
+    }
+
+    /// queues a failed or cancelled upload again
+    pub fn retry(&self, id: UploadId) {
+        {
+            let mut queue = self.queue.lock().unwrap();
+            match queue.uploads.iter_mut().find(|upload| upload.id == id) {
+                Some(upload) if !matches!(upload.status, UploadStatus::Done) => {
+                    upload.status = UploadStatus::Queued;
+                    upload.paused = false;
+                    // the name is picked again, the folder may have changed in the meantime
+                    if let Some(name) = upload.local_path.file_name() {
+                        upload.name = name.to_string_lossy().to_string();
+                    }
+                }
+                _ => return,
+            }
+            if let Some(job) = queue.jobs.get(&id) {
+                let _ = job.paused.0.send(false);
+            }
+        }
+        self.start(id);
+    }
+
+    /// stops every upload and empties the queue
+    pub fn clear(&self) {
+        let mut queue = self.queue.lock().unwrap();
+        for job in queue.jobs.values_mut() {
+            if let Some(handle) = job.handle.take() {
+                handle.abort();
+            }
+        }
+        *queue = Queue::default();
+    }
+
+    /// removes uploads which are done, failed or cancelled from the queue
+    pub fn clear_finished(&self) {
+        let mut queue = self.queue.lock().unwrap();
+        let finished: Vec<UploadId> = queue
+            .uploads
+            .iter()
+            .filter(|upload| upload.status.is_finished())
+            .map(|upload| upload.id)
+            .collect();
+        queue
+            .uploads
+            .retain(|upload| !finished.contains(&upload.id));
+        queue.jobs.retain(|id, _| !finished.contains(id));
+    }
+}
+
+fn set_status(queue: &Mutex<Queue>, id: UploadId, status: UploadStatus) {
+    if let Some(upload) = queue
+        .lock()
+        .unwrap()
+        .uploads
+        .iter_mut()
+        .find(|upload| upload.id == id)
+    {
+        upload.status = status;
+    }
+}
+
+fn finish(queue: &Mutex<Queue>, id: UploadId, status: UploadStatus) {
+    let mut queue = queue.lock().unwrap();
+    if let Some(job) = queue.jobs.get_mut(&id) {
+        job.handle = None;
+    }
+    let reserved = match queue.uploads.iter_mut().find(|upload| upload.id == id) {
+        Some(upload) => {
+            upload.status = status;
+            (upload.destination.clone(), upload.name.clone())
+        }
+        None => return,
+    };
+    queue.reserved.remove(&reserved);
+}
+
+async fn upload(queue: &Mutex<Queue>, id: UploadId) -> Result<(), Box<dyn std::error::Error>> {
+    // picks the name to save the file under while holding the queue, so two uploads of files with the
+    // same name into the same folder can't both pick it
+    let (mut storage, paused, local_path, destination, directory, filename) = {
+        let mut queue = queue.lock().unwrap();
+        let (storage, paused) = match queue.jobs.get(&id) {
+            Some(job) => (job.storage.clone(), job.paused.1.clone()),
+            None => return Err(Box::from(Error::InvalidItem)),
+        };
+        let (local_path, destination, original) = match queue.uploads.iter().find(|u| u.id == id) {
+            Some(upload) => (
+                upload.local_path.clone(),
+                upload.destination.clone(),
+                upload.name.clone(),
+            ),
+            None => return Err(Box::from(Error::InvalidItem)),
+        };
+        let directory = directory_at(&storage, &destination)?;
+        let filename = unique_name(&original, |name| {
+            directory.has_item(name)
+                || queue
+                    .reserved
+                    .contains(&(destination.clone(), name.to_string()))
+        });
+        if filename != original {
+            log::info!("Duplicate name, changing file name to {}", &filename);
+        }
+        queue
+            .reserved
+            .insert((destination.clone(), filename.clone()));
+        if let Some(upload) = queue.uploads.iter_mut().find(|u| u.id == id) {
+            upload.name = filename.clone();
+        }
+        (
+            storage,
+            paused,
+            local_path,
+            destination,
+            directory,
+            filename,
+        )
+    };
+
+    let tokio_file = tokio::fs::File::open(&local_path).await?;
+    let total_size_for_stream = match tokio_file.metadata().await {
+        Ok(data) => Some(data.len() as usize),
+        Err(error) => {
+            log::error!("Error getting metadata: {:?}", error);
+            None
+        }
+    };
+    set_status(
+        queue,
+        id,
+        UploadStatus::Uploading {
+            current: 0,
+            total: total_size_for_stream,
+        },
+    );
+
+    let file_stream = ReaderStream::new(tokio_file)
+        .filter_map(|x| async { x.ok() })
+        .then(move |chunk| {
+            let mut paused = paused.clone();
+            async move {
+                loop {
+                    let is_paused = *paused.borrow();
+                    if !is_paused || paused.changed().await.is_err() {
+                        break;
+                    }
+                }
+                chunk
+            }
+        })
+        .map(|x| x.into());
+
+    let switched = switch_path(&storage, &destination).await;
+    let upload_progress = storage
+        .put_stream(&filename, total_size_for_stream, file_stream.boxed())
+        .await;
+    drop(switched);
+
+    let mut upload_progress = upload_progress?;
+    while let Some(upload_progress) = upload_progress.next().await {
+        match upload_progress {
+            Progression::CurrentProgress {
+                name,
+                current,
+                total,
+            } => {
+                log::debug!("Written {} MB for {name}", current / 1024 / 1024);
+                set_status(queue, id, UploadStatus::Uploading { current, total });
+            }
+            Progression::ProgressComplete { name, total } => {
+                log::info!(
+                    "{name} has been uploaded with {} MB",
+                    total.unwrap_or_default() / 1024 / 1024
+                );
+            }
+            Progression::ProgressFailed {
+                name,
+                last_size,
+                error,
+            } => {
+                log::info!(
+                    "{name} failed to upload at {} MB due to: {}",
+                    last_size.unwrap_or_default(),
+                    error.clone().unwrap_or_default()
+                );
+                return Err(Box::from(
+                    error.unwrap_or_else(|| String::from("upload failed")),
+                ));
+            }
+        }
+    }
+    log::info!("{:?} file uploaded!", &filename);
+
+    thumbnails::create_for_upload(&directory, &filename, &local_path).await;
+    Ok(())
+}
@@ -1,27 +1,27 @@
-use std::{
-    cmp::Ordering,
-    ffi::OsStr,
-    io::Cursor,
-    path::{Path, PathBuf},
-};
+use std::{cmp::Ordering, path::PathBuf, time::Duration};
 
 use dioxus::{
     core::to_owned,
-    desktop::{use_window, wry::webview::FileDropEvent, DesktopContext},
+    desktop::{use_window, wry::webview::FileDropEvent},
     events::MouseEvent,
     prelude::*,
 };
 use dioxus_heroicons::outline::Shape;
 
+use folder::Filters;
 use futures::StreamExt;
-use image::io::Reader as ImageReader;
-use mime::*;
+use humansize::{format_size, DECIMAL};
 use rfd::FileDialog;
 use ui_kit::button::Button;
-use warp::{constellation::Progression, error::Error};
 
-use crate::{Storage, DRAG_FILE_EVENT};
-use tokio_util::io::ReaderStream;
+use crate::{components::main::files::operations, Storage, DRAG_FILE_EVENT};
+
+pub mod folder;
+pub mod manager;
+pub mod queue;
+
+// filters are applied once the user stops typing, walking a big folder on every key would be slow
+const SUMMARY_DELAY: Duration = Duration::from_millis(300);
 
 #[derive(Props)]
 pub struct Props<'a> {
@@ -35,6 +35,15 @@ enum Action {
     Stop,
 }
 
+// what a folder upload would do, shown before it starts
+#[derive(Clone, PartialEq)]
+struct Summary {
+    files: usize,
+    folders: usize,
+    size: u64,
+    skipped: usize,
+}
+
 #[allow(non_snake_case)]
 pub fn Upload<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
     let file_storage = cx.props.storage.clone();
@@ -42,6 +51,25 @@ pub fn Upload<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
     let eval_script = use_window(&cx).clone();
     let file_over_dropzone_js = include_str!("./file_over_dropzone.js");
     let file_leave_dropzone_js = include_str!("./file_leave_dropzone.js");
+    // folders dropped or picked, waiting for the user to confirm the upload
+    let pending = use_ref(&cx, || None::<Vec<PathBuf>>);
+    let include = use_state(&cx, String::new);
+    let exclude = use_state(&cx, String::new);
+    let summary = use_future(
+        &cx,
+        (
+            &pending.read().clone(),
+            &include.get().clone(),
+            &exclude.get().clone(),
+        ),
+        |(paths, include, exclude)| async move {
+            let paths = paths?;
+            tokio::time::sleep(SUMMARY_DELAY).await;
+            let summary =
+                tokio::task::spawn_blocking(move || summarize(&paths, &include, &exclude));
+            summary.await.ok()
+        },
+    );
 
     let upload_file_dropped_routine = use_coroutine(&cx, |mut rx: UnboundedReceiver<Action>| {
         to_owned![
@@ -49,7 +77,8 @@ pub fn Upload<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
             drag_over_dropzone,
             eval_script,
             file_leave_dropzone_js,
-            file_over_dropzone_js
+            file_over_dropzone_js,
+            pending
         ];
         async move {
             while let Some(action) = rx.next().await {
@@ -88,15 +117,7 @@ pub fn Upload<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
 
                             if let FileDropEvent::Dropped(files_local_path) = drag_file_event {
                                 *drag_over_dropzone.write_silent() = false;
-                                for file_path in &files_local_path {
-                                    upload_file(
-                                        file_storage.clone(),
-                                        file_path.clone(),
-                                        eval_script.clone(),
-                                    )
-                                    .await;
-                                    log::info!("{} file uploaded!", file_path.to_string_lossy());
-                                }
+                                upload_paths(&file_storage, files_local_path, &pending);
                                 // TODO(use_eval): Try new solution in the future
                                 eval_script.eval(&file_leave_dropzone_js);
                             }
@@ -169,17 +190,19 @@ pub fn Upload<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
                                     Some(path) => path,
                                     None => return
                                 };
-                                let file_storage = cx.props.storage.clone();
-                                cx.spawn({
-                                    to_owned![file_storage, files_local_path, eval_script];
-                                    async move {
-                                        for file_path in &files_local_path {
-                                            upload_file(file_storage.clone(), file_path.clone(), eval_script.clone()).await;
-                                        }
-                                    }
-                                });
+                                enqueue_uploads(&cx.props.storage, &files_local_path);
                             }
                         }
+                        Button {
+                            icon: Shape::FolderArrowDown,
+                            text: String::from("Upload folder"),
+                            state: ui_kit::button::State::Secondary,
+                            on_pressed: move |_| {
+                                if let Some(folder) = FileDialog::new().set_directory(".").pick_folder() {
+                                    upload_paths(&cx.props.storage, vec![folder], pending);
+                                }
+                            },
+                        },
                         hr {
                            class: "hr-between-input-and-dropzone",
                         }
@@ -228,6 +251,73 @@ pub fn Upload<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
                         }
                     }
                 },
+                pending.read().is_some().then(|| {
+                    let (text, valid) = match summary.value().cloned().flatten() {
+                        Some(Ok(summary)) => (
+                            format!(
+                                "{} files ({}) in {} folders{}",
+                                summary.files,
+                                format_size(summary.size, DECIMAL),
+                                summary.folders,
+                                match summary.skipped {
+                                    0 => String::new(),
+                                    skipped => format!(", {skipped} left out by the filters"),
+                                }
+                            ),
+                            summary.files > 0,
+                        ),
+                        Some(Err(error)) => (format!("Invalid pattern: {error}"), false),
+                        None => (String::new(), false),
+                    };
+                    rsx!(
+                        div {
+                            id: "folder-upload",
+                            div {
+                                class: "filters",
+                                input {
+                                    class: "new_folder_input",
+                                    placeholder: "Only include, e.g. *.jpg, *.png",
+                                    value: "{include}",
+                                    oninput: move |evt| include.set(evt.value.clone()),
+                                },
+                                input {
+                                    class: "new_folder_input",
+                                    placeholder: "Leave out, e.g. .git, *.tmp",
+                                    value: "{exclude}",
+                                    oninput: move |evt| exclude.set(evt.value.clone()),
+                                },
+                            },
+                            p {
+                                class: "summary",
+                                "{text}"
+                            },
+                            div {
+                                class: "actions",
+                                Button {
+                                    text: String::from("Cancel"),
+                                    state: ui_kit::button::State::Secondary,
+                                    on_pressed: move |_| *pending.write() = None,
+                                },
+                                Button {
+                                    icon: Shape::ArrowUpTray,
+                                    text: String::from("Upload"),
+                                    disabled: !valid,
+                                    on_pressed: move |_| {
+                                        let paths = pending.write().take().unwrap_or_default();
+                                        let filters = match Filters::parse(include, exclude) {
+                                            Ok(filters) => filters,
+                                            Err(_) => return,
+                                        };
+                                        let storage = cx.props.storage.clone();
+                                        let destination = operations::shown_path();
+                                        // carries on when the panel is closed
+                                        cx.spawn_forever(folder::start(storage, paths, filters, destination));
+                                    },
+                                },
+                            }
+                        }
+                    )
+                }),
                 div {
                     id: "close",
                     Button {
@@ -248,167 +338,34 @@ fn get_drag_file_event() -> FileDropEvent {
     drag_file_event
 }
 
-async fn upload_file(file_storage: Storage, file_path: PathBuf, eval_script: DesktopContext) {
-    let mut filename = match file_path
-        .file_name()
-        .map(|file| file.to_string_lossy().to_string())
-    {
-        Some(file) => file,
-        None => return,
-    };
-
-    let local_path = Path::new(&file_path).to_string_lossy().to_string();
-    let mut count_index_for_duplicate_filename = 1;
-    let mut file_storage = file_storage.clone();
-    let current_directory = match file_storage.current_directory() {
-        Ok(current_directory) => current_directory,
-        Err(error) => {
-            log::error!("Not possible to get current directory, error: {:?}", error);
-            return;
-        }
-    };
-    let original = filename.clone();
-    let file = PathBuf::from(&original);
-
-    loop {
-        if !current_directory.has_item(&filename) {
-            break;
-        }
-        let file_extension = file.extension().and_then(OsStr::to_str).map(str::to_string);
-        let file_stem = file.file_stem().and_then(OsStr::to_str).map(str::to_string);
-
-        filename = match (file_stem, file_extension) {
-            (Some(file_stem), Some(file_extension)) => {
-                format!("{file_stem} ({count_index_for_duplicate_filename}).{file_extension}")
-            }
-            _ => format!("{original} ({count_index_for_duplicate_filename})"),
-        };
+fn summarize(paths: &[PathBuf], include: &str, exclude: &str) -> Result<Summary, String> {
+    let filters = Filters::parse(include, exclude).map_err(|error| error.to_string())?;
+    let plan = folder::plan(paths, &filters);
+    Ok(Summary {
+        files: plan.files.len(),
+        folders: plan.folders.len(),
+        size: plan.total_size(),
+        skipped: plan.skipped,
+    })
+}
 
-        log::info!("Duplicate name, changing file name to {}", &filename);
-        count_index_for_duplicate_filename += 1;
+// files are queued straight away, anything with folders waits for the user to look at the summary
+fn upload_paths(
+    file_storage: &Storage,
+    paths: Vec<PathBuf>,
+    pending: &UseRef<Option<Vec<PathBuf>>>,
+) {
+    if paths.iter().any(|path| path.is_dir()) {
+        *pending.write() = Some(paths);
+    } else {
+        enqueue_uploads(file_storage, &paths);
     }
-
-    let tokio_file = match tokio::fs::File::open(&local_path).await {
-        Ok(file) => file,
-        Err(error) => {
-            log::error!("Error on get tokio file, cancelling upload action, error: {error}");
-            return;
-        }
-    };
-
-    let total_size_for_stream = match tokio_file.metadata().await {
-        Ok(data) => Some(data.len() as usize),
-        Err(error) => {
-            log::error!("Error getting metadata: {:?}", error);
-            None
-        }
-    };
-
-    let file_stream = ReaderStream::new(tokio_file)
-        .filter_map(|x| async { x.ok() })
-        .map(|x| x.into());
-
-    match file_storage
-        .put_stream(&filename, total_size_for_stream, file_stream.boxed())
-        .await
-    {
-        Ok(mut upload_progress) => {
-            while let Some(upload_progress) = upload_progress.next().await {
-                match upload_progress {
-                    Progression::CurrentProgress {
-                        name,
-                        current,
-                        total,
-                    } => {
-                        log::info!("Written {} MB for {name}", current / 1024 / 1024);
-                        if let Some(total) = total {
-                            let mut selector_without_percentage =
-                                "document.getElementById('dropzone').value = '".to_owned();
-
-                            let percentage =
-                                ((((current as f64) / (total as f64)) * 100.) as usize).to_string();
-                            selector_without_percentage.push_str(&percentage);
-
-                            let ending_string = "% uploaded'";
-                            selector_without_percentage.push_str(ending_string);
-
-                            eval_script.eval(&selector_without_percentage);
-
-                            log::info!(
-                                "{}% completed",
-                                (((current as f64) / (total as f64)) * 100.) as usize
-                            )
-                        }
-                    }
-                    Progression::ProgressComplete { name, total } => {
-                        log::info!(
-                            "{name} has been uploaded with {} MB",
-                            total.unwrap_or_default() / 1024 / 1024
-                        );
-                    }
-                    Progression::ProgressFailed {
-                        name,
-                        last_size,
-                        error,
-                    } => {
-                        log::info!(
-                            "{name} failed to upload at {} MB due to: {}",
-                            last_size.unwrap_or_default(),
-                            error.unwrap_or_default()
-                        );
-                    }
-                }
-            }
-            log::info!("{:?} file uploaded!", &filename);
-
-            match set_thumbnail_if_file_is_image(file_storage.clone(), filename.clone()).await {
-                Ok(success) => log::info!("{:?}", success),
-                Err(error) => log::error!("Error on update thumbnail: {:?}", error),
-            }
-        }
-        Err(error) => log::error!("Error to upload file: {:?}, error: {:?}", &filename, error),
-    };
 }
 
-async fn set_thumbnail_if_file_is_image(
-    file_storage: Storage,
-    filename_to_save: String,
-) -> Result<String, Box<dyn std::error::Error>> {
-    let item = file_storage.root_directory().get_item(&filename_to_save)?;
-    let parts_of_filename: Vec<&str> = filename_to_save.split('.').collect();
-
-    let file = file_storage.get_buffer(&filename_to_save).await?;
-
-    // Guarantee that is an image that has been uploaded
-    let image = ImageReader::new(Cursor::new(&file))
-        .with_guessed_format()?
-        .decode()?;
-
-    let image_thumbnail = image.thumbnail(70, 70);
-
-    // Since files selected are filtered to be jpg, jpeg, png or svg the last branch is not reachable
-    let mime = match parts_of_filename
-        .iter()
-        .map(|extension| extension.to_lowercase())
-        .last()
-    {
-        Some(m) => match m.as_str() {
-            "png" => IMAGE_PNG.to_string(),
-            "jpg" => IMAGE_JPEG.to_string(),
-            "jpeg" => IMAGE_JPEG.to_string(),
-            "svg" => IMAGE_SVG.to_string(),
-            &_ => "".to_string(),
-        },
-        None => "".to_string(),
-    };
-
-    if !file.is_empty() || !mime.is_empty() {
-        let prefix = format!("data:{};base64,", mime);
-        let base64_image = base64::encode(&file);
-        let img = prefix + base64_image.as_str();
-        item.set_thumbnail(&img);
-        Ok(format_args!("{} thumbnail updated with success!", item.name()).to_string())
-    } else {
-        Err(Box::from(Error::InvalidItem))
+// queues files to be uploaded into the folder being browsed
+fn enqueue_uploads(file_storage: &Storage, files_local_path: &[PathBuf]) {
+    let destination = operations::shown_path();
+    for file_path in files_local_path {
+        manager::instance().enqueue(file_storage.clone(), file_path.clone(), destination.clone());
     }
 }
@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use dioxus::prelude::*;
+use dioxus_heroicons::outline::Shape;
+use ui_kit::button::Button;
+
+use crate::components::main::files::upload::manager::{self, Upload, UploadStatus};
+
+fn status_text(upload: &Upload) -> String {
+    match &upload.status {
+        UploadStatus::Queued | UploadStatus::Uploading { .. } if upload.paused => {
+            format!("Paused at {}%", upload.percent())
+        }
+        UploadStatus::Queued => String::from("Waiting"),
+        UploadStatus::Uploading { .. } => format!("{}%", upload.percent()),
+        UploadStatus::Done => String::from("Uploaded"),
+        UploadStatus::Failed(error) => format!("Failed: {error}"),
+        UploadStatus::Cancelled => String::from("Cancelled"),
+    }
+}
+
+#[allow(non_snake_case)]
+pub fn UploadQueue(cx: Scope) -> Element {
+    let uploads = use_state(&cx, || manager::instance().list());
+    let collapsed = use_state(&cx, || false);
+
+    // the queue lives outside the page, so it's checked for changes
+    use_future(&cx, (uploads,), |(uploads,)| async move {
+        loop {
+            let updated = manager::instance().list();
+            if *uploads.current() != updated {
+                uploads.set(updated);
+            }
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+    });
+
+    if uploads.is_empty() {
+        return None;
+    }
+
+    let active = uploads
+        .iter()
+        .filter(|upload| !upload.status.is_finished())
+        .count();
+    let failed = uploads
+        .iter()
+        .filter(|upload| matches!(upload.status, UploadStatus::Failed(_)))
+        .count();
+    let mut title = match active {
+        0 => String::from("Uploads"),
+        _ => format!("Uploading {active} file(s)"),
+    };
+    if failed > 0 {
+        title.push_str(&format!(", {failed} failed"));
+    }
+    let chevron = match **collapsed {
+        true => Shape::ChevronUp,
+        false => Shape::ChevronDown,
+    };
+
+    cx.render(rsx! {
+        div {
+            id: "upload-queue",
+            div {
+                class: "header",
+                span {
+                    onclick: move |_| collapsed.set(!**collapsed),
+                    "{title}"
+                },
+                Button {
+                    icon: Shape::Trash,
+                    text: String::from("Clear finished"),
+                    state: ui_kit::button::State::Transparent,
+                    on_pressed: move |_| {
+                        manager::instance().clear_finished();
+                        uploads.set(manager::instance().list());
+                    },
+                },
+                Button {
+                    icon: chevron,
+                    state: ui_kit::button::State::Transparent,
+                    on_pressed: move |_| collapsed.set(!**collapsed),
+                },
+            },
+            (!**collapsed).then(|| rsx!(
+                div {
+                    class: "uploads",
+                    uploads.iter().map(|upload| {
+                        let id = upload.id;
+                        let name = &upload.name;
+                        let percent = upload.percent();
+                        let status = status_text(upload);
+                        let state_class = match upload.status {
+                            UploadStatus::Failed(_) => "failed",
+                            UploadStatus::Done => "done",
+                            _ => "",
+                        };
+                        let finished = upload.status.is_finished();
+                        let paused = upload.paused;
+                        let pause_icon = match paused {
+                            true => Shape::Play,
+                            false => Shape::Pause,
+                        };
+                        let can_retry = matches!(upload.status, UploadStatus::Failed(_) | UploadStatus::Cancelled);
+
+                        rsx!(
+                            div {
+                                key: "{id}",
+                                class: "upload {state_class}",
+                                div {
+                                    class: "details",
+                                    span {
+                                        class: "name",
+                                        title: "{name}",
+                                        "{name}"
+                                    },
+                                    span {
+                                        class: "status",
+                                        "{status}"
+                                    },
+                                    (!finished).then(|| rsx!(
+                                        Button {
+                                            icon: pause_icon,
+                                            state: ui_kit::button::State::Transparent,
+                                            on_pressed: move |_| match paused {
+                                                true => manager::instance().resume(id),
+                                                false => manager::instance().pause(id),
+                                            },
+                                        },
+                                        Button {
+                                            icon: Shape::XMark,
+                                            state: ui_kit::button::State::Transparent,
+                                            on_pressed: move |_| manager::instance().cancel(id),
+                                        }
+                                    )),
+                                    can_retry.then(|| rsx!(
+                                        Button {
+                                            icon: Shape::ArrowPath,
+                                            state: ui_kit::button::State::Transparent,
+                                            on_pressed: move |_| manager::instance().retry(id),
+                                        }
+                                    )),
+                                },
+                                div {
+                                    class: "bar",
+                                    div {
+                                        class: "fill",
+                                        style: "width: {percent}%;",
+                                    }
+                                }
+                            }
+                        )
+                    })
+                }
+            )),
+        }
+    })
+}
@@ -6,16 +6,37 @@ use dioxus::{
 };
 use dioxus_heroicons::outline::Shape;
 use dioxus_toast::{Position, ToastInfo};
+use rfd::FileDialog;
 
-use crate::{Account, LANGUAGE, TOAST_MANAGER};
+use crate::{
+    components::reusable::{ban_warning::BanWarning, friend_code::FriendCode},
+    iutils::{
+        ban_list::{self, Ban},
+        friend_requests, invite,
+    },
+    language::Language,
+    Account, LANGUAGE, TOAST_MANAGER,
+};
 
 use ui_kit::{
     button::Button,
     input::{Input, SelectOption},
+    popup::Popup,
 };
 
 use warp::{crypto::DID, multipass::identity::Identifier};
 
+/// what to tell the user when a friend request couldn't be sent
+pub fn request_error(l: &Language, e: warp::error::Error) -> String {
+    match e {
+        warp::error::Error::CannotSendFriendRequest => l.couldnt_send.to_string(),
+        warp::error::Error::FriendRequestExist => l.already_sent.to_string(),
+        warp::error::Error::CannotSendSelfFriendRequest => l.add_self.to_string(),
+        warp::error::Error::FriendExist => l.friend_exist.to_string(),
+        _ => l.something_went_wrong.to_string(),
+    }
+}
+
 #[inline_props]
 #[allow(non_snake_case)]
 pub fn FindFriends(
@@ -25,8 +46,11 @@ pub fn FindFriends(
     is_compact: bool,
 ) -> Element {
     let toast = use_atom_ref(&cx, TOAST_MANAGER);
-    let l = use_atom_ref(&cx, LANGUAGE).read();
+    let language = use_atom_ref(&cx, LANGUAGE);
+    let l = language.read();
     let remote_friend = use_state(&cx, String::new);
+    // the request itself can't carry it, so it's kept as a reminder and sent once they accept
+    let note = use_state(&cx, String::new);
 
     let l2 = l.clone();
     let l3 = l.clone();
@@ -42,13 +66,34 @@ pub fn FindFriends(
             };
             let _id = toast.write().popup(single_toast); // copy to the clipboard without prefix 'did:key:'
             clipboard
-                .set_text(&ident.did_key().to_string()[8..])
+                .set_text(&invite::friend_code(&ident.did_key()))
                 .unwrap();
         }
     };
     let copy_friend_id2 = copy_friend_id.clone();
 
     let search_results = use_state(&cx, Vec::<SelectOption>::new);
+    // a request to a banned account waits until the user confirms it
+    let pending_ban = use_state(&cx, || None::<(DID, Ban)>);
+    let show_qr_code = use_state(&cx, || false);
+
+    // the friend code is put in the box, to be sent like a typed one
+    let scan_qr_code = move || {
+        let path = match FileDialog::new()
+            .add_filter("image", &["png", "jpg", "jpeg", "gif", "bmp", "webp"])
+            .pick_file()
+        {
+            Some(path) => path,
+            None => return,
+        };
+        match invite::decode_qr_code(&path) {
+            Ok(did) => {
+                remote_friend.set(invite::friend_code(&did));
+                add_error.set("".into());
+            }
+            Err(e) => add_error.set(e),
+        }
+    };
 
     let on_change = move |ev: FormEvent| {
         let value = ev.data.value.clone();
@@ -66,6 +111,7 @@ pub fn FindFriends(
         if let Ok(results) = account.get_identity(Identifier::user_name(value.as_str())) {
             let opts = results
                 .iter()
+                .filter(|result| !ban_list::is_banned(&result.did_key()))
                 .map(|result| SelectOption {
                     value: result.did_key().to_string().replace("did:key:", ""),
                     label: format!("{}#{}", result.username(), result.short_id()),
@@ -76,6 +122,25 @@ pub fn FindFriends(
         };
     };
 
+    let send_request = move |did: DID| {
+        let l = language.read();
+        match account.clone().send_request(&did) {
+            Ok(_) => {
+                let single_toast = ToastInfo {
+                    position: Position::TopRight,
+                    ..ToastInfo::simple(&l.request_sent)
+                };
+                let _id = toast.write().popup(single_toast);
+                add_error.set("".into());
+                if let Err(e) = friend_requests::set_note(&did, note.get()) {
+                    log::error!("failed to save the note on the request to {did}: {e}");
+                }
+                note.set(String::new());
+            }
+            Err(e) => add_error.set(request_error(&l, e)),
+        }
+    };
+
     cx.render(rsx!(
         div {
             id: "find-friends",
@@ -83,7 +148,7 @@ pub fn FindFriends(
                 "{l.add_someone}",
             },
             div {
-                class: "add",  
+                class: "add",
                 Input {
                     placeholder: l.add_placeholder.clone(),
                     on_change: on_change,
@@ -100,36 +165,28 @@ pub fn FindFriends(
                     on_pressed: move |e: UiEvent<MouseData>| {
                         e.cancel_bubble();
 
-                        let did = DID::try_from(format!("did:key:{}", remote_friend.clone()));
-                        match did {
-                            Ok(d) => {
-                                match account.clone()
-                                    .send_request(&d)
-                                {
-                                    Ok(_) => {
-                                        let single_toast = ToastInfo {
-                                            position: Position::TopRight,
-                                            ..ToastInfo::simple(&l2.request_sent)
-                                        };
-                                        let _id = toast.write().popup(single_toast);
-                                        add_error.set("".into());
-                                    }
-                                    Err(e) => {
-                                        add_error.set(match e {
-                                            warp::error::Error::CannotSendFriendRequest => l2.couldnt_send.to_string(),
-                                            warp::error::Error::FriendRequestExist => l2.already_sent.to_string(),
-                                            warp::error::Error::CannotSendSelfFriendRequest => l2.add_self.to_string(),
-                                            warp::error::Error::FriendExist => l2.friend_exist.to_string(),
-                                            _ => l2.something_went_wrong.to_string()
-                                        })
-                                    },
-                                };
+                        // an invite link pasted in works as well
+                        match invite::parse(remote_friend.get()) {
+                            Some(d) => match ban_list::check(&d) {
+                                Some(ban) => pending_ban.set(Some((d, ban))),
+                                None => send_request(d),
                             },
-                            Err(_) => add_error.set(l2.invalid_code.to_string()),
+                            None => add_error.set(l2.invalid_code.to_string()),
                         }
                         remote_friend.set("".into());
                     },
                 },
+                span {
+                    title: "Scan a QR code from an image",
+                    Button {
+                        icon: Shape::QrCode,
+                        state: ui_kit::button::State::Secondary,
+                        on_pressed: move |e: UiEvent<MouseData>| {
+                            e.cancel_bubble();
+                            scan_qr_code();
+                        }
+                    }
+                },
                 is_compact.then(|| rsx!{
                     span {
                         title: "{l2.copy_friend_code}",
@@ -140,13 +197,51 @@ pub fn FindFriends(
                                 copy_friend_id();
                             }
                         }
+                    },
+                    span {
+                        title: "Show your QR code",
+                        Button {
+                            icon: Shape::Share,
+                            on_pressed: move |e: UiEvent<MouseData>| {
+                                e.cancel_bubble();
+                                show_qr_code.set(true);
+                            }
+                        }
                     }
                 }),
             },
+            div {
+                class: "request-note",
+                Input {
+                    icon: Shape::PencilSquare,
+                    placeholder: String::from("Note, sent once they accept (optional)"),
+                    value: note.get().clone(),
+                    on_change: move |evt: FormEvent| note.set(evt.value.clone()),
+                    on_enter: move |_| {},
+                },
+            },
             div {
                 class: "error_text",
                 "{add_error}"
             },
+            pending_ban.get().clone().map(|(did, ban)| rsx!(
+                Popup {
+                    hidden: false,
+                    on_dismiss: move |_| pending_ban.set(None),
+                    children: cx.render(rsx!(
+                        BanWarning {
+                            ban: ban,
+                            continue_text: String::from("Send Request Anyway"),
+                            cancel_text: String::from("Cancel"),
+                            on_continue: move |_| {
+                                pending_ban.set(None);
+                                send_request(did.clone());
+                            },
+                            on_cancel: move |_| pending_ban.set(None),
+                        }
+                    )),
+                }
+            )),
             (!is_compact).then(|| rsx!{
                 div {
                     class: "copy-friend-code",
@@ -165,8 +260,31 @@ pub fn FindFriends(
                             }
                         }
                     },
+                    div {
+                        class: "code",
+                        Button {
+                            text: String::from("Show QR Code"),
+                            icon: Shape::QrCode,
+                            state: ui_kit::button::State::Secondary,
+                            on_pressed: move |e: UiEvent<MouseData>| {
+                                e.cancel_bubble();
+                                show_qr_code.set(true);
+                            }
+                        }
+                    },
                 }
             }),
+            show_qr_code.then(|| rsx!(
+                Popup {
+                    hidden: false,
+                    on_dismiss: move |_| show_qr_code.set(false),
+                    children: cx.render(rsx!(
+                        FriendCode {
+                            account: account.clone(),
+                        }
+                    )),
+                }
+            )),
         }
     ))
 }
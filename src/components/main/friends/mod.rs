@@ -8,14 +8,24 @@ pub mod users_list;
 use crate::{
     components::main::friends::sidebar::Sidebar,
     components::main::friends::{
-        find::FindFriends, list_type_button::ListTypeButton, requests::FriendRequests,
+        find::{request_error, FindFriends},
+        list_type_button::ListTypeButton,
+        requests::FriendRequests,
         users_list::UsersList,
     },
-    components::reusable::nav::Nav,
-    Account, Messaging,
+    components::reusable::{ban_warning::BanWarning, nav::Nav, profile_modal::ProfileModal},
+    iutils::{ban_list, get_username_from_did, invite},
+    Account, Messaging, LANGUAGE, TOAST_MANAGER,
 };
 
-use dioxus::prelude::*;
+use dioxus::{prelude::*, router::use_router};
+use dioxus_heroicons::outline::Shape;
+use dioxus_toast::ToastInfo;
+use ui_kit::{
+    button::{self, Button},
+    popup::Popup,
+};
+use warp::crypto::DID;
 
 #[derive(Clone, PartialEq)]
 struct UsernameAndDID {
@@ -35,6 +45,73 @@ pub fn Friends(cx: Scope<Props>) -> Element {
     let add_error = use_state(&cx, String::new);
 
     let show_friend_list = use_state(&cx, || true);
+    let toast = use_atom_ref(&cx, TOAST_MANAGER);
+    let router = use_router(&cx);
+    let language = use_atom_ref(&cx, LANGUAGE);
+    // an invite link the app was opened with
+    let pending_invite = use_state(&cx, invite::take_pending);
+
+    let accept_invite = move |did: &DID| {
+        pending_invite.set(None);
+        match cx.props.account.clone().send_request(did) {
+            Ok(_) => {
+                toast
+                    .write()
+                    .popup(ToastInfo::simple(&language.read().request_sent));
+                add_error.set("".into());
+            }
+            Err(e) => add_error.set(request_error(&language.read(), e)),
+        }
+    };
+    let invite_popup = pending_invite.get().as_ref().map(|did| {
+        let username = get_username_from_did(did.clone(), &cx.props.account);
+        let code = invite::friend_code(did);
+        let content = match ban_list::check(did) {
+            Some(ban) => rsx!(BanWarning {
+                ban: ban,
+                continue_text: String::from("Send Request Anyway"),
+                cancel_text: String::from("Cancel"),
+                on_continue: move |_| accept_invite(did),
+                on_cancel: move |_| pending_invite.set(None),
+            }),
+            None => rsx!(
+                div {
+                    class: "invite",
+                    h3 {
+                        "Send a friend request?"
+                    },
+                    (!username.is_empty()).then(|| rsx!(
+                        p {
+                            class: "username",
+                            "{username}"
+                        }
+                    )),
+                    p {
+                        class: "code",
+                        "{code}"
+                    },
+                    div {
+                        class: "invite-controls",
+                        Button {
+                            text: String::from("Cancel"),
+                            state: button::State::Secondary,
+                            on_pressed: move |_| pending_invite.set(None),
+                        },
+                        Button {
+                            icon: Shape::UserPlus,
+                            text: String::from("Send Request"),
+                            on_pressed: move |_| accept_invite(did),
+                        },
+                    }
+                }
+            ),
+        };
+        rsx!(Popup {
+            hidden: false,
+            on_dismiss: move |_| pending_invite.set(None),
+            children: cx.render(content),
+        })
+    });
 
     let incoming_requests = cx
         .props
@@ -54,6 +131,12 @@ pub fn Friends(cx: Scope<Props>) -> Element {
             id: "friends",
             class: "mobile-sidebar-hidden",
             Sidebar { account: cx.props.account.clone(), messaging: cx.props.messaging.clone(), add_error: add_error.clone()},
+            invite_popup,
+            ProfileModal {
+                account: cx.props.account.clone(),
+                messaging: cx.props.messaging.clone(),
+                on_chat: move |_| router.push_route("/main", None, None),
+            },
             div {
                 id: "content",
                 div {
@@ -15,6 +15,8 @@ use warp::multipass::identity::FriendRequest;
 pub struct Props<'a> {
     account: Account,
     request: FriendRequest,
+    // shown under the name, like mutual friends or the note left on a sent request
+    detail: String,
     deny_only: bool,
     on_deny: EventHandler<'a, ()>,
     on_accept: EventHandler<'a, ()>,
@@ -53,7 +55,13 @@ pub fn FriendRequest<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
                 )} else {rsx!(
                     h3 {
                         "{username}",
-                    }
+                    },
+                    (!cx.props.detail.is_empty()).then(|| rsx!(
+                        p {
+                            class: "detail",
+                            "{cx.props.detail}"
+                        }
+                    ))
                 )}
             },
             div {
@@ -1,9 +1,19 @@
 use crate::components::main::friends::request::FriendRequest;
-use crate::{Account, LANGUAGE};
+use crate::{
+    iutils::{
+        ban_list,
+        friend_requests::{self, Order},
+        get_username_from_did,
+    },
+    Account, LANGUAGE, STATE, TOAST_MANAGER,
+};
 
 use std::{collections::HashSet, time::Duration};
 
-use dioxus::prelude::*;
+use dioxus::{events::FormEvent, prelude::*};
+use dioxus_heroicons::outline::Shape;
+use dioxus_toast::ToastInfo;
+use ui_kit::{input::Input, select::Select};
 
 #[inline_props]
 #[allow(non_snake_case)]
@@ -13,6 +23,10 @@ pub fn FriendRequests(cx: Scope, account: Account, add_error: UseState<String>)
     let l = use_atom_ref(&cx, LANGUAGE).read();
     let incomingRequestsLang = { l.incoming_requests.to_string() };
     let outgoingRequestsLang = { l.outgoing_requests.to_string() };
+    let toast = use_atom_ref(&cx, TOAST_MANAGER);
+    let state = use_atom_ref(&cx, STATE);
+    let search = use_state(&cx, String::new);
+    let order = use_state(&cx, || Order::Newest);
 
     let incoming: &UseState<HashSet<_>> = use_state(&cx, || {
         HashSet::from_iter(account.list_incoming_request().unwrap_or_default())
@@ -46,49 +60,88 @@ pub fn FriendRequests(cx: Scope, account: Account, add_error: UseState<String>)
         },
     );
 
+    let report = move |result: Result<(), warp::error::Error>, action: &str| match result {
+        Ok(_) => add_error.set("".into()),
+        Err(e) => {
+            log::error!("failed to {action} friend request: {e}");
+            toast.write().popup(ToastInfo::simple(&format!(
+                "Couldn't {action} the friend request: {e}"
+            )));
+        }
+    };
+
+    // requests from banned accounts are denied as they come in, older ones are left out
+    let query = search.to_lowercase();
+    let mut shown_incoming: Vec<_> = incoming
+        .iter()
+        .filter(|request| !ban_list::is_banned(&request.from()))
+        .map(|request| (get_username_from_did(request.from(), account), request))
+        .filter(|(username, _)| query.is_empty() || username.to_lowercase().contains(&query))
+        .collect();
+    match **order {
+        Order::Newest => {
+            shown_incoming.sort_by_key(|(_, request)| std::cmp::Reverse(request.date()))
+        }
+        Order::Oldest => shown_incoming.sort_by_key(|(_, request)| request.date()),
+        Order::Name => shown_incoming.sort_by_key(|(username, _)| username.to_lowercase()),
+    }
+    let has_incoming = incoming
+        .iter()
+        .any(|request| !ban_list::is_banned(&request.from()));
+    let friends = account.list_friends().unwrap_or_default();
+    let notes = friend_requests::notes();
+
     cx.render(rsx! {
         div {
             div {
-                (!incoming.is_empty()).then(|| rsx!(
+                has_incoming.then(|| rsx!(
                     label {
                     "{incomingRequestsLang}"
                     },
                     div {
-                        incoming.iter().map(|request| rsx!(
-                            FriendRequest {
-                                account: account.clone(),
-                                request: request.clone(),
-                                on_accept: move |_| {
-                                    match account.clone()
-                                        .accept_request(&request.from())
-                                    {
-                                        Ok(_) => {
-                                            add_error.set("".into());
-                                        },
-                                        Err(e) => {
-                                            // TODO: Catch this and display it
-                                            log::error!("error accepting friend request: {e}");
-                                            println!("Error");
-                                        },
-                                    }
-                                },
-                                on_deny: move |_| {
-                                    match account.clone()
-                                        .deny_request(&request.from())
-                                    {
-                                        Ok(_) => {
-                                            add_error.set("".into());
-                                        },
-                                        Err(e) => {
-                                            // TODO: Catch this and display it
-                                            log::error!("error denying friend request: {e}");
-                                            println!("Error");
-                                        },
-                                    }
-                                },
-                                deny_only: false,
+                        class: "request-filters",
+                        Input {
+                            icon: Shape::MagnifyingGlass,
+                            placeholder: String::from("Search requests"),
+                            value: search.to_string(),
+                            on_change: move |evt: FormEvent| search.set(evt.value.clone()),
+                            on_enter: move |_| {},
+                        },
+                        Select {
+                            options: Order::ALL.iter().map(|order| order.label().to_string()).collect(),
+                            on_change: move |label: String| {
+                                if let Some(new_order) = Order::ALL.into_iter().find(|o| o.label() == label) {
+                                    order.set(new_order);
+                                }
                             }
-                        )),
+                        },
+                    },
+                    div {
+                        shown_incoming.into_iter().map(|(_, request)| {
+                            let mutual = friend_requests::mutual_friends(&state.read(), &friends, &request.from());
+                            let detail = match mutual.iter().collect::<Vec<_>>().as_slice() {
+                                [] => String::new(),
+                                [friend] => format!(
+                                    "In a group chat with {}",
+                                    get_username_from_did((*friend).clone(), account)
+                                ),
+                                more => format!("In group chats with {} of your friends", more.len()),
+                            };
+                            rsx!(
+                                FriendRequest {
+                                    account: account.clone(),
+                                    request: request.clone(),
+                                    detail: detail,
+                                    on_accept: move |_| {
+                                        report(account.clone().accept_request(&request.from()), "accept");
+                                    },
+                                    on_deny: move |_| {
+                                        report(account.clone().deny_request(&request.from()), "deny");
+                                    },
+                                    deny_only: false,
+                                }
+                            )
+                        }),
                     }
                 )),
                 (!outgoing.is_empty()).then(|| rsx!(
@@ -100,19 +153,13 @@ pub fn FriendRequests(cx: Scope, account: Account, add_error: UseState<String>)
                             FriendRequest {
                                 account: account.clone(),
                                 request: request.clone(),
+                                detail: notes.get(&request.to().to_string()).cloned().unwrap_or_default(),
                                 on_deny:  move |_| {
-                                    match account.clone()
-                                        .close_request(&request.to())
-                                    {
-                                        Ok(_) => {
-                                            add_error.set("".into());
-                                        },
-                                        Err(e) => {
-                                            // TODO: Catch this and display it
-                                            log::error!("error canceling friend request: {e}");
-                                            println!("Error");
-                                        },
+                                    let result = account.clone().close_request(&request.to());
+                                    if result.is_ok() {
+                                        friend_requests::remove_note(&request.to());
                                     }
+                                    report(result, "cancel");
                                 },
                                 on_accept: move |_| {},
                                 deny_only: true,
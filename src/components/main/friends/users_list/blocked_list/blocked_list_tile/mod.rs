@@ -11,18 +11,26 @@ use warp::crypto::DID;
 use crate::iutils;
 use utils::Account;
 
-#[derive(Props, PartialEq)]
-pub struct Props {
+#[derive(Props)]
+pub struct Props<'a> {
     account: Account,
     friend: DID,
     friend_username: String,
+    // when and why they were blocked, empty when it was from another device
+    since: String,
+    reason: String,
+    selected: bool,
+    on_select: EventHandler<'a, bool>,
+    on_unblock: EventHandler<'a, ()>,
 }
 
 #[allow(non_snake_case)]
-pub fn BlockedListTile(cx: Scope<Props>) -> Element {
+pub fn BlockedListTile<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
     log::debug!("rendering Blocked Users");
 
     let mp = cx.props.account.clone();
+    let since = &cx.props.since;
+    let reason = &cx.props.reason;
 
     let username = cx.props.friend_username.clone();
     let show_skeleton = username.is_empty();
@@ -31,7 +39,12 @@ pub fn BlockedListTile(cx: Scope<Props>) -> Element {
 
     cx.render(rsx! {
         div {
-            class: "friend",
+            class: "friend blocked",
+            input {
+                "type": "checkbox",
+                checked: "{cx.props.selected}",
+                oninput: move |_| cx.props.on_select.call(!cx.props.selected),
+            },
             if show_skeleton {rsx!(
                 PFPSkeleton {}
             )} else {rsx!(
@@ -52,7 +65,19 @@ pub fn BlockedListTile(cx: Scope<Props>) -> Element {
                         inline: true,
                         remote_did: cx.props.friend.clone(),
                         account: cx.props.account.clone(),
-                    }
+                    },
+                    (!since.is_empty()).then(|| rsx!(
+                        span {
+                            class: "since",
+                            "{since}"
+                        }
+                    )),
+                    (!reason.is_empty()).then(|| rsx!(
+                        span {
+                            class: "reason",
+                            "{reason}"
+                        }
+                    ))
                 )}
             },
             div {
@@ -69,16 +94,7 @@ pub fn BlockedListTile(cx: Scope<Props>) -> Element {
                         Button {
                             text:"Unblock".to_string(),
                             state: ui_kit::button::State::Secondary,
-                            on_pressed: move |_| {
-                                let mut multipass = cx.props.account.clone();
-                                let did_to_unblock = cx.props.friend.clone();
-                                match multipass.unblock(&did_to_unblock) {
-                                    Ok(_) => {}
-                                    Err(e) => {
-                                        log::debug!("faied to unblock friend {}:{}", &cx.props.friend, e);
-                                    }
-                                }
-                            }
+                            on_pressed: move |_| cx.props.on_unblock.call(()),
                         },
                     )}
                 }
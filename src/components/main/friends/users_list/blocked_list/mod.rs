@@ -1,11 +1,22 @@
-use dioxus::prelude::*;
+use dioxus::{events::FormEvent, prelude::*};
+use dioxus_heroicons::outline::Shape;
+use dioxus_toast::ToastInfo;
+use ui_kit::{
+    button::{self, Button},
+    input::Input,
+};
 use utils::Account;
+use warp::crypto::DID;
 mod blocked_list_tile;
 
 use std::{collections::HashSet, time::Duration};
 
-use crate::components::main::friends::users_list::{
-    blocked_list::blocked_list_tile::BlockedListTile, get_alpha, order_friend_list,
+use crate::{
+    components::main::friends::{
+        users_list::blocked_list::blocked_list_tile::BlockedListTile, UsernameAndDID,
+    },
+    iutils::{blocked, get_username_from_did, parse_did},
+    TOAST_MANAGER,
 };
 
 #[derive(Props, PartialEq)]
@@ -15,9 +26,13 @@ pub struct BlockedListProps {
 
 #[allow(non_snake_case)]
 pub fn BlockedList(cx: Scope<BlockedListProps>) -> Element {
+    let toast = use_atom_ref(&cx, TOAST_MANAGER);
     let block_users = use_ref(&cx, HashSet::new);
-    let disp_block_users = use_state(&cx, Vec::new);
-    let alpha = get_alpha();
+    let disp_block_users = use_state(&cx, Vec::<UsernameAndDID>::new);
+    let search = use_state(&cx, String::new);
+    let selected = use_state(&cx, HashSet::<DID>::new);
+    let new_did = use_state(&cx, String::new);
+    let new_reason = use_state(&cx, String::new);
     // same way as friend list to get the block user list
     use_future(
         &cx,
@@ -29,10 +44,16 @@ pub fn BlockedList(cx: Scope<BlockedListProps>) -> Element {
 
                 if *block_users.read() != block_users_list {
                     log::debug!("updating block users list ");
-                    if let Some(new_disp) = order_friend_list(&block_users_list, &mp) {
-                        *block_users.write_silent() = block_users_list;
-                        disp_block_users.set(new_disp);
-                    }
+                    let mut new_disp: Vec<_> = block_users_list
+                        .iter()
+                        .map(|did| UsernameAndDID {
+                            username: get_username_from_did(did.clone(), &mp),
+                            did: did.clone(),
+                        })
+                        .collect();
+                    new_disp.sort_by_key(|user| user.username.to_lowercase());
+                    *block_users.write_silent() = block_users_list;
+                    disp_block_users.set(new_disp);
                 }
 
                 tokio::time::sleep(Duration::from_millis(1000)).await;
@@ -40,39 +61,136 @@ pub fn BlockedList(cx: Scope<BlockedListProps>) -> Element {
         },
     );
 
-    let blocked_list = rsx!(
+    let unblock = move |dids: Vec<DID>| {
+        let failed = dids
+            .iter()
+            .filter(|did| match blocked::unblock(&cx.props.account, did) {
+                Ok(_) => false,
+                Err(e) => {
+                    log::error!("failed to unblock {}: {}", did, e);
+                    true
+                }
+            })
+            .count();
+        let message = match (failed, dids.len()) {
+            (0, 1) => String::from("Unblocked."),
+            (0, count) => format!("Unblocked {count} users."),
+            (failed, _) => format!("Not possible to unblock {failed} of them."),
+        };
+        toast.write().popup(ToastInfo::simple(&message));
+        selected.set(HashSet::new());
+    };
+    let block = move || match parse_did(new_did.get()) {
+        Some(did) => match blocked::block(&cx.props.account, &did, new_reason.get()) {
+            Ok(_) => {
+                toast.write().popup(ToastInfo::simple("Blocked."));
+                new_did.set(String::new());
+                new_reason.set(String::new());
+            }
+            Err(e) => {
+                log::error!("failed to block {}: {}", did, e);
+                toast
+                    .write()
+                    .popup(ToastInfo::simple("Not possible to block them."));
+            }
+        },
+        None => {
+            toast
+                .write()
+                .popup(ToastInfo::simple("That isn't a valid DID or friend code."));
+        }
+    };
+
+    // when and why is only known for users blocked from this device
+    let records = blocked::records();
+    let query = search.to_lowercase();
+    let shown = |user: &&UsernameAndDID| {
+        let reason = records
+            .get(&user.did.to_string())
+            .map(|record| record.reason.to_lowercase())
+            .unwrap_or_default();
+        query.is_empty()
+            || user.username.to_lowercase().contains(&query)
+            || user.did.to_string().to_lowercase().contains(&query)
+            || reason.contains(&query)
+    };
+    let nobody_shown = !disp_block_users.iter().any(|user| shown(&user));
+
+    cx.render(rsx!(
         div {
-            class: "users-list",
-            disp_block_users.iter().map(|friends_per_char_list| {
-                let first_username_char = friends_per_char_list.letter;
+            class: "users-list blocked-list",
+            div {
+                class: "block-by-did",
+                Input {
+                    icon: Shape::User,
+                    placeholder: String::from("DID or friend code"),
+                    value: new_did.to_string(),
+                    on_change: move |evt: FormEvent| new_did.set(evt.value.clone()),
+                    on_enter: move |_| block(),
+                },
+                Input {
+                    placeholder: String::from("Reason (optional)"),
+                    value: new_reason.to_string(),
+                    on_change: move |evt: FormEvent| new_reason.set(evt.value.clone()),
+                    on_enter: move |_| block(),
+                },
+                Button {
+                    icon: Shape::NoSymbol,
+                    text: String::from("Block"),
+                    state: button::State::Danger,
+                    disabled: new_did.trim().is_empty(),
+                    on_pressed: move |_| block(),
+                },
+            },
+            div {
+                class: "blocked-controls",
+                Input {
+                    icon: Shape::MagnifyingGlass,
+                    placeholder: String::from("Search blocked users"),
+                    value: search.to_string(),
+                    on_change: move |evt: FormEvent| search.set(evt.value.clone()),
+                    on_enter: move |_| {},
+                },
+                Button {
+                    text: format!("Unblock Selected ({})", selected.len()),
+                    state: button::State::Secondary,
+                    disabled: selected.is_empty(),
+                    on_pressed: move |_| unblock(selected.iter().cloned().collect()),
+                },
+            },
+            nobody_shown.then(|| rsx!(
+                p {
+                    class: "empty",
+                    "No blocked users match."
+                }
+            )),
+            disp_block_users.iter().filter(shown).map(|user| {
+                let record = records.get(&user.did.to_string());
+                let since = record
+                    .map(|record| format!("Blocked {}", record.since.format("%b %-d, %Y")))
+                    .unwrap_or_default();
+                let reason = record.map(|record| record.reason.clone()).unwrap_or_default();
                 rsx!(
-                    div {
-                        class: "users-separator",
-                        h5 {
-                            id: "{first_username_char}",
-                            "{first_username_char}"
-                        }
+                    BlockedListTile {
+                        key: "{user.did}",
+                        account: cx.props.account.clone(),
+                        friend: user.did.clone(),
+                        friend_username: user.username.clone(),
+                        since: since,
+                        reason: reason,
+                        selected: selected.contains(&user.did),
+                        on_select: move |select| {
+                            let mut dids = selected.get().clone();
+                            match select {
+                                true => dids.insert(user.did.clone()),
+                                false => dids.remove(&user.did),
+                            };
+                            selected.set(dids);
+                        },
+                        on_unblock: move |_| unblock(vec![user.did.clone()]),
                     }
-                    friends_per_char_list.friends.iter().map(|user| {
-                        rsx!(
-                            BlockedListTile {
-                                account: cx.props.account.clone(),
-                                friend: user.did.clone(),
-                                friend_username: user.username.clone(),
-                            }
-                        )
-                    }),
                 )
             }),
-        },
-        ul {
-            class: "a-z-list",
-            alpha.iter().map(|letter| {
-                rsx!( li { a { href: "#{letter}", prevent_default: "onclick", rel: "noopener noreferrer", "{letter}", } } )
-            })
         }
-
-    );
-
-    cx.render(blocked_list)
+    ))
 }
@@ -1,5 +1,6 @@
 use dioxus::prelude::*;
 use dioxus_heroicons::outline::Shape;
+use dioxus_toast::ToastInfo;
 use ui_kit::{
     activity_indicator::ActivityIndicator,
     button::Button,
@@ -8,7 +9,11 @@ use ui_kit::{
 };
 use warp::crypto::DID;
 
-use crate::{iutils, state::Actions, Messaging, STATE};
+use crate::{
+    iutils::{self, blocked},
+    state::Actions,
+    Messaging, PROFILE_MODAL, STATE, TOAST_MANAGER,
+};
 use utils::Account;
 
 #[derive(Props)]
@@ -33,6 +38,8 @@ pub fn FriendListTile<'a>(cx: Scope<'a, Props>) -> Element<'a> {
 
     let profile_picture = iutils::get_pfp_from_did(cx.props.friend.clone(), &mp);
     let state = use_atom_ref(&cx, STATE);
+    let toast = use_atom_ref(&cx, TOAST_MANAGER);
+    let profile_modal = use_atom_ref(&cx, PROFILE_MODAL);
 
     cx.render(rsx! {
         div {
@@ -90,6 +97,11 @@ pub fn FriendListTile<'a>(cx: Scope<'a, Props>) -> Element<'a> {
 
                             }
                         },
+                        Button {
+                            icon: Shape::UserCircle,
+                            state: ui_kit::button::State::Secondary,
+                            on_pressed: move |_| *profile_modal.write() = Some(cx.props.friend.clone()),
+                        },
                         Button {
                             icon: Shape::XMark,
                             state: ui_kit::button::State::Danger,
@@ -103,13 +115,16 @@ pub fn FriendListTile<'a>(cx: Scope<'a, Props>) -> Element<'a> {
                             text:"Block".to_string(),
                             state: ui_kit::button::State::Secondary,
                             on_pressed: move |_| {
-                                 let mut multipass = cx.props.account.clone();
-                                 let did_to_block = cx.props.friend.clone();
-                                 if let Err(e) =  multipass.block(&did_to_block) {
-                                    log::debug!("faied to block friend {}:{}", &cx.props.friend, e);
-                                 }
-                             }
-                         },
+                                let message = match blocked::block(&cx.props.account, &cx.props.friend, "") {
+                                    Ok(_) => format!("Blocked {}.", cx.props.friend_username),
+                                    Err(e) => {
+                                        log::error!("failed to block friend {}: {}", &cx.props.friend, e);
+                                        format!("Not possible to block {}.", cx.props.friend_username)
+                                    }
+                                };
+                                toast.write().popup(ToastInfo::simple(&message));
+                            }
+                        },
                     )}
                 }
             }
@@ -1,7 +1,7 @@
 use crate::{
     main::{compose::Compose, sidebar::Sidebar, welcome::Welcome},
     state::Actions,
-    Account, Messaging, STATE,
+    Account, Messaging, Storage, STATE,
 };
 
 use dioxus::prelude::*;
@@ -21,6 +21,7 @@ pub mod welcome;
 pub struct Prop {
     account: Account,
     messaging: Messaging,
+    storage: Storage,
 }
 
 #[allow(non_snake_case)]
@@ -127,6 +128,7 @@ pub fn Main(cx: Scope<Prop>) -> Element {
                         Compose {
                             account: cx.props.account.clone(),
                             messaging: cx.props.messaging.clone(),
+                            storage: cx.props.storage.clone(),
                         }
                     )
                 }
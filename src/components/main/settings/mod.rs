@@ -4,12 +4,12 @@ use ui_kit::button::Button;
 
 use crate::{
     components::main::settings::pages::{
-        audio_video::AudioVideo, developer::Developer, extensions::Extensions, general::General,
-        profile::Profile,
+        audio_video::AudioVideo, ban_list::BanList, developer::Developer, extensions::Extensions,
+        general::General, privacy::Privacy, profile::Profile,
     },
     components::reusable::page_header,
     state::Actions,
-    Account, Messaging, STATE,
+    Account, Messaging, Storage, STATE,
 };
 
 use self::sidebar::nav::Route;
@@ -21,6 +21,7 @@ pub mod sidebar;
 pub struct Props {
     account: Account,
     messaging: Messaging,
+    storage: Storage,
     page_to_open: Route,
 }
 
@@ -44,6 +45,7 @@ pub fn Settings(cx: Scope<Props>) -> Element {
     let active_page_string = match **active_page {
         Route::Profile => "Profile",
         Route::Privacy => "Privacy",
+        Route::BanList => "Ban List",
         Route::AudioVideo => "Audio Video",
         Route::Extensions => "Extensions",
         Route::Developer => "Developer",
@@ -96,11 +98,13 @@ pub fn Settings(cx: Scope<Props>) -> Element {
                             class: "content",
                             match active_page.get() {
                                 Route::General => rsx!(General { account: cx.props.account.clone() }),
-                                Route::Developer => rsx!(Developer { account: cx.props.account.clone() }),
+                                Route::Developer => rsx!(Developer { account: cx.props.account.clone(), storage: cx.props.storage.clone() }),
                                 Route::Profile => rsx!(Profile { account: cx.props.account.clone() }),
                                 Route::Extensions => rsx!(Extensions {}),
                                 Route::AudioVideo => rsx!(AudioVideo {}),
-                                _ => rsx!(Developer { account: cx.props.account.clone() }),
+                                Route::Privacy => rsx!(Privacy {}),
+                                Route::BanList => rsx!(BanList {}),
+                                _ => rsx!(Developer { account: cx.props.account.clone(), storage: cx.props.storage.clone() }),
                             }
                         }
                     }
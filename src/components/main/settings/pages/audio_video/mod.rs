@@ -1,12 +1,20 @@
 use dioxus::prelude::*;
+use dioxus_heroicons::outline::Shape;
+use rfd::FileDialog;
 
 use crate::iutils::config::Config;
-use ui_kit::switch::Switch;
+use ::utils::sounds::{Play, Sounds};
+use ui_kit::{
+    button::{Button, State},
+    switch::Switch,
+};
 
 #[allow(non_snake_case)]
 pub fn AudioVideo(cx: Scope) -> Element {
     log::debug!("rendering settings/pages/AudioVideo");
     let mut config = Config::load_config_or_default();
+    let notifications = config.notifications.clone();
+    let volume = (notifications.volume * 100.0).round() as u32;
 
     cx.render(rsx! {
         div {
@@ -33,7 +41,153 @@ pub fn AudioVideo(cx: Scope) -> Element {
                         }
                     }
                 }
-            }
+            },
+            div {
+                class: "item",
+                div {
+                    class: "description",
+                    label {
+                        "Notification Sounds"
+                    },
+                    p {
+                        "Play a sound when a message, friend request, call or mention arrives."
+                    }
+                },
+                div {
+                    class: "interactive",
+                    Switch {
+                        active: notifications.sounds,
+                        on_change: move |_| {
+                            let mut config = Config::load_config_or_default();
+                            config.notifications.sounds = !config.notifications.sounds;
+                            let _ = config.save();
+                            config.notifications.apply();
+                        }
+                    }
+                }
+            },
+            div {
+                class: "item",
+                div {
+                    class: "description",
+                    label {
+                        "Volume"
+                    },
+                    p {
+                        "{volume}%"
+                    }
+                },
+                div {
+                    class: "interactive",
+                    input {
+                        "type": "range",
+                        min: "0",
+                        max: "100",
+                        value: "{volume}",
+                        onchange: move |evt| {
+                            if let Ok(value) = evt.value.parse::<f32>() {
+                                let mut config = Config::load_config_or_default();
+                                config.notifications.volume = (value / 100.0).clamp(0.0, 1.0);
+                                let _ = config.save();
+                                config.notifications.apply();
+                                Play(Sounds::Notification);
+                                cx.needs_update();
+                            }
+                        }
+                    }
+                }
+            },
+            SoundPicker {
+                label: String::from("Message Sound"),
+                sound: Sounds::Notification,
+            },
+            SoundPicker {
+                label: String::from("Friend Request Sound"),
+                sound: Sounds::FriendReq,
+            },
+            SoundPicker {
+                label: String::from("Call Sound"),
+                sound: Sounds::Call,
+            },
+            SoundPicker {
+                label: String::from("Mention Sound"),
+                sound: Sounds::Mention,
+            },
         },
     })
 }
+
+#[inline_props]
+#[allow(non_snake_case)]
+fn SoundPicker(cx: Scope, label: String, sound: Sounds) -> Element {
+    let sound = *sound;
+    let path = use_state(&cx, || {
+        Config::load_config_or_default()
+            .notifications
+            .sound_path_mut(sound)
+            .cloned()
+            .unwrap_or_default()
+    });
+    let description = if path.is_empty() {
+        String::from("Default")
+    } else {
+        path.to_string()
+    };
+
+    // an empty path resets the sound to the built in one
+    let set_path = move |new_path: String| {
+        let mut config = Config::load_config_or_default();
+        if let Some(p) = config.notifications.sound_path_mut(sound) {
+            *p = new_path.clone();
+        }
+        let _ = config.save();
+        config.notifications.apply();
+        path.set(new_path);
+    };
+    let set_path2 = set_path.clone();
+
+    cx.render(rsx! {
+        div {
+            class: "item",
+            div {
+                class: "description",
+                label {
+                    "{label}"
+                },
+                p {
+                    class: "ellipsis",
+                    "{description}"
+                }
+            },
+            div {
+                class: "interactive",
+                Button {
+                    icon: Shape::Play,
+                    state: State::Secondary,
+                    on_pressed: move |_| Play(sound),
+                },
+                Button {
+                    icon: Shape::FolderOpen,
+                    text: String::from("Choose"),
+                    on_pressed: move |_| {
+                        if let Some(file) = FileDialog::new()
+                            .add_filter("audio", &["ogg", "wav", "mp3", "flac"])
+                            .set_directory(".")
+                            .pick_file()
+                        {
+                            set_path(file.to_string_lossy().to_string());
+                            Play(sound);
+                        }
+                    },
+                },
+                (!path.is_empty()).then(|| rsx!(
+                    Button {
+                        icon: Shape::ArrowUturnLeft,
+                        state: State::Secondary,
+                        on_pressed: move |_| set_path2(String::new()),
+                    }
+                )),
+            }
+        }
+    })
+}
@@ -0,0 +1,169 @@
+use dioxus::{events::FormEvent, prelude::*};
+use dioxus_heroicons::outline::Shape;
+use dioxus_toast::ToastInfo;
+use ui_kit::{
+    button::{self, Button},
+    input::Input,
+};
+
+use crate::{
+    iutils::{
+        ban_list::{self, Source},
+        parse_did,
+    },
+    TOAST_MANAGER,
+};
+
+#[allow(non_snake_case)]
+pub fn BanList(cx: Scope) -> Element {
+    log::debug!("rendering settings/pages/BanList");
+    let toast = use_atom_ref(&cx, TOAST_MANAGER);
+    let search = use_state(&cx, String::new);
+    let new_did = use_state(&cx, String::new);
+    let new_reason = use_state(&cx, String::new);
+
+    let report = move |result: std::io::Result<()>| {
+        if let Err(e) = result {
+            log::error!("failed to update the ban list: {}", e);
+            toast
+                .write()
+                .popup(ToastInfo::simple("Not possible to update the ban list."));
+        }
+        cx.needs_update();
+    };
+    let add = move || match parse_did(new_did.get()) {
+        Some(did) => {
+            report(ban_list::ban(&did, new_reason.get()));
+            new_did.set(String::new());
+            new_reason.set(String::new());
+        }
+        None => {
+            toast
+                .write()
+                .popup(ToastInfo::simple("That isn't a valid DID or friend code."));
+        }
+    };
+
+    let query = search.to_lowercase();
+    let entries: Vec<_> = ban_list::entries()
+        .into_iter()
+        .filter(|ban| {
+            query.is_empty()
+                || ban.did.to_lowercase().contains(&query)
+                || ban.reason.to_lowercase().contains(&query)
+        })
+        .collect();
+
+    cx.render(rsx! {
+        div {
+            id: "page_ban_list",
+            class: "padded",
+            p {
+                class: "explanation",
+                "Friend requests, messages and search results from these accounts are held back. Satellite keeps a global list, which you're free to override, and you can add your own."
+            },
+            div {
+                class: "add-ban",
+                Input {
+                    icon: Shape::User,
+                    placeholder: String::from("DID or friend code"),
+                    value: new_did.to_string(),
+                    on_change: move |evt: FormEvent| new_did.set(evt.value.clone()),
+                    on_enter: move |_| add(),
+                },
+                Input {
+                    placeholder: String::from("Reason (optional)"),
+                    value: new_reason.to_string(),
+                    on_change: move |evt: FormEvent| new_reason.set(evt.value.clone()),
+                    on_enter: move |_| add(),
+                },
+                Button {
+                    icon: Shape::NoSymbol,
+                    text: String::from("Ban"),
+                    state: button::State::Danger,
+                    disabled: new_did.trim().is_empty(),
+                    on_pressed: move |_| add(),
+                },
+            },
+            Input {
+                icon: Shape::MagnifyingGlass,
+                placeholder: String::from("Search the ban list"),
+                value: search.to_string(),
+                on_change: move |evt: FormEvent| search.set(evt.value.clone()),
+                on_enter: move |_| {},
+            },
+            entries.is_empty().then(|| rsx!(
+                p {
+                    class: "empty",
+                    "Nobody on the ban list matches."
+                }
+            )),
+            entries.into_iter().map(|ban| {
+                let did = ban.did.clone();
+                let (source, source_class) = match (ban.source, ban.allowed) {
+                    (Source::Local, _) => ("Yours", "local"),
+                    (Source::Global, false) => ("Global", "global"),
+                    (Source::Global, true) => ("Global, trusted", "trusted"),
+                };
+                let reason = match ban.reason.is_empty() {
+                    true => String::from("No reason given"),
+                    false => ban.reason.clone(),
+                };
+                let added = ban
+                    .added
+                    .map(|added| added.format("%b %-d, %Y").to_string())
+                    .unwrap_or_default();
+                let action = match (ban.source, ban.allowed) {
+                    (Source::Local, _) => rsx!(
+                        Button {
+                            icon: Shape::Trash,
+                            text: String::from("Remove"),
+                            state: button::State::Secondary,
+                            on_pressed: move |_| report(ban_list::unban(&did)),
+                        }
+                    ),
+                    (Source::Global, allowed) => {
+                        let (icon, text) = match allowed {
+                            true => (Shape::NoSymbol, "Ban Again"),
+                            false => (Shape::CheckCircle, "Trust"),
+                        };
+                        rsx!(
+                        Button {
+                            icon: icon,
+                            text: String::from(text),
+                            state: button::State::Secondary,
+                            on_pressed: move |_| report(ban_list::set_allowed(&did, !allowed)),
+                        }
+                        )
+                    }
+                };
+                rsx!(
+                    div {
+                        key: "{ban.did}",
+                        class: "ban",
+                        div {
+                            class: "details",
+                            span {
+                                class: "did",
+                                "{ban.did}"
+                            },
+                            span {
+                                class: "reason",
+                                "{reason}"
+                            },
+                            span {
+                                class: "meta",
+                                span {
+                                    class: "source {source_class}",
+                                    "{source}"
+                                },
+                                " {added}"
+                            },
+                        },
+                        action
+                    }
+                )
+            })
+        }
+    })
+}
@@ -2,6 +2,8 @@ use std::fs;
 
 use dioxus::prelude::*;
 use dioxus_heroicons::outline::Shape;
+use dioxus_toast::ToastInfo;
+use humansize::{format_size, DECIMAL};
 use warp::crypto::DID;
 
 use ui_kit::{
@@ -10,12 +12,35 @@ use ui_kit::{
     switch::Switch,
 };
 
-use crate::iutils::config::Config;
-use ::utils::Account;
+use crate::{
+    iutils::{
+        config::Config,
+        storage_usage::{self, constellation_usage, percent, LocalUsage},
+    },
+    Storage, TOAST_MANAGER,
+};
+use ::utils::{at_rest, Account};
+
+const GB: u64 = 1000 * 1000 * 1000;
 
 #[derive(Props, PartialEq)]
 pub struct Props {
     account: Account,
+    storage: Storage,
+}
+
+// sizes next to their share of `total`
+fn breakdown(sizes: &[(String, u64)], total: u64) -> Vec<(String, String, u16)> {
+    sizes
+        .iter()
+        .map(|(name, size)| {
+            (
+                name.clone(),
+                format_size(*size, DECIMAL),
+                percent(*size, total),
+            )
+        })
+        .collect()
 }
 
 #[allow(non_snake_case)]
@@ -30,6 +55,38 @@ pub fn Developer(cx: Scope<Props>) -> Element {
         DID::default().to_string()
     };
 
+    let toast = use_atom_ref(&cx, TOAST_MANAGER);
+    let usage = constellation_usage(&cx.props.storage, &config.storage);
+    let used = format_size(usage.used, DECIMAL);
+    let max = format_size(usage.max, DECIMAL);
+    let by_kind = breakdown(&usage.by_kind, usage.used);
+    let by_folder = breakdown(&usage.by_folder, usage.used);
+    let quota_gb = config.storage.quota as f64 / GB as f64;
+    let warn_at_percent = config.storage.warn_at_percent;
+
+    // walking the app's folder reads the disk, so it's done in the background
+    let local = use_state(&cx, || None::<LocalUsage>);
+    use_future(
+        &cx,
+        (local, &local.is_some()),
+        |(local, known)| async move {
+            if !known {
+                if let Ok(usage) = tokio::task::spawn_blocking(storage_usage::local_usage).await {
+                    local.set(Some(usage));
+                }
+            }
+        },
+    );
+    let local_text = match &**local {
+        Some(local) => format!(
+            "{} on this device, {} of it thumbnails and previews which are made again when needed and {} files waiting to be sent.",
+            format_size(local.total, DECIMAL),
+            format_size(local.caches, DECIMAL),
+            format_size(local.staged, DECIMAL)
+        ),
+        None => String::from("Working it out..."),
+    };
+
     let cache_path = dirs::home_dir()
         .unwrap_or_default()
         .join(".warp")
@@ -136,6 +193,117 @@ pub fn Developer(cx: Scope<Props>) -> Element {
                     }
                 }
             }
+            div {
+                class: "item",
+                div {
+                    class: "description",
+                    label {
+                        "Storage"
+                    },
+                    p {
+                        "{used} of {max} used."
+                    },
+                    div {
+                        class: "storage-breakdown",
+                        div {
+                            h4 { "By type" },
+                            by_kind.iter().map(|(name, size, percent)| rsx!(
+                                div {
+                                    key: "{name}",
+                                    class: "storage-row",
+                                    span { "{name}" },
+                                    span { class: "size", "{size} ({percent}%)" },
+                                }
+                            ))
+                        },
+                        div {
+                            h4 { "By folder" },
+                            by_folder.iter().map(|(name, size, percent)| rsx!(
+                                div {
+                                    key: "{name}",
+                                    class: "storage-row",
+                                    span { "{name}" },
+                                    span { class: "size", "{size} ({percent}%)" },
+                                }
+                            ))
+                        },
+                    },
+                },
+                div {
+                    class: "interactive",
+                }
+            }
+            div {
+                class: "item",
+                div {
+                    class: "description",
+                    label {
+                        "Storage Quota"
+                    },
+                    p {
+                        "The most Constellation may hold, in GB. 0 lets it fill the disk. You're warned once {warn_at_percent}% of it is used."
+                    }
+                },
+                div {
+                    class: "interactive",
+                    input {
+                        "type": "number",
+                        min: "0",
+                        step: "0.1",
+                        value: "{quota_gb}",
+                        onchange: move |evt| {
+                            if let Ok(value) = evt.value.parse::<f64>() {
+                                let mut config = Config::load_config_or_default();
+                                config.storage.quota = (value.max(0.0) * GB as f64) as u64;
+                                let _ = config.save();
+                                cx.needs_update();
+                            }
+                        }
+                    },
+                    input {
+                        "type": "number",
+                        min: "1",
+                        max: "100",
+                        value: "{warn_at_percent}",
+                        title: "Warn at this percent",
+                        onchange: move |evt| {
+                            if let Ok(value) = evt.value.parse::<u8>() {
+                                let mut config = Config::load_config_or_default();
+                                config.storage.warn_at_percent = value.clamp(1, 100);
+                                let _ = config.save();
+                                cx.needs_update();
+                            }
+                        }
+                    }
+                }
+            }
+            div {
+                class: "item",
+                div {
+                    class: "description",
+                    label {
+                        "Local Data"
+                    },
+                    p {
+                        "{local_text}"
+                    }
+                },
+                div {
+                    class: "interactive",
+                    Button {
+                        icon: Shape::Trash,
+                        state: State::Secondary,
+                        text: String::from("Clear Thumbnails"),
+                        on_pressed: move |_| {
+                            if let Err(error) = storage_usage::clear_caches() {
+                                log::error!("Not possible to clear the caches: {error}");
+                                toast.write().popup(ToastInfo::simple("Not possible to clear the thumbnails and previews."));
+                            }
+                            local.set(None);
+                        },
+                    }
+                }
+            }
             div {
                 class: "item",
                 div {
@@ -153,6 +321,37 @@ pub fn Developer(cx: Scope<Props>) -> Element {
                 }
             }
             (c.developer.developer_mode).then(|| rsx! {
+                div {
+                    class: "item",
+                    div {
+                        class: "description",
+                        label {
+                            "Export Decrypted Data"
+                        },
+                        p {
+                            "Writes plain copies of the settings and chat state, which are encrypted on disk, to a folder for debugging. Anyone with access to this device can read them."
+                        }
+                    },
+                    div {
+                        class: "interactive",
+                        Button {
+                            icon: Shape::DocumentArrowDown,
+                            state: State::Secondary,
+                            text: String::from("Export"),
+                            on_pressed: move |_| {
+                                match at_rest::export_decrypted() {
+                                    Ok(directory) => {
+                                        let _ = opener::open(&directory);
+                                    }
+                                    Err(error) => {
+                                        log::error!("Not possible to export the decrypted data: {error}");
+                                        toast.write().popup(ToastInfo::simple("Not possible to export the decrypted data."));
+                                    }
+                                }
+                            },
+                        }
+                    }
+                },
                 ExtensionPlaceholder {},
             })
         }
@@ -1,6 +1,6 @@
 use dioxus::prelude::*;
 
-use crate::{iutils::config::Config, Account};
+use crate::{components::reusable::profiles::ProfileManager, iutils::config::Config, Account};
 use ui_kit::switch::Switch;
 
 #[derive(Props, PartialEq)]
@@ -38,6 +38,19 @@ pub fn General(cx: Scope<Props>) -> Element {
                         }
                     }
                 }
+            },
+            div {
+                class: "item profiles",
+                div {
+                    class: "description",
+                    label {
+                        "Profiles"
+                    },
+                    p {
+                        "Each profile is a separate account with its own friends, chats and files. Switching locks this one."
+                    }
+                },
+                ProfileManager {},
             }
         },
     })
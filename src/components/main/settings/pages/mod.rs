@@ -1,5 +1,7 @@
 pub mod audio_video;
+pub mod ban_list;
 pub mod developer;
 pub mod extensions;
 pub mod general;
+pub mod privacy;
 pub mod profile;
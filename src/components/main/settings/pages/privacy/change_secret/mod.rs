@@ -0,0 +1,171 @@
+use dioxus::{
+    events::{KeyCode, KeyboardEvent},
+    prelude::*,
+};
+use dioxus_heroicons::outline::Shape;
+use ui_kit::{
+    button::{self, Button},
+    popup::Popup,
+};
+
+use crate::{
+    iutils::lockout::{self, Lockout, Outcome, Refused, Secret},
+    ProfileAction,
+};
+
+/// changes the pin or passphrase the keystore is locked with. The profile is opened again afterwards, so
+/// the new secret has to be typed in straight away.
+#[allow(non_snake_case)]
+pub fn ChangeSecret(cx: Scope) -> Element {
+    let editing = use_state(&cx, || false);
+    let current = use_state(&cx, String::new);
+    let new = use_state(&cx, String::new);
+    let repeated = use_state(&cx, String::new);
+    let error = use_state(&cx, String::new);
+    let switcher = use_coroutine_handle::<ProfileAction>(&cx);
+
+    let current_secret = Lockout::load().secret;
+    let secret = use_state(&cx, || current_secret);
+    let label = current_secret.label();
+    let new_label = secret.label();
+
+    let close = move || {
+        current.set(String::new());
+        new.set(String::new());
+        repeated.set(String::new());
+        error.set(String::new());
+        editing.set(false);
+    };
+    let submit = move || {
+        if let Err(e) = secret.validate(new.get()) {
+            error.set(e);
+            return;
+        }
+        if new.get() != repeated.get() {
+            error.set(format!("The new {new_label} doesn't match."));
+            return;
+        }
+        // the keystore is only re-locked with the new secret once the profile has let go of it
+        let action = match lockout::verify(current.get()) {
+            Ok(keystore) => {
+                keystore.lock();
+                ProfileAction::ChangeSecret {
+                    old: current.to_string(),
+                    new: new.to_string(),
+                    secret: **secret,
+                }
+            }
+            Err(refused) => {
+                error.set(refused.message(current_secret));
+                if refused != Refused::Wrong(Outcome::Wipe) {
+                    return;
+                }
+                ProfileAction::Wipe
+            }
+        };
+        if let Some(switcher) = switcher {
+            switcher.send(action);
+        }
+        close();
+    };
+    let on_enter = move |evt: KeyboardEvent| {
+        if evt.key_code == KeyCode::Enter {
+            submit();
+        }
+    };
+
+    cx.render(rsx! {
+        div {
+            class: "item",
+            div {
+                class: "description",
+                label {
+                    "{label}"
+                },
+                p {
+                    "Your account is locked with a {label}. After changing it, unlock again with the new one."
+                }
+            },
+            div {
+                class: "interactive",
+                Button {
+                    icon: Shape::Key,
+                    text: format!("Change {label}"),
+                    state: button::State::Secondary,
+                    on_pressed: move |_| editing.set(true),
+                }
+            }
+        },
+        editing.then(|| rsx!(
+            Popup {
+                hidden: false,
+                on_dismiss: move |_| close(),
+                children: cx.render(rsx!(
+                    div {
+                        id: "change-secret",
+                        h2 {
+                            "Change {label}"
+                        },
+                        input {
+                            "type": "password",
+                            placeholder: "Current {label}",
+                            autofocus: "true",
+                            value: "{current}",
+                            oninput: move |evt| {
+                                error.set(String::new());
+                                current.set(evt.value.clone());
+                            },
+                            onkeyup: on_enter,
+                        },
+                        div {
+                            class: "kinds",
+                            [Secret::Pin, Secret::Passphrase].into_iter().map(|kind| rsx!(
+                                Button {
+                                    text: String::from(kind.label()),
+                                    state: match **secret == kind {
+                                        true => button::State::Primary,
+                                        false => button::State::Secondary,
+                                    },
+                                    on_pressed: move |_| {
+                                        error.set(String::new());
+                                        secret.set(kind);
+                                    },
+                                }
+                            ))
+                        },
+                        input {
+                            "type": "password",
+                            placeholder: "New {new_label}",
+                            value: "{new}",
+                            oninput: move |evt| {
+                                error.set(String::new());
+                                new.set(evt.value.chars().take(secret.max_length()).collect());
+                            },
+                            onkeyup: on_enter,
+                        },
+                        input {
+                            "type": "password",
+                            placeholder: "Repeat the new {new_label}",
+                            value: "{repeated}",
+                            oninput: move |evt| {
+                                error.set(String::new());
+                                repeated.set(evt.value.chars().take(secret.max_length()).collect());
+                            },
+                            onkeyup: on_enter,
+                        },
+                        p {
+                            class: "error_text",
+                            "{error}"
+                        },
+                        Button {
+                            icon: Shape::LockClosed,
+                            text: format!("Change {label}"),
+                            disabled: current.is_empty() || new.is_empty(),
+                            on_pressed: move |_| submit(),
+                        },
+                    }
+                )),
+            }
+        )),
+    })
+}
@@ -0,0 +1,214 @@
+use dioxus::prelude::*;
+use dioxus_heroicons::outline::Shape;
+
+mod change_secret;
+
+use crate::iutils::{
+    config::{Config, LinkPreviewPolicy},
+    lockout::Lockout,
+};
+use ::utils::link_preview;
+use ui_kit::{
+    button::{Button, State},
+    select::Select,
+};
+
+const MB: u64 = 1000 * 1000;
+
+#[allow(non_snake_case)]
+pub fn Privacy(cx: Scope) -> Element {
+    log::debug!("rendering settings/pages/Privacy");
+    let config = Config::load_config_or_default();
+
+    // the select has no initial value, so the current policy is listed first
+    let current = config.privacy.link_previews;
+    let options: Vec<String> = std::iter::once(current)
+        .chain(
+            LinkPreviewPolicy::ALL
+                .into_iter()
+                .filter(|policy| *policy != current),
+        )
+        .map(|policy| policy.label().to_string())
+        .collect();
+
+    let wipe_after = Lockout::load().wipe_after;
+    let auto_lock_minutes = config.privacy.auto_lock_minutes;
+    let request_expiry_days = config.friends.request_expiry_days;
+    let auto_download_mb = config.attachments.auto_download_limit as f64 / MB as f64;
+
+    cx.render(rsx! {
+        div {
+            id: "page_privacy",
+            class: "padded",
+            change_secret::ChangeSecret {},
+            div {
+                class: "item",
+                div {
+                    class: "description",
+                    label {
+                        "Auto-lock"
+                    },
+                    p {
+                        "Uplink locks itself after this many minutes without any input. 0 turns it off."
+                    }
+                },
+                div {
+                    class: "interactive",
+                    input {
+                        "type": "number",
+                        min: "0",
+                        step: "1",
+                        value: "{auto_lock_minutes}",
+                        onchange: move |evt| {
+                            if let Ok(value) = evt.value.parse::<u32>() {
+                                let mut config = Config::load_config_or_default();
+                                config.privacy.auto_lock_minutes = value;
+                                let _ = config.save();
+                                cx.needs_update();
+                            }
+                        }
+                    }
+                }
+            },
+            div {
+                class: "item",
+                div {
+                    class: "description",
+                    label {
+                        "Erase After Failed Attempts"
+                    },
+                    p {
+                        "Everything in this profile is erased after this many wrong guesses in a row when unlocking. 0 turns it off."
+                    }
+                },
+                div {
+                    class: "interactive",
+                    input {
+                        "type": "number",
+                        min: "0",
+                        step: "1",
+                        value: "{wipe_after}",
+                        onchange: move |evt| {
+                            if let Ok(value) = evt.value.parse::<u32>() {
+                                let mut lockout = Lockout::load();
+                                lockout.wipe_after = value;
+                                if let Err(e) = lockout.save() {
+                                    log::error!("failed to save the lockout policy: {}", e);
+                                }
+                                cx.needs_update();
+                            }
+                        }
+                    }
+                }
+            },
+            div {
+                class: "item",
+                div {
+                    class: "description",
+                    label {
+                        "Friend Request Expiry"
+                    },
+                    p {
+                        "Friend requests you sent are canceled when nobody answers them for this many days. 0 keeps them until they're answered."
+                    }
+                },
+                div {
+                    class: "interactive",
+                    input {
+                        "type": "number",
+                        min: "0",
+                        step: "1",
+                        value: "{request_expiry_days}",
+                        onchange: move |evt| {
+                            if let Ok(value) = evt.value.parse::<u32>() {
+                                let mut config = Config::load_config_or_default();
+                                config.friends.request_expiry_days = value;
+                                let _ = config.save();
+                                cx.needs_update();
+                            }
+                        }
+                    }
+                }
+            },
+            div {
+                class: "item",
+                div {
+                    class: "description",
+                    label {
+                        "Link Previews"
+                    },
+                    p {
+                        "Loading a preview reveals your IP address to the site being linked."
+                    }
+                },
+                div {
+                    class: "interactive",
+                    Select {
+                        options: options,
+                        on_change: move |label: String| {
+                            if let Some(policy) = LinkPreviewPolicy::ALL.into_iter().find(|p| p.label() == label) {
+                                let mut config = Config::load_config_or_default();
+                                config.privacy.link_previews = policy;
+                                let _ = config.save();
+                            }
+                        }
+                    }
+                }
+            },
+            div {
+                class: "item",
+                div {
+                    class: "description",
+                    label {
+                        "Auto-download Images"
+                    },
+                    p {
+                        "Images in chat up to this size, in MB, are fetched and shown straight away. 0 turns it off."
+                    }
+                },
+                div {
+                    class: "interactive",
+                    input {
+                        "type": "number",
+                        min: "0",
+                        step: "1",
+                        value: "{auto_download_mb}",
+                        onchange: move |evt| {
+                            if let Ok(value) = evt.value.parse::<f64>() {
+                                let mut config = Config::load_config_or_default();
+                                config.attachments.auto_download_limit = (value.max(0.0) * MB as f64) as u64;
+                                let _ = config.save();
+                                cx.needs_update();
+                            }
+                        }
+                    }
+                }
+            },
+            div {
+                class: "item",
+                div {
+                    class: "description",
+                    label {
+                        "Cached Previews"
+                    },
+                    p {
+                        "Previews are kept on this device so links aren't fetched again."
+                    }
+                },
+                div {
+                    class: "interactive",
+                    Button {
+                        icon: Shape::Trash,
+                        text: String::from("Clear"),
+                        state: State::Secondary,
+                        on_pressed: move |_| {
+                            if let Err(e) = link_preview::instance().clear_cache() {
+                                log::error!("failed to clear link previews: {}", e);
+                            }
+                        },
+                    }
+                }
+            },
+        },
+    })
+}
@@ -1,9 +1,10 @@
 use dioxus::prelude::*;
 
+mod recovery_phrase;
 mod status_msg;
 mod username;
 
-use crate::Account;
+use crate::{components::reusable::friend_code::FriendCode, Account};
 
 use ui_kit::photo_picker::PhotoPicker;
 
@@ -38,6 +39,19 @@ pub fn Profile(cx: Scope<Props>, account: Account) -> Element {
                     account:account.clone(),
                 }
             },
+            div {
+                class: "share-friend-code",
+                label {
+                    "Your Friend Code"
+                },
+                p {
+                    "Let someone scan this or send them the link to get a friend request from them."
+                },
+                FriendCode {
+                    account: account.clone(),
+                },
+            },
+            recovery_phrase::RecoveryPhrase {},
         }
     })
 }
@@ -0,0 +1,177 @@
+use arboard::Clipboard;
+use dioxus::prelude::*;
+use dioxus_heroicons::outline::Shape;
+use dioxus_toast::ToastInfo;
+use ui_kit::{
+    button::{self, Button},
+    popup::Popup,
+};
+
+use crate::{
+    iutils::{
+        lockout::{self, Lockout, Outcome, Refused},
+        recovery,
+    },
+    ProfileAction, TOAST_MANAGER,
+};
+
+enum Step {
+    Hidden,
+    // waiting for the pin or passphrase to be typed in again
+    Confirming,
+    Shown(Option<String>),
+}
+
+#[allow(non_snake_case)]
+pub fn RecoveryPhrase(cx: Scope) -> Element {
+    let step = use_state(&cx, || Step::Hidden);
+    let pin = use_state(&cx, String::new);
+    let error = use_state(&cx, String::new);
+    let toast = use_atom_ref(&cx, TOAST_MANAGER);
+
+    let switcher = use_coroutine_handle::<ProfileAction>(&cx);
+    let secret = Lockout::load().secret;
+    let label = secret.label();
+
+    let confirm = move || {
+        let keystore = match lockout::verify(pin.get()) {
+            Ok(keystore) => keystore,
+            Err(refused) => {
+                error.set(refused.message(secret));
+                if refused == Refused::Wrong(Outcome::Wipe) {
+                    if let Some(switcher) = switcher {
+                        switcher.send(ProfileAction::Wipe);
+                    }
+                }
+                return;
+            }
+        };
+        let phrase = recovery::recovery_phrase(&keystore);
+        keystore.lock();
+        match phrase {
+            Ok(phrase) => {
+                pin.set(String::new());
+                step.set(Step::Shown(phrase));
+            }
+            Err(e) => {
+                log::debug!("Not possible to read the recovery phrase: {e}");
+                error.set(String::from("Not possible to read the recovery phrase."));
+            }
+        }
+    };
+
+    cx.render(rsx! {
+        div {
+            class: "recovery-phrase",
+            label {
+                "Recovery Phrase"
+            },
+            p {
+                "Anyone with these words can take over your account. Keep them somewhere safe and never share them."
+            },
+            match step.get() {
+                Step::Shown(Some(phrase)) => {
+                    let phrase = phrase.clone();
+                    let copy_text = phrase.clone();
+                    rsx!(
+                        div {
+                            class: "words",
+                            phrase.split_whitespace().enumerate().map(|(index, word)| {
+                                let number = index + 1;
+                                rsx!(
+                                    span {
+                                        key: "{number}",
+                                        class: "word",
+                                        span { class: "number", "{number}." },
+                                        "{word}"
+                                    }
+                                )
+                            })
+                        },
+                        div {
+                            class: "actions",
+                            Button {
+                                icon: Shape::ClipboardDocument,
+                                text: String::from("Copy"),
+                                state: button::State::Secondary,
+                                on_pressed: move |_| {
+                                    let copied = Clipboard::new().and_then(|mut clipboard| clipboard.set_text(copy_text.clone()));
+                                    if copied.is_ok() {
+                                        toast.write().popup(ToastInfo::simple("Recovery phrase copied"));
+                                    }
+                                },
+                            },
+                            Button {
+                                icon: Shape::EyeSlash,
+                                text: String::from("Hide"),
+                                state: button::State::Secondary,
+                                on_pressed: move |_| step.set(Step::Hidden),
+                            },
+                        }
+                    )
+                }
+                Step::Shown(None) => rsx!(
+                    p {
+                        class: "missing",
+                        "No recovery phrase was kept for this account."
+                    }
+                ),
+                _ => rsx!(
+                    Button {
+                        icon: Shape::Eye,
+                        text: String::from("Show Recovery Phrase"),
+                        state: button::State::Secondary,
+                        on_pressed: move |_| {
+                            error.set(String::new());
+                            step.set(Step::Confirming);
+                        },
+                    }
+                ),
+            },
+            matches!(step.get(), Step::Confirming).then(|| rsx!(
+                Popup {
+                    hidden: false,
+                    on_dismiss: move |_| {
+                        pin.set(String::new());
+                        step.set(Step::Hidden);
+                    },
+                    children: cx.render(rsx!(
+                        div {
+                            id: "confirm-pin",
+                            h2 {
+                                "Enter {label}"
+                            },
+                            p {
+                                "Type in your {label} to see your recovery phrase."
+                            },
+                            input {
+                                "type": "password",
+                                autofocus: "true",
+                                value: "{pin}",
+                                oninput: move |evt| {
+                                    error.set(String::new());
+                                    pin.set(evt.value.clone());
+                                },
+                                onkeyup: move |evt| {
+                                    if evt.key_code == dioxus::events::KeyCode::Enter {
+                                        confirm();
+                                    }
+                                },
+                            },
+                            p {
+                                class: "error_text",
+                                "{error}"
+                            },
+                            Button {
+                                icon: Shape::LockClosed,
+                                text: String::from("Confirm"),
+                                disabled: pin.is_empty(),
+                                on_pressed: move |_| confirm(),
+                            },
+                        }
+                    )),
+                }
+            )),
+        }
+    })
+}
@@ -6,6 +6,7 @@ use ui_kit::button::Button;
 pub enum Route {
     General,
     Privacy,
+    BanList,
     AudioVideo,
     Extensions,
     Developer,
@@ -80,12 +81,21 @@ pub fn Nav<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
             NavButton {
                 text: String::from("Privacy"),
                 active: Route::Privacy.eq(active_item),
-                disabled: true,
+                disabled: false,
                 on_pressed: |_| {
                     active_item.set(Route::Privacy);
                     cx.props.on_pressed.call(Route::Privacy);
                 }
             },
+            NavButton {
+                text: String::from("Ban List"),
+                active: Route::BanList.eq(active_item),
+                disabled: false,
+                on_pressed: |_| {
+                    active_item.set(Route::BanList);
+                    cx.props.on_pressed.call(Route::BanList);
+                }
+            },
             NavButton {
                 text: String::from("AudioVideo"),
                 active: Route::AudioVideo.eq(active_item),
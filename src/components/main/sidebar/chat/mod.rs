@@ -1,4 +1,5 @@
 use crate::{
+    components::main::files::sharing,
     iutils,
     state::{Actions, ConversationInfo, LastMsgSent},
     Account, Messaging, LANGUAGE, STATE,
@@ -34,6 +35,7 @@ pub fn Chat<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
     log::debug!("rendering main/sidebar/Chat");
     let state = use_atom_ref(&cx, STATE).clone();
     let state2 = state.clone();
+    let state3 = state.clone();
     let l = use_atom_ref(&cx, LANGUAGE).read();
     // must be 'moved' into the use_future. don't pass it as a dependency because that won't work with
     // Rust's ownership model
@@ -56,6 +58,10 @@ pub fn Chat<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
     let mp = cx.props.account.clone();
 
     let ident = mp.get_own_identity().expect("Unexpected error <temp>");
+    let my_username = ident.username();
+    let is_muted = state.read().muted_chats.contains(&chat_id);
+    let has_unread_mention =
+        !cx.props.is_active && cx.props.conversation_info.num_unread_mentions > 0;
 
     let did = cx
         .props
@@ -121,8 +127,12 @@ pub fn Chat<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
 
     use_future(
         &cx,
-        (&cx.props.conversation_info.clone(), &cx.props.is_active),
-        |(mut conversation_info, is_active)| async move {
+        (
+            &cx.props.conversation_info.clone(),
+            &cx.props.is_active,
+            &my_username,
+        ),
+        |(mut conversation_info, is_active, my_username)| async move {
             if is_active {
                 if *unread_count.current() != 0 {
                     log::debug!("sidebar/chat exiting future ");
@@ -167,7 +177,13 @@ pub fn Chat<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
                     message_id,
                 } = event
                 {
-                    match rg.get_message(conversation_id, message_id).await {
+                    let message = rg.get_message(conversation_id, message_id).await;
+                    if matches!(&message, Ok(msg) if sharing::changes_shares(msg)) {
+                        sharing::invalidate();
+                    }
+                    match message {
+                        // messages keeping shared files up to date aren't news
+                        Ok(msg) if sharing::is_control_message(&msg) => {}
                         Ok(msg) => {
                             log::debug!("sidebar/chat streamed a message");
                             tx_chan.send(msg.clone());
@@ -180,7 +196,14 @@ pub fn Chat<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
                             }
                             // will silently remain zero if you only use *unread_count
                             conversation_info.num_unread_messages = *unread_count.current();
-                            conversation_info.last_msg_sent = Some(LastMsgSent::new(&msg.value()));
+                            if iutils::mentions::mentions_user(
+                                &sharing::visible_lines(&msg).join("\n"),
+                                &my_username,
+                            ) {
+                                conversation_info.num_unread_mentions += 1;
+                            }
+                            conversation_info.last_msg_sent =
+                                Some(LastMsgSent::new(&sharing::visible_lines(&msg)));
                             state
                                 .write()
                                 .dispatch(Actions::UpdateConversation(conversation_info.clone()));
@@ -225,6 +248,13 @@ pub fn Chat<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
                             onpressed: move |_| {},
                             text: String::from("Mark Seen"),
                         },
+                        ContextItem {
+                            icon: if is_muted { Shape::BellAlert } else { Shape::BellSlash },
+                            onpressed: move |_| {
+                                state3.write().dispatch(Actions::SetMuted(chat_id, !is_muted));
+                            },
+                            text: if is_muted { String::from("Unmute") } else { String::from("Mute") },
+                        },
                         hr{}
                         ContextItem {
                             onpressed: move |_| {},
@@ -297,6 +327,15 @@ pub fn Chat<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
                                 })
                             }
                         }
+                        has_unread_mention.then(|| rsx!(
+                            div {
+                                class: "unread-mention",
+                                title: "You were mentioned",
+                                span {
+                                    "@"
+                                }
+                            }
+                        )),
                         match *unread_count2.current() {
                             0 =>  rsx!( div {
                                 class: "unread-placeholder",
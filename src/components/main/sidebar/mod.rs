@@ -1,4 +1,8 @@
-use std::{borrow::Borrow, cell::RefCell, collections::HashMap};
+use std::{
+    borrow::Borrow,
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
 
 use dioxus::{events::FormEvent, prelude::*};
 use dioxus_heroicons::outline::Shape;
@@ -7,16 +11,19 @@ use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 use utils::extensions::{get_renders, ExtensionType};
 use uuid::Uuid;
-use warp::raygun::Message;
+use warp::raygun::{ConversationType, Message};
 
 use crate::{
-    components::{main::sidebar::favorites::Favorites, reusable::nav::Nav},
-    iutils::config::Config,
+    components::{
+        main::{files::sharing, sidebar::favorites::Favorites},
+        reusable::nav::Nav,
+    },
+    iutils::{ban_list, config::Config, mentions},
     state::{Actions, ConversationInfo},
     Messaging, LANGUAGE, STATE,
 };
 
-use ::utils::{notifications::PushNotification, Account};
+use ::utils::{notifications::PushNotification, sounds::Sounds, Account};
 use ui_kit::{
     context_menu::{ContextItem, ContextMenu},
     extension_placeholder::ExtensionPlaceholder,
@@ -57,16 +64,37 @@ pub fn Sidebar(cx: Scope<Props>) -> Element {
     }
 
     let ext_enabled = state.read().enabled_extensions.clone();
-    let exts = get_renders(ExtensionType::SidebarWidget, config.extensions.enable, ext_enabled);
+    let exts = get_renders(
+        ExtensionType::SidebarWidget,
+        config.extensions.enable,
+        ext_enabled,
+    );
 
+    let notification_state = state.clone();
     let notifications_tx = use_coroutine(&cx, |mut rx: UnboundedReceiver<Message>| async move {
         while let Some(msg) = rx.next().await {
+            if ban_list::is_banned(&msg.sender()) {
+                continue;
+            }
+            let content = sharing::visible_lines(&msg).join("\n");
+            let my_username = mp
+                .get_own_identity()
+                .map(|i| i.username())
+                .unwrap_or_default();
+            // mentions always notify, even when the chat is muted
+            let sound = if mentions::mentions_user(&content, &my_username) {
+                Sounds::Mention
+            } else if notification_state
+                .read()
+                .muted_chats
+                .contains(&msg.conversation_id())
+            {
+                continue;
+            } else {
+                Sounds::Notification
+            };
             let display_username = crate::iutils::get_username_from_did(msg.sender().clone(), &mp);
-            PushNotification(
-                display_username,
-                msg.value().join("\n"),
-                ::utils::sounds::Sounds::Notification,
-            );
+            PushNotification(display_username, content, sound);
         }
     });
 
@@ -83,6 +111,17 @@ pub fn Sidebar(cx: Scope<Props>) -> Element {
     let mp = cx.props.account.clone();
     let ident = mp.get_own_identity().expect("Unexpected error <temp>");
 
+    // direct conversations with anyone blocked are left out, wherever they were blocked from
+    let blocked_users: HashSet<_> = mp.block_list().unwrap_or_default().into_iter().collect();
+    chats.retain(|conv| {
+        conv.conversation.conversation_type() != ConversationType::Direct
+            || !conv
+                .conversation
+                .recipients()
+                .iter()
+                .any(|did| blocked_users.contains(did))
+    });
+
     let matcher = SkimMatcherV2::default();
     let filtered_chats = chats.clone().into_iter().filter(|conv| {
         if search_value.get().is_empty() {
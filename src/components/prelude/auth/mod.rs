@@ -13,7 +13,7 @@ use ui_kit::{
 };
 use warp::multipass::identity::IdentityUpdate;
 
-use crate::{Account, LANGUAGE, WINDOW_SUFFIX_NAME};
+use crate::{Account, DEFAULT_WINDOW_NAME, LANGUAGE, WINDOW_SUFFIX_NAME};
 
 // Remember: owned props must implement PartialEq!
 #[derive(Props, PartialEq)]
@@ -21,12 +21,32 @@ pub struct Props {
     account: Account,
 }
 
+/// checks a new username, returning why it can't be used
+pub fn validate_username(username: &str) -> Result<(), &'static str> {
+    if username.is_empty() {
+        return Err("Username is required");
+    }
+    if username.len() < 4 || username.len() > 32 {
+        return Err("Username needs to be between 4 and 32 characters long");
+    }
+    let username_regex_set = RegexSet::new([r"@", r"[[:^alnum:]&&[:^punct:]&&[^ ]]"]).unwrap();
+    let matches = username_regex_set.matches(username);
+    if matches.matched(0) {
+        return Err("@ is not allowed in username");
+    }
+    if matches.matched(1) {
+        return Err("Illegal input in username");
+    }
+    Ok(())
+}
+
 #[allow(non_snake_case)]
 pub fn Auth(cx: Scope<Props>) -> Element {
     log::debug!("rendering Auth");
     let window = use_window(&cx);
     let l = use_atom_ref(&cx, LANGUAGE).read();
     let router = use_router(&cx).clone();
+    let router2 = router.clone();
 
     let username = use_state(&cx, String::new);
     let valid_username = username.len() >= 4;
@@ -44,39 +64,26 @@ pub fn Auth(cx: Scope<Props>) -> Element {
     let mut mp = cx.props.account.clone();
     let mut new_account = move || {
         let username = username.trim();
-        if username.is_empty() {
-            error.set("Username is required".into())
-        } else if username.len() < 4 || username.len() > 32 {
-            error.set("Username needs to be between 4 and 32 characters long".into())
-        } else {
-            let username_regex_set =
-                RegexSet::new([r"@", r"[[:^alnum:]&&[:^punct:]&&[^ ]]"]).unwrap();
-            let matches = username_regex_set.matches(username);
-            if matches.matched(0) {
-                error.set("@ is not allowed in username".into())
-            } else if matches.matched(1) {
-                error.set("Illegal input in username".into())
-            } else {
-                match mp.create_identity(Some(username), None) {
-                    Ok(_) => {
-                        if !profile_picture_is_empty {
-                            if let Err(e) =
-                                mp.update_identity(IdentityUpdate::set_graphics_picture(
-                                    profile_picture_state.to_string(),
-                                ))
-                            {
-                                println!("{}", e);
-                            }
+        match validate_username(username) {
+            Err(message) => error.set(message.into()),
+            Ok(()) => match mp.create_identity(Some(username), None) {
+                Ok(_) => {
+                    if !profile_picture_is_empty {
+                        if let Err(e) = mp.update_identity(IdentityUpdate::set_graphics_picture(
+                            profile_picture_state.to_string(),
+                        )) {
+                            println!("{}", e);
                         }
-                        window.set_title(&format!("{} - {}", username, WINDOW_SUFFIX_NAME));
-                        router.replace_route("/loading", None, None);
-                    }
-                    Err(warp::error::Error::InvalidLength { .. }) => {
-                        error.set("Username length is invalid".into())
                     }
-                    Err(_) => error.set("Unexpected error has occurred".into()),
+                    *DEFAULT_WINDOW_NAME.write() = format!("{} - {}", username, WINDOW_SUFFIX_NAME);
+                    window.set_title(&DEFAULT_WINDOW_NAME.read());
+                    router.replace_route("/loading", None, None);
                 }
-            }
+                Err(warp::error::Error::InvalidLength { .. }) => {
+                    error.set("Username length is invalid".into())
+                }
+                Err(_) => error.set("Unexpected error has occurred".into()),
+            },
         }
     };
     let mut new_account2 = new_account.clone();
@@ -185,6 +192,13 @@ pub fn Auth(cx: Scope<Props>) -> Element {
                             },
                             on_pressed:  move |_| new_account2(),
                         }
+                        div { class: "m-bottom" },
+                        Button {
+                            icon: Shape::ArrowPath,
+                            text: String::from("Restore From Recovery Phrase"),
+                            state: button::State::Secondary,
+                            on_pressed: move |_| router2.replace_route("/restore", None, None),
+                        }
                     }
                 }
             }
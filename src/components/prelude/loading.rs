@@ -1,4 +1,7 @@
-use crate::{iutils::config::Config, Account, LANGUAGE, WINDOW_SUFFIX_NAME};
+use crate::{
+    iutils::{config::Config, invite},
+    Account, DEFAULT_WINDOW_NAME, LANGUAGE, WINDOW_SUFFIX_NAME,
+};
 use dioxus::core::to_owned;
 use dioxus::desktop::use_window;
 use dioxus::prelude::*;
@@ -47,8 +50,14 @@ pub fn Loading(cx: Scope<Props>) -> Element {
                 //     let _ = stream_handle.play_raw(source.convert_samples());
                 //     std::thread::sleep(std::time::Duration::from_secs(2));
                 // }
-                window.set_title(&format!("{} - {}", i.username(), WINDOW_SUFFIX_NAME));
-                router.replace_route("/main", None, None);
+                *DEFAULT_WINDOW_NAME.write() = format!("{} - {}", i.username(), WINDOW_SUFFIX_NAME);
+                window.set_title(&DEFAULT_WINDOW_NAME.read());
+                // an invite the app was opened with is taken up on the Friends page
+                let next_route = match invite::has_pending() {
+                    true => "/main/friends",
+                    false => "/main",
+                };
+                router.replace_route(next_route, None, None);
             } else {
                 tx.send(true);
             }
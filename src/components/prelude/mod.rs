@@ -1,3 +1,4 @@
 pub mod auth;
 pub mod loading;
+pub mod restore;
 pub mod unlock;
@@ -0,0 +1,127 @@
+use dioxus::desktop::use_window;
+use dioxus::router::use_router;
+use dioxus::{events::FormEvent, prelude::*};
+use dioxus_heroicons::outline::Shape;
+use sir::css;
+use ui_kit::{
+    button::{self, Button},
+    input::Input,
+};
+
+use crate::{
+    components::prelude::auth::validate_username, iutils::recovery, Account, DEFAULT_WINDOW_NAME,
+    WINDOW_SUFFIX_NAME,
+};
+
+// Remember: owned props must implement PartialEq!
+#[derive(Props, PartialEq)]
+pub struct Props {
+    account: Account,
+}
+
+#[allow(non_snake_case)]
+pub fn Restore(cx: Scope<Props>) -> Element {
+    log::debug!("rendering Restore");
+    let window = use_window(&cx);
+    let router = use_router(&cx).clone();
+    let router2 = router.clone();
+
+    let phrase = use_state(&cx, String::new);
+    let username = use_state(&cx, String::new);
+    let error = use_state(&cx, String::new);
+    let error_class = if error.is_empty() {
+        css!("opacity: 0")
+    } else {
+        "error_text"
+    };
+    let ready = !phrase.trim().is_empty() && username.trim().len() >= 4;
+
+    let mut mp = cx.props.account.clone();
+    let mut restore = move || {
+        let username = username.trim();
+        let normalized = match recovery::normalize_phrase(phrase.get()) {
+            Ok(normalized) => normalized,
+            Err(message) => return error.set(message.into()),
+        };
+        if let Err(message) = validate_username(username) {
+            return error.set(message.into());
+        }
+        // the keypair comes from the phrase, so this is the identity it was made for
+        match mp.create_identity(Some(username), Some(&normalized)) {
+            Ok(_) => {
+                *DEFAULT_WINDOW_NAME.write() = format!("{} - {}", username, WINDOW_SUFFIX_NAME);
+                window.set_title(&DEFAULT_WINDOW_NAME.read());
+                router.replace_route("/loading", None, None);
+            }
+            Err(warp::error::Error::IdentityExist) => {
+                error.set("There's already an account on this device".into())
+            }
+            Err(e) => {
+                log::error!("Not possible to restore the account: {e}");
+                error.set("That recovery phrase couldn't be used".into())
+            }
+        }
+    };
+    let mut restore2 = restore.clone();
+
+    cx.render(rsx! {
+        div {
+            class: "auth restore",
+            div {
+                class: "container",
+                h2 {
+                    "Restore Account",
+                },
+                label {
+                    "Type in the recovery phrase of your account, with a space between each word.",
+                },
+                div { class: "m-bottom" },
+                div {
+                    class: "full-width",
+                    textarea {
+                        class: "phrase",
+                        rows: "4",
+                        placeholder: "word word word…",
+                        value: "{phrase}",
+                        oninput: move |evt| {
+                            error.set(String::new());
+                            phrase.set(evt.value.clone());
+                        },
+                    },
+                    div { class: "m-bottom" },
+                    Input {
+                        icon: Shape::Identification,
+                        value: username.to_string(),
+                        placeholder: String::from("Your username.."),
+                        on_change: move |evt: FormEvent| {
+                            error.set(String::new());
+                            username.set(evt.value.clone());
+                        },
+                        on_enter: move |_| restore(),
+                    },
+                    p {
+                        class: "{error_class}",
+                        "　{error}　"
+                    },
+                    Button {
+                        icon: Shape::ArrowPath,
+                        text: String::from("Restore Account"),
+                        disabled: !ready,
+                        state: match ready {
+                            true => button::State::Primary,
+                            false => button::State::Secondary,
+                        },
+                        on_pressed: move |_| restore2(),
+                    },
+                    div { class: "m-bottom" },
+                    Button {
+                        icon: Shape::ArrowLeft,
+                        text: String::from("Create a New Account Instead"),
+                        state: button::State::Secondary,
+                        on_pressed: move |_| router2.replace_route("/auth", None, None),
+                    }
+                }
+            }
+        }
+    })
+}
@@ -1,16 +1,29 @@
+use std::time::Duration;
+
+use ::utils::at_rest;
 use dioxus::core::to_owned;
 use dioxus::router::use_router;
 use dioxus::{events::KeyCode, prelude::*};
 use dioxus_heroicons::outline::Shape;
 use sir::css;
+use state::{PersistedState, STATE};
 use ui_kit::{
     button::{self, Button},
     pin::Pin,
+    popup::Popup,
     tooltip::{ArrowPosition, Tooltip},
 };
 use warp::tesseract::Tesseract;
 
-use crate::LANGUAGE;
+use crate::{
+    components::reusable::profiles::ProfileManager,
+    iutils::{
+        config::Config,
+        lockout::{self, Lockout, Outcome, Secret},
+        profiles,
+    },
+    ProfileAction, LANGUAGE,
+};
 
 // Remember: owned props must implement PartialEq!
 #[derive(PartialEq, Props)]
@@ -21,20 +34,15 @@ pub struct UnlockProps {
 #[allow(non_snake_case)]
 pub fn Unlock(cx: Scope<UnlockProps>) -> Element {
     log::debug!("rendering Unlock");
-    let l = use_atom_ref(&cx, LANGUAGE).read();
-    let l2 = l.clone();
-    let router = use_router(&cx).clone();
-    let router2 = router.clone();
-    let router3 = router.clone();
+    let language = use_atom_ref(&cx, LANGUAGE);
+    let l = language.read();
+    let router = use_router(&cx);
+    let state = use_atom_ref(&cx, STATE);
+    let switcher = use_coroutine_handle::<ProfileAction>(&cx);
 
     let pin = use_state(&cx, String::new);
     let show_tip = use_state(&cx, || false);
     let error = use_state(&cx, String::new);
-    let error_class = if error.is_empty() {
-        css!("opacity: 0")
-    } else {
-        "error_text"
-    };
     let auth_text = l.auth_tooltip.clone();
 
     let confirm_button_class = if error.is_empty() {
@@ -44,6 +52,121 @@ pub fn Unlock(cx: Scope<UnlockProps>) -> Element {
     };
 
     let tesseract_available = cx.props.tesseract.exist("keypair");
+    let profile_name = profiles::active().name;
+    let show_profiles = use_state(&cx, || false);
+    // a new pin can also be used for an account restored from its recovery phrase
+    let importing = use_state(&cx, || false);
+    let next_route = match **importing {
+        true => "/restore",
+        false => "/loading",
+    };
+
+    let lockout = use_state(&cx, Lockout::load);
+    // the kind of secret a new keystore is locked with, an existing one keeps its own
+    let new_secret = use_state(&cx, || lockout.secret);
+    let secret = match tesseract_available {
+        true => lockout.secret,
+        false => **new_secret,
+    };
+    let waiting = lockout
+        .wait_remaining()
+        .filter(|_| tesseract_available)
+        .map(lockout::wait_message);
+    let wipe_warning = lockout
+        .attempts_left()
+        .filter(|left| tesseract_available && lockout.failed_attempts > 0 && *left > 0)
+        .map(|left| format!("{left} attempts left before this profile is erased."));
+
+    // counts the wait down a second at a time
+    let tick = use_state(&cx, || 0u32);
+    use_future(&cx, (lockout.get(), tick.get()), |(lockout, _)| {
+        to_owned![tick];
+        async move {
+            if let Some(wait) = lockout.wait_remaining() {
+                tokio::time::sleep(wait.min(Duration::from_secs(1))).await;
+                tick.modify(|tick| tick.wrapping_add(1));
+            }
+        }
+    });
+
+    // only a deliberate attempt is checked, so every wrong guess is counted
+    let attempt = move || {
+        let value = pin.get();
+        if !tesseract_available {
+            if let Err(e) = new_secret.validate(value) {
+                error.set(e);
+                return;
+            }
+        } else if lockout.wait_remaining().is_some() {
+            return;
+        }
+        let mut next = lockout.get().clone();
+        let outcome = match cx.props.tesseract.unlock(value.as_bytes()) {
+            Ok(_) => {
+                next.record_success();
+                next.secret = secret;
+                None
+            }
+            Err(_) => Some(next.record_failure()),
+        };
+        if let Err(e) = next.save() {
+            log::error!("Not possible to save failed attempts: {e}");
+        }
+        lockout.set(next);
+        match outcome {
+            None => {
+                if let Err(e) = at_rest::unlock(&cx.props.tesseract) {
+                    log::error!("Not possible to decrypt local data: {e}");
+                }
+                // brings back what was cleared when the app was locked. Saving straight away encrypts files
+                // written before encryption.
+                let restored = PersistedState::load_or_initial();
+                restored.save();
+                *state.write() = restored;
+                let config = Config::load_config_or_default();
+                config.notifications.apply();
+                if let Err(e) = config.save() {
+                    log::error!("Not possible to save the config: {e}");
+                }
+                router.replace_route(next_route, None, None)
+            }
+            Some(outcome) => {
+                pin.set(String::new());
+                error.set(match secret {
+                    Secret::Pin => language.read().invalid_pin.clone(),
+                    Secret::Passphrase => String::from("Incorrect passphrase supplied."),
+                });
+                if outcome == Outcome::Wipe {
+                    if let Some(switcher) = switcher {
+                        switcher.send(ProfileAction::Wipe);
+                    }
+                }
+            }
+        }
+    };
+
+    let (title, description) = match (secret, tesseract_available) {
+        (Secret::Pin, true) => (l.enter_pin.clone(), l.enter_your_pin.clone()),
+        (Secret::Pin, false) => (l.create_pin.clone(), l.choose_a_pin.clone()),
+        (Secret::Passphrase, true) => (
+            String::from("Enter Passphrase"),
+            String::from("Enter your passphrase to unlock your account."),
+        ),
+        (Secret::Passphrase, false) => (
+            String::from("Create a Passphrase"),
+            String::from("Choose a passphrase of at least 8 characters to secure your account."),
+        ),
+    };
+    let (other_secret, switch_text) = match secret {
+        Secret::Pin => (Secret::Passphrase, "Use a passphrase instead"),
+        Secret::Passphrase => (Secret::Pin, "Use a PIN instead"),
+    };
+    let message = waiting.unwrap_or_else(|| error.to_string());
+    let message_class = if message.is_empty() {
+        css!("opacity: 0")
+    } else {
+        "error_text"
+    };
 
     cx.render(rsx! {
         div {
@@ -51,128 +174,187 @@ pub fn Unlock(cx: Scope<UnlockProps>) -> Element {
             div {
                 class: "container",
                 h2 {
-                    (tesseract_available).then(|| l.enter_pin.clone()),
-                    (!tesseract_available).then(|| l.create_pin.clone()),
+                    "{title}"
                 },
                 label {
-                    (tesseract_available).then(|| l.enter_your_pin.clone()),
-                    (!tesseract_available).then(|| l.choose_a_pin.clone()),
+                    "{description}"
+                },
+                p {
+                    class: "profile-name",
+                    "Profile: {profile_name}"
                 },
+                (**importing).then(|| rsx!(
+                    p {
+                        class: "import-note",
+                        "Your recovery phrase is asked for next."
+                    }
+                )),
                 div {
                     class: "m-bottom-xl",
                 },
-                div {
-                    style: "position: relative;",
-                    Pin {
-                        pin: pin.as_bytes().to_vec(),
-                        error: !error.is_empty()
-                    },
-                    show_tip.then(||
+                match secret {
+                    Secret::Pin => rsx!(
+                        div {
+                            style: "position: relative;",
+                            Pin {
+                                pin: pin.as_bytes().to_vec(),
+                                error: !error.is_empty()
+                            },
+                            show_tip.then(||
+                                rsx! {
+                                    span {
+                                        class: "{confirm_button_class}",
+                                        Button {
+                                            icon: if error.is_empty() {
+                                                Shape::Check
+                                            } else {
+                                                Shape::XMark
+                                            }
+                                            on_pressed: move |_| attempt(),
+                                        },
+                                    },
+                                }
+                            ),
+                        },
+                        div {
+                            class: "m-bottom-xl",
+                        },
+                        show_tip.then(||
                         rsx! {
                             span {
-                                class: "{confirm_button_class}",
-                                Button {
-                                    icon: if error.is_empty() {
-                                        Shape::Check
-                                    } else {
-                                        Shape::XMark
-                                    }
-                                    on_pressed: move |_| {
-                                        let tesseract = cx.props.tesseract.clone();
-                                        match tesseract.unlock(pin.as_bytes()) {
-                                            Ok(_) => {
-                                                router.replace_route("/loading", None, None)
-                                            },
-                                            Err(_) => error.set(l2.invalid_pin.clone()),
+                                class: "pin_tooltip",
+                                Tooltip {
+                                    text: auth_text,
+                                    arrow_position: ArrowPosition::Top
+                                }
+                            }
+                        }),
+                        input {
+                            class: "invis-input",
+                            value: "{pin}",
+                            autofocus: "true",
+                            oninput: move |evt| {
+                                error.set(String::from(""));
+
+                                // If the pin entered is longer than the allowed limit, ignore it.
+                                if evt.value.len() <= 6 {
+                                    pin.set(evt.value.to_string());
+                                } else {
+                                    //Because we exceeded 6, we want to show the tooltip showing the error
+                                    show_tip.set(true);
+                                    //This will spawn the background task as kind of a "timeout" for "show_tip" state
+                                    cx.spawn({
+                                        // this is the equiv if `let show_tip = show_tip.clone()`
+                                        to_owned![show_tip];
+                                        async move {
+                                            // since we are using `async` we want to avoid using `std::thread::sleep` as it would stall all
+                                            // running task. Instead, rely on internal functions from either tokio or futures to
+                                            // delay for a set duration
+                                            tokio::time::sleep(std::time::Duration::from_secs(4)).await;
+                                            show_tip.set(false);
                                         }
-                                    },
+                                    });
+                                    pin.set(evt.value[..6].to_string());
+                                }
+                            },
+                            onkeyup: move |evt| {
+                                if evt.key_code == KeyCode::Enter {
+                                    attempt();
+                                }
+                            },
+                        }
+                    ),
+                    Secret::Passphrase => rsx!(
+                        div {
+                            class: "passphrase",
+                            input {
+                                class: "passphrase-input",
+                                "type": "password",
+                                placeholder: "Passphrase",
+                                value: "{pin}",
+                                autofocus: "true",
+                                oninput: move |evt| {
+                                    error.set(String::new());
+                                    pin.set(evt.value.chars().take(secret.max_length()).collect());
+                                },
+                                onkeyup: move |evt| {
+                                    if evt.key_code == KeyCode::Enter {
+                                        attempt();
+                                    }
                                 },
                             },
+                            Button {
+                                icon: Shape::ArrowRight,
+                                disabled: pin.is_empty(),
+                                on_pressed: move |_| attempt(),
+                            },
                         }
                     ),
-                }
-                div {
-                    class: "m-bottom-xl",
                 },
-                show_tip.then(||
-                rsx! {
-                    span {
-                        class: "pin_tooltip",
-                        Tooltip {
-                            text: auth_text,
-                            arrow_position: ArrowPosition::Top
-                        }
-                    }
-                }),
                 p {
-                    class: "{error_class}",
-                    "{error}　"
+                    class: "{message_class}",
+                    "{message}　"
                 },
-                input {
-                    class: "invis-input",
-                    value: "{pin}",
-                    autofocus: "true",
-                    oninput: move |evt| {
-                        error.set(String::from(""));
-
-                        // If the pin entered is longer than the allowed limit, ignore it.
-                        if evt.value.len() <= 6 {
-                            pin.set(evt.value.to_string());
-                        } else {
-                            //Because we exceeded 6, we want to show the tooltip showing the error
-                            show_tip.set(true);
-                            //This will spawn the background task as kind of a "timeout" for "show_tip" state
-                            cx.spawn({
-                                // this is the equiv if `let show_tip = show_tip.clone()`
-                                to_owned![show_tip];
-                                async move {
-                                    // since we are using `async` we want to avoid using `std::thread::sleep` as it would stall all
-                                    // running task. Instead, rely on internal functions from either tokio or futures to
-                                    // delay for a set duration
-                                    tokio::time::sleep(std::time::Duration::from_secs(4)).await;
-                                    show_tip.set(false);
-                                }
-                            });
-                            pin.set(evt.value[..6].to_string());
-                        }
-
-                        // If tesseract exists, we can try to unlock as we type to save time
-                        // We can ignore the error though since we're doing this without the users command
-                        if evt.value.len() >= 4 && tesseract_available {
-                            let tesseract = cx.props.tesseract.clone();
-                            if tesseract.unlock(evt.value.as_ref()).is_ok() {
-                                router2.replace_route("/loading", None, None)
-                            }
-                        }
-                    },
-                    onkeyup: move |evt| {
-                        if evt.key_code == KeyCode::Enter {
-                            if pin.len() < 4 && !tesseract_available {
-                                error.set(l.short_pin.clone());
-                            } else {
-                                let tesseract = cx.props.tesseract.clone();
-                                match tesseract.unlock(pin.as_bytes()) {
-                                    Ok(_) => router3.replace_route("/loading", None, None),
-                                    Err(_) => error.set(l.invalid_pin.clone()),
-                                }
-                            }
+                wipe_warning.map(|warning| rsx!(
+                    p {
+                        class: "wipe-warning",
+                        "{warning}"
+                    }
+                )),
+                (!tesseract_available).then(|| rsx!(
+                    Button {
+                        text: String::from(switch_text),
+                        state: button::State::Transparent,
+                        on_pressed: move |_| {
+                            pin.set(String::new());
+                            error.set(String::new());
+                            new_secret.set(other_secret);
+                        },
+                    }
+                )),
+            },
+            (**show_profiles).then(|| rsx!(
+                Popup {
+                    hidden: false,
+                    on_dismiss: move |_| show_profiles.set(false),
+                    children: cx.render(rsx!(
+                        div {
+                            id: "unlock-profiles",
+                            h2 {
+                                "Profiles"
+                            },
+                            ProfileManager {},
                         }
-                    },
+                    )),
                 }
-            },
+            )),
             div {
                 class: "login-actions",
                 Button {
-                    icon: Shape::User,
-                    disabled: true,
+                    icon: Shape::Users,
+                    text: String::from("Profiles"),
                     state: button::State::Secondary,
-                    on_pressed: move |_| {},
+                    on_pressed: move |_| show_profiles.set(true),
+                },
+                Button {
+                    icon: Shape::User,
+                    text: String::from("New Account"),
+                    disabled: tesseract_available,
+                    state: match **importing {
+                        true => button::State::Secondary,
+                        false => button::State::Primary,
+                    },
+                    on_pressed: move |_| importing.set(false),
                 },
                 Button {
                     icon: Shape::GlobeAlt,
-                    disabled: true,
-                    state: button::State::Secondary,
-                    on_pressed: move |_| {},
+                    text: String::from("Restore Account"),
+                    disabled: tesseract_available,
+                    state: match **importing {
+                        true => button::State::Primary,
+                        false => button::State::Secondary,
+                    },
+                    on_pressed: move |_| importing.set(true),
                 },
             }
         },
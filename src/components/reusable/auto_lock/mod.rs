@@ -0,0 +1,85 @@
+// Locks the app again once it's been left alone for a while, or straight away when asked to from the nav.
+// It wraps every route, so input anywhere counts as activity and the unlock screen can be shown from
+// wherever the user is.
+
+use std::{
+    cell::Cell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use ::utils::at_rest;
+use dioxus::core::to_owned;
+use dioxus::router::{use_router, RouterService};
+use dioxus::{fermi::UseAtomRef, prelude::*};
+use futures::StreamExt;
+use state::{PersistedState, STATE};
+use warp::tesseract::Tesseract;
+
+use crate::iutils::config::Config;
+
+// how often the time since the last input is looked at
+const CHECK_EVERY: Duration = Duration::from_secs(5);
+
+/// sent by the "lock now" button
+pub struct LockNow;
+
+#[derive(Props)]
+pub struct Props<'a> {
+    tesseract: Tesseract,
+    children: Element<'a>,
+}
+
+fn lock(tesseract: &Tesseract, state: &UseAtomRef<PersistedState>, router: &RouterService) {
+    tesseract.lock();
+    at_rest::lock();
+    // the messages go with the routes showing them, the previews kept in the state go here
+    state.write().clear_previews();
+    router.replace_route("/", None, None);
+}
+
+#[allow(non_snake_case)]
+pub fn AutoLock<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
+    let router = use_router(&cx).clone();
+    let state = use_atom_ref(&cx, STATE).clone();
+    let tesseract = cx.props.tesseract.clone();
+    // a plain cell, since input shouldn't render anything again
+    let last_input = &*cx.use_hook(|_| Rc::new(Cell::new(Instant::now())));
+
+    use_coroutine(&cx, |mut rx: UnboundedReceiver<LockNow>| {
+        to_owned![tesseract, state, router];
+        async move {
+            while rx.next().await.is_some() {
+                lock(&tesseract, &state, &router);
+            }
+        }
+    });
+
+    use_future(&cx, (), |_| {
+        to_owned![tesseract, state, router, last_input];
+        async move {
+            loop {
+                tokio::time::sleep(CHECK_EVERY).await;
+                let minutes = Config::load_config_or_default().privacy.auto_lock_minutes;
+                if minutes == 0 || !tesseract.is_unlock() {
+                    continue;
+                }
+                if last_input.get().elapsed() >= Duration::from_secs(u64::from(minutes) * 60) {
+                    log::debug!("locking after {minutes} minutes without input");
+                    lock(&tesseract, &state, &router);
+                }
+            }
+        }
+    });
+
+    cx.render(rsx! {
+        div {
+            class: "auto-lock",
+            onmousemove: move |_| last_input.set(Instant::now()),
+            onmousedown: move |_| last_input.set(Instant::now()),
+            onkeydown: move |_| last_input.set(Instant::now()),
+            onwheel: move |_| last_input.set(Instant::now()),
+            &cx.props.children
+        }
+    })
+}
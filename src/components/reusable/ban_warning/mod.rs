@@ -0,0 +1,72 @@
+use dioxus::prelude::*;
+use dioxus_heroicons::{outline::Shape, Icon};
+use ui_kit::button::{self, Button};
+
+use crate::iutils::ban_list::{Ban, Source};
+
+#[derive(Props)]
+pub struct Props<'a> {
+    ban: Ban,
+    // what going ahead does, like "Show Anyway"
+    continue_text: String,
+    cancel_text: String,
+    on_continue: EventHandler<'a, ()>,
+    on_cancel: EventHandler<'a, ()>,
+}
+
+/// stands in front of anything from a banned account until the user decides to go ahead
+#[allow(non_snake_case)]
+pub fn BanWarning<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
+    let ban = &cx.props.ban;
+    let list = match ban.source {
+        Source::Global => "the Satellite Global Ban List",
+        Source::Local => "your ban list",
+    };
+    let reason = match ban.reason.is_empty() {
+        true => String::from("No reason was given."),
+        false => ban.reason.clone(),
+    };
+    let added = ban
+        .added
+        .map(|added| format!("Added {}", added.format("%B %-d, %Y")));
+
+    cx.render(rsx! {
+        div {
+            class: "ban-warning",
+            Icon {
+                icon: Shape::ShieldExclamation,
+            },
+            h3 {
+                "This account is on {list}"
+            },
+            p {
+                class: "reason",
+                "{reason}"
+            },
+            added.map(|added| rsx!(
+                p {
+                    class: "added",
+                    "{added}"
+                }
+            )),
+            p {
+                class: "did",
+                "{ban.did}"
+            },
+            div {
+                class: "actions",
+                Button {
+                    text: cx.props.cancel_text.clone(),
+                    state: button::State::Secondary,
+                    on_pressed: move |_| cx.props.on_cancel.call(()),
+                },
+                Button {
+                    icon: Shape::ExclamationTriangle,
+                    text: cx.props.continue_text.clone(),
+                    state: button::State::Danger,
+                    on_pressed: move |_| cx.props.on_continue.call(()),
+                },
+            }
+        }
+    })
+}
@@ -0,0 +1,515 @@
+// Shows a file inside the app instead of saving it somewhere first. Files are read straight from
+// Constellation; attachments are fetched through RayGun into a preview cache next to the app data, so
+// opening the same one again doesn't download it twice.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use dioxus::{core::to_owned, events::KeyCode, prelude::*};
+use dioxus_heroicons::outline::Shape;
+use futures::StreamExt;
+use humansize::{format_size, DECIMAL};
+use once_cell::sync::Lazy;
+use ui_kit::button::Button;
+use utils::DEFAULT_PATH;
+use uuid::Uuid;
+use warp::constellation::Progression;
+
+use crate::{components::main::files::operations::switch_path, Messaging, Storage};
+
+// under DEFAULT_PATH, where previewed attachments are kept
+pub const CACHE_DIR: &str = "previews";
+// anything bigger is only offered for download, it would all have to be held in memory
+const MAX_PREVIEW_BYTES: usize = 64 * 1024 * 1024;
+// longer text files are cut, highlighting them gets slow
+const MAX_TEXT_BYTES: usize = 1024 * 1024;
+const MIN_ZOOM: f64 = 0.25;
+const MAX_ZOOM: f64 = 8.0;
+const FOCUS_SCRIPT: &str = "(function focus(tries) {
+    const preview = document.getElementById('file-preview');
+    if (preview) preview.focus();
+    else if (tries > 0) requestAnimationFrame(() => focus(tries - 1));
+})(30)";
+
+// attachments being downloaded, by where they're kept. whoever comes second waits and reads the result.
+static DOWNLOADING: Lazy<Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Clone, PartialEq)]
+pub enum PreviewSource {
+    // the folder the file is in
+    Constellation {
+        storage: Storage,
+        path: Vec<String>,
+    },
+    Attachment {
+        messaging: Messaging,
+        conversation_id: Uuid,
+        message_id: Uuid,
+    },
+}
+
+#[derive(Clone, PartialEq)]
+pub struct PreviewItem {
+    pub name: String,
+    pub size: usize,
+    pub source: PreviewSource,
+}
+
+#[derive(Clone, PartialEq)]
+enum Content {
+    Image(String),
+    Video(String),
+    Audio(String),
+    Pdf(String),
+    Markdown(String),
+    // highlighted HTML and whether the file was cut short
+    Text(String, bool),
+    Unsupported,
+}
+
+pub fn extension(name: &str) -> String {
+    Path::new(name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+pub fn mime_type(extension: &str) -> Option<&'static str> {
+    let mime = match extension {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "ico" => "image/x-icon",
+        "svg" => "image/svg+xml",
+        "mp4" | "m4v" => "video/mp4",
+        "webm" => "video/webm",
+        "mov" => "video/quicktime",
+        "ogv" => "video/ogg",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" | "oga" | "opus" => "audio/ogg",
+        "flac" => "audio/flac",
+        "m4a" | "aac" => "audio/mp4",
+        "pdf" => "application/pdf",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+pub fn data_url(mime: &str, bytes: &[u8]) -> String {
+    format!("data:{mime};base64,{}", base64::encode(bytes))
+}
+
+fn content(name: &str, bytes: Vec<u8>) -> Content {
+    let extension = extension(name);
+    if let Some(mime) = mime_type(&extension) {
+        let url = data_url(mime, &bytes);
+        return match mime.split('/').next() {
+            Some("image") => Content::Image(url),
+            Some("video") => Content::Video(url),
+            Some("audio") => Content::Audio(url),
+            _ => Content::Pdf(url),
+        };
+    }
+
+    let truncated = bytes.len() > MAX_TEXT_BYTES;
+    let text_bytes = &bytes[..bytes.len().min(MAX_TEXT_BYTES)];
+    // a NUL byte is a good sign the file isn't text at all
+    if text_bytes.contains(&0) {
+        return Content::Unsupported;
+    }
+    // cutting the file may split a character, which is fine to lose
+    let text = match std::str::from_utf8(text_bytes) {
+        Ok(text) => text.to_string(),
+        Err(error) if truncated && error.error_len().is_none() => {
+            String::from_utf8_lossy(&text_bytes[..error.valid_up_to()]).to_string()
+        }
+        Err(_) => return Content::Unsupported,
+    };
+    match extension.as_str() {
+        "md" | "markdown" => Content::Markdown(utils::markdown::render(&text)),
+        _ => Content::Text(utils::markdown::highlight(&text, &extension), truncated),
+    }
+}
+
+// where previewed attachments are kept. the name comes from a peer, so only its last part is used and
+// nothing that would leave the message's folder is accepted.
+fn cache_path(message_id: Uuid, name: &str) -> Result<PathBuf, String> {
+    let file_name = Path::new(name)
+        .file_name()
+        .filter(|file_name| !file_name.is_empty() && *file_name != "..")
+        .ok_or_else(|| format!("{name:?} isn't a valid attachment name"))?;
+    Ok(DEFAULT_PATH
+        .read()
+        .join(CACHE_DIR)
+        .join(message_id.to_string())
+        .join(file_name))
+}
+
+/// the contents of an attachment, fetched once and then read from the cache. the size a peer announces
+/// can't be trusted, so the download is given up on once more than `limit` bytes have been written.
+pub async fn download_attachment(
+    messaging: &Messaging,
+    conversation_id: Uuid,
+    message_id: Uuid,
+    name: &str,
+    limit: u64,
+) -> Result<Vec<u8>, String> {
+    let path = cache_path(message_id, name)?;
+    let downloading = DOWNLOADING
+        .lock()
+        .unwrap()
+        .entry(path.clone())
+        .or_default()
+        .clone();
+    let guard = downloading.lock().await;
+    let result =
+        cached_or_download(messaging, conversation_id, message_id, name, &path, limit).await;
+    drop(guard);
+    // the last one done with it takes it out
+    let mut all = DOWNLOADING.lock().unwrap();
+    if Arc::strong_count(&downloading) == 2 {
+        all.remove(&path);
+    }
+    result
+}
+
+async fn cached_or_download(
+    messaging: &Messaging,
+    conversation_id: Uuid,
+    message_id: Uuid,
+    name: &str,
+    path: &Path,
+    limit: u64,
+) -> Result<Vec<u8>, String> {
+    let too_big = || format!("{name} is bigger than it claimed to be.");
+    if let Ok(metadata) = tokio::fs::metadata(path).await {
+        if metadata.len() > limit {
+            return Err(too_big());
+        }
+        return tokio::fs::read(path)
+            .await
+            .map_err(|error| error.to_string());
+    }
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|error| error.to_string())?;
+    }
+
+    // written under another name until it's complete, so a download that was cut off, even by quitting,
+    // is never taken for the file. one left from before is started over.
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let partial = path.with_file_name(format!(".{file_name}.part"));
+    let _ = tokio::fs::remove_file(&partial).await;
+
+    let mut progress = messaging
+        .download(
+            conversation_id,
+            message_id,
+            name.to_string(),
+            partial.clone(),
+        )
+        .await
+        .map_err(|error| error.to_string())?;
+    let mut complete = false;
+    while let Some(progress) = progress.next().await {
+        let failure = match progress {
+            Progression::CurrentProgress { current, .. } if current as u64 > limit => too_big(),
+            Progression::ProgressFailed { error, .. } => {
+                error.unwrap_or_else(|| String::from("download failed"))
+            }
+            Progression::ProgressComplete { .. } => {
+                complete = true;
+                break;
+            }
+            _ => continue,
+        };
+        let _ = tokio::fs::remove_file(&partial).await;
+        return Err(failure);
+    }
+    if !complete {
+        let _ = tokio::fs::remove_file(&partial).await;
+        return Err(format!("{name} wasn't downloaded completely."));
+    }
+
+    let written = tokio::fs::metadata(&partial)
+        .await
+        .map_err(|error| error.to_string())?
+        .len();
+    if written > limit {
+        let _ = tokio::fs::remove_file(&partial).await;
+        return Err(too_big());
+    }
+    tokio::fs::rename(&partial, path)
+        .await
+        .map_err(|error| error.to_string())?;
+    tokio::fs::read(path)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+async fn load(item: PreviewItem) -> Result<Content, String> {
+    if item.size > MAX_PREVIEW_BYTES {
+        return Err(format!(
+            "{} is too big to preview, download it instead.",
+            format_size(item.size, DECIMAL)
+        ));
+    }
+    let bytes = match &item.source {
+        PreviewSource::Constellation { storage, path } => {
+            let switched = switch_path(storage, path).await;
+            let buffer = storage.get_buffer(&item.name).await;
+            drop(switched);
+            buffer.map_err(|error| error.to_string())?
+        }
+        PreviewSource::Attachment {
+            messaging,
+            conversation_id,
+            message_id,
+        } => {
+            download_attachment(
+                messaging,
+                *conversation_id,
+                *message_id,
+                &item.name,
+                MAX_PREVIEW_BYTES as u64,
+            )
+            .await?
+        }
+    };
+    // the file may have been written to since its size was read
+    if bytes.len() > MAX_PREVIEW_BYTES {
+        return Err(String::from(
+            "This file is too big to preview, download it instead.",
+        ));
+    }
+    let name = item.name.clone();
+    tokio::task::spawn_blocking(move || content(&name, bytes))
+        .await
+        .map_err(|error| error.to_string())
+}
+
+#[derive(Props)]
+pub struct Props<'a> {
+    // the files which can be stepped through, in order
+    items: Vec<PreviewItem>,
+    start: usize,
+    on_close: EventHandler<'a, ()>,
+}
+
+#[allow(non_snake_case)]
+pub fn FilePreview<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
+    let index = use_state(&cx, || cx.props.start);
+    let loaded = use_state(&cx, || None::<(usize, Result<Content, String>)>);
+    let zoom = use_state(&cx, || 1.0_f64);
+    let offset = use_state(&cx, || (0.0_f64, 0.0_f64));
+    let dragging = use_ref(&cx, || None::<(i32, i32)>);
+    let eval = use_eval(&cx);
+
+    // keys only reach the preview when it has focus, which it can only get once it's on the page
+    cx.use_hook(|_| eval(String::from(FOCUS_SCRIPT)));
+
+    let count = cx.props.items.len();
+    let current = (**index).min(count.saturating_sub(1));
+    let item = cx.props.items.get(current).cloned();
+
+    use_future(&cx, (&item, &current), |(item, current)| {
+        to_owned![loaded, zoom, offset];
+        async move {
+            zoom.set(1.0);
+            offset.set((0.0, 0.0));
+            let item = match item {
+                Some(item) => item,
+                None => return,
+            };
+            let result = load(item).await;
+            if let Err(error) = &result {
+                log::error!("Not possible to preview the file: {error}");
+            }
+            loaded.set(Some((current, result)));
+        }
+    });
+
+    let item = item?;
+    let step = move |forward: bool| {
+        if count == 0 {
+            return;
+        }
+        let next = match forward {
+            true => (current + 1) % count,
+            false => (current + count - 1) % count,
+        };
+        index.set(next);
+    };
+    let size = format_size(item.size, DECIMAL);
+    let position = current + 1;
+    let (x, y) = **offset;
+    let scale = **zoom;
+    let shown = match &**loaded {
+        Some((loaded_index, result)) if *loaded_index == current => Some(result.clone()),
+        _ => None,
+    };
+
+    cx.render(rsx! {
+        div {
+            id: "file-preview",
+            tabindex: "0",
+            onclick: move |evt| {
+                evt.cancel_bubble();
+                cx.props.on_close.call(());
+            },
+            onkeydown: move |evt| match evt.key_code {
+                KeyCode::Escape => cx.props.on_close.call(()),
+                KeyCode::LeftArrow => step(false),
+                KeyCode::RightArrow => step(true),
+                _ => {}
+            },
+            div {
+                class: "preview-header",
+                onclick: move |evt| evt.cancel_bubble(),
+                span {
+                    class: "name",
+                    title: "{item.name}",
+                    "{item.name}"
+                },
+                span {
+                    class: "details",
+                    "{size} · {position} of {count}"
+                },
+                Button {
+                    icon: Shape::XMark,
+                    state: ui_kit::button::State::Transparent,
+                    on_pressed: move |_| cx.props.on_close.call(()),
+                },
+            },
+            div {
+                class: "preview-body",
+                (count > 1).then(|| rsx!(
+                    div {
+                        class: "step previous",
+                        onclick: move |evt| evt.cancel_bubble(),
+                        Button {
+                            icon: Shape::ChevronLeft,
+                            state: ui_kit::button::State::Secondary,
+                            on_pressed: move |_| step(false),
+                        },
+                    }
+                )),
+                div {
+                    class: "preview-content",
+                    onclick: move |evt| evt.cancel_bubble(),
+                    match shown {
+                        None => rsx!(
+                            p {
+                                class: "message",
+                                "Loading..."
+                            }
+                        ),
+                        Some(Err(error)) => rsx!(
+                            p {
+                                class: "message",
+                                "{error}"
+                            }
+                        ),
+                        Some(Ok(Content::Image(url))) => rsx!(
+                            div {
+                                class: "image-frame",
+                                prevent_default: "onwheel",
+                                onwheel: move |evt| {
+                                    let factor = if evt.data.delta_y < 0.0 { 1.1 } else { 1.0 / 1.1 };
+                                    zoom.set((scale * factor).clamp(MIN_ZOOM, MAX_ZOOM));
+                                },
+                                onmousedown: move |evt| {
+                                    *dragging.write_silent() = Some((evt.data.client_x, evt.data.client_y));
+                                },
+                                onmousemove: move |evt| {
+                                    let last = *dragging.read();
+                                    if let Some((last_x, last_y)) = last {
+                                        let (client_x, client_y) = (evt.data.client_x, evt.data.client_y);
+                                        *dragging.write_silent() = Some((client_x, client_y));
+                                        offset.set((x + (client_x - last_x) as f64, y + (client_y - last_y) as f64));
+                                    }
+                                },
+                                onmouseup: move |_| *dragging.write_silent() = None,
+                                onmouseleave: move |_| *dragging.write_silent() = None,
+                                // double click goes back to the original size
+                                ondblclick: move |_| {
+                                    zoom.set(1.0);
+                                    offset.set((0.0, 0.0));
+                                },
+                                img {
+                                    draggable: "false",
+                                    src: "{url}",
+                                    style: "transform: translate({x}px, {y}px) scale({scale});",
+                                }
+                            }
+                        ),
+                        Some(Ok(Content::Video(url))) => rsx!(
+                            video {
+                                src: "{url}",
+                                controls: "true",
+                            }
+                        ),
+                        Some(Ok(Content::Audio(url))) => rsx!(
+                            audio {
+                                src: "{url}",
+                                controls: "true",
+                            }
+                        ),
+                        Some(Ok(Content::Pdf(url))) => rsx!(
+                            embed {
+                                src: "{url}",
+                                "type": "application/pdf",
+                            }
+                        ),
+                        Some(Ok(Content::Markdown(html))) => rsx!(
+                            div {
+                                class: "markdown",
+                                dangerous_inner_html: "{html}",
+                            }
+                        ),
+                        Some(Ok(Content::Text(html, truncated))) => rsx!(
+                            div {
+                                class: "text",
+                                truncated.then(|| rsx!(
+                                    p {
+                                        class: "message",
+                                        "Only the beginning of this file is shown."
+                                    }
+                                )),
+                                div {
+                                    dangerous_inner_html: "{html}",
+                                }
+                            }
+                        ),
+                        Some(Ok(Content::Unsupported)) => rsx!(
+                            p {
+                                class: "message",
+                                "This kind of file can't be previewed."
+                            }
+                        ),
+                    }
+                },
+                (count > 1).then(|| rsx!(
+                    div {
+                        class: "step next",
+                        onclick: move |evt| evt.cancel_bubble(),
+                        Button {
+                            icon: Shape::ChevronRight,
+                            state: ui_kit::button::State::Secondary,
+                            on_pressed: move |_| step(true),
+                        },
+                    }
+                )),
+            }
+        }
+    })
+}
@@ -0,0 +1,70 @@
+use arboard::Clipboard;
+use dioxus::prelude::*;
+use dioxus_heroicons::outline::Shape;
+use dioxus_toast::ToastInfo;
+use ui_kit::button::{self, Button};
+
+use crate::{iutils::invite, Account, TOAST_MANAGER};
+
+#[derive(Props, PartialEq)]
+pub struct Props {
+    account: Account,
+}
+
+/// the user's own friend code as a QR code and as an invite link, for someone else to scan or open
+#[allow(non_snake_case)]
+pub fn FriendCode(cx: Scope<Props>) -> Element {
+    let toast = use_atom_ref(&cx, TOAST_MANAGER);
+    let did = match cx.props.account.get_own_identity() {
+        Ok(identity) => identity.did_key(),
+        Err(_) => return None,
+    };
+    let code = invite::friend_code(&did);
+    let code2 = code.clone();
+    let link = invite::link(&did);
+    let qr_code = invite::qr_code(&link);
+
+    let copy = move |text: &str, message: &str| {
+        let copied = Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text));
+        let message = match copied {
+            Ok(_) => message.to_string(),
+            Err(e) => {
+                log::error!("failed to copy to the clipboard: {e}");
+                String::from("Not possible to copy that.")
+            }
+        };
+        toast.write().popup(ToastInfo::simple(&message));
+    };
+
+    cx.render(rsx! {
+        div {
+            class: "friend-code",
+            qr_code.map(|src| rsx!(
+                img {
+                    class: "qr-code",
+                    alt: "QR code of your friend code",
+                    src: "{src}",
+                }
+            )),
+            span {
+                class: "code",
+                "{code}"
+            },
+            div {
+                class: "friend-code-controls",
+                Button {
+                    icon: Shape::ClipboardDocument,
+                    text: String::from("Copy Code"),
+                    state: button::State::Secondary,
+                    on_pressed: move |_| copy(&code2, "Friend code copied!"),
+                },
+                Button {
+                    icon: Shape::Link,
+                    text: String::from("Copy Invite Link"),
+                    state: button::State::Secondary,
+                    on_pressed: move |_| copy(&link, "Invite link copied!"),
+                },
+            },
+        }
+    })
+}
@@ -1,5 +1,12 @@
+pub mod auto_lock;
+pub mod ban_warning;
+pub mod file_preview;
+pub mod friend_code;
 pub mod nav;
 pub mod page_header;
+pub mod profile_modal;
+pub mod profiles;
 pub mod sidebar;
 pub mod textarea;
 pub mod toolbar;
+pub mod unread_badge;
@@ -1,20 +1,29 @@
 use ::utils::notifications::PushNotification;
 use dioxus::prelude::*;
 use dioxus_heroicons::outline::Shape;
+use dioxus_toast::ToastInfo;
 use futures::StreamExt;
 use state::{Actions, STATE};
+use std::time::Duration;
 use ui_kit::{
     button::{self, Button},
     context_menu::{ContextItem, ContextMenu},
     numeric_indicator::NumericIndicator,
 };
 
-use crate::{Account, Messaging, LANGUAGE};
+use crate::{
+    components::reusable::{auto_lock::LockNow, unread_badge::UnreadBadge},
+    iutils::{ban_list, config::Config, friend_requests},
+    Account, Messaging, LANGUAGE, TOAST_MANAGER,
+};
 use warp::{
     multipass::MultiPassEventKind,
     raygun::{Conversation, ConversationType},
 };
 
+// sent requests are checked for expiry far less often than the request lists are refreshed
+const EXPIRY_CHECK: Duration = Duration::from_secs(60);
+
 #[derive(PartialEq, Eq, Clone, Copy)]
 pub enum NavEvent {
     Home,
@@ -34,6 +43,7 @@ pub struct Props {
 pub fn Nav(cx: Scope<Props>) -> Element {
     log::debug!("rendering reusable Nav");
     let state = use_atom_ref(&cx, STATE).clone();
+    let toast = use_atom_ref(&cx, TOAST_MANAGER).clone();
     let l = use_atom_ref(&cx, LANGUAGE).read().clone();
     let router = use_router(&cx).clone();
     let router2 = router.clone();
@@ -46,6 +56,7 @@ pub fn Nav(cx: Scope<Props>) -> Element {
     });
 
     let route = use_route(&cx).last_segment();
+    let locker = use_coroutine_handle::<LockNow>(&cx);
 
     let active = match route {
         Some(r) => match r {
@@ -85,6 +96,13 @@ pub fn Nav(cx: Scope<Props>) -> Element {
             while let Some(event) = stream.next().await {
                 match event {
                     MultiPassEventKind::FriendRequestReceived { from } => {
+                        if ban_list::is_banned(&from) {
+                            log::info!("denying a friend request from banned account {from}");
+                            if let Err(e) = multipass.deny_request(&from) {
+                                log::error!("failed to deny the friend request: {e}");
+                            }
+                            continue;
+                        }
                         // Use to show the name or did of who its from
                         let name_or_did = multipass
                             .get_identity(from.clone().into())
@@ -125,6 +143,18 @@ pub fn Nav(cx: Scope<Props>) -> Element {
                         }
                         log::debug!("creating chat");
                         let _result = rg.create_conversation(&did).await;
+                        // a note left on the request goes out once they've accepted it
+                        if let Some(note) = friend_requests::notes().remove(&did.to_string()) {
+                            friend_requests::remove_note(&did);
+                            if let Err(e) =
+                                friend_requests::send_note(rg.clone(), did.clone(), note).await
+                            {
+                                log::error!("failed to send the note on the request to {did}: {e}");
+                                toast.write().popup(ToastInfo::simple(
+                                    "Not possible to send your note on the friend request.",
+                                ));
+                            }
+                        }
                     }
                     MultiPassEventKind::FriendRemoved { did } => {
                         log::debug!("removing friend {}", &did);
@@ -153,9 +183,28 @@ pub fn Nav(cx: Scope<Props>) -> Element {
         },
     );
 
+    // the nav is on every page, so sent requests nobody answered in time are taken back here rather
+    // than only while the friends page is open
+    use_future(&cx, &multipass, |multipass| async move {
+        loop {
+            let days = Config::load_config_or_default().friends.request_expiry_days;
+            for request in multipass.list_outgoing_request().unwrap_or_default() {
+                if !friend_requests::expired(&request, days) {
+                    continue;
+                }
+                match multipass.clone().close_request(&request.to()) {
+                    Ok(_) => friend_requests::remove_note(&request.to()),
+                    Err(e) => log::error!("error canceling an expired friend request: {e}"),
+                }
+            }
+            tokio::time::sleep(EXPIRY_CHECK).await;
+        }
+    });
+
     cx.render(rsx! {
         div {
             class: "nav",
+            UnreadBadge {},
             Button {
                 on_pressed: move |_| {
                     router.replace_route("/main", None, None);
@@ -203,6 +252,15 @@ pub fn Nav(cx: Scope<Props>) -> Element {
                     }
                 )),
             }
+            Button {
+                on_pressed: move |_| {
+                    if let Some(locker) = locker {
+                        locker.send(LockNow);
+                    }
+                },
+                state: button::State::Secondary,
+                icon: Shape::LockClosed
+            },
             span {
                 id: "settings-cog",
                 ContextMenu {
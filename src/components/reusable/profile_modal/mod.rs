@@ -0,0 +1,321 @@
+use arboard::Clipboard;
+use dioxus::prelude::*;
+use dioxus_heroicons::outline::Shape;
+use dioxus_toast::ToastInfo;
+use ui_kit::{
+    activity_indicator::ActivityIndicator,
+    button::{self, Button},
+    popup::Popup,
+    profile_picture::PFP,
+};
+
+use crate::{
+    components::main::files::sharing,
+    iutils::{ban_list, blocked, friend_requests, get_username_from_did},
+    state::Actions,
+    Account, Messaging, PROFILE_MODAL, STATE, TOAST_MANAGER,
+};
+
+#[derive(Props)]
+pub struct Props<'a> {
+    account: Account,
+    messaging: Messaging,
+    // once the chat with them is selected
+    on_chat: EventHandler<'a, ()>,
+    // the Call button is only shown where a call can be started
+    #[props(optional)]
+    on_call: Option<EventHandler<'a, ()>>,
+}
+
+/// the profile of whoever `PROFILE_MODAL` points at, friend or not
+#[allow(non_snake_case)]
+pub fn ProfileModal<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
+    let viewed = use_atom_ref(&cx, PROFILE_MODAL);
+    let state = use_atom_ref(&cx, STATE);
+    let toast = use_atom_ref(&cx, TOAST_MANAGER);
+    let did = viewed.read().clone();
+
+    // shares are worked out from the conversations, which takes a moment
+    let shared_files = use_future(
+        &cx,
+        (&did, &cx.props.messaging.clone(), &cx.props.account.clone()),
+        |(did, messaging, account)| async move {
+            let (did, own) = match (did, account.get_own_identity()) {
+                (Some(did), Ok(own)) => (did, own.did_key()),
+                _ => return Vec::new(),
+            };
+            let shares = sharing::list(&messaging, &own).await;
+            let shared_with = |share: &sharing::Share| share.messages.iter().any(|m| m.peer == did);
+            shares
+                .outgoing
+                .iter()
+                .filter(|share| shared_with(share))
+                .map(|share| format!("{} (shared by you)", share.notice.name))
+                .chain(
+                    shares
+                        .incoming
+                        .iter()
+                        .filter(|share| shared_with(share))
+                        .map(|share| share.notice.name.clone()),
+                )
+                .collect::<Vec<_>>()
+        },
+    );
+
+    let did = did?;
+    let identity = cx
+        .props
+        .account
+        .get_identity(did.clone().into())
+        .ok()
+        .and_then(|identities| identities.first().cloned());
+    let own = cx
+        .props
+        .account
+        .get_own_identity()
+        .ok()
+        .map(|own| own.did_key());
+    let username = get_username_from_did(did.clone(), &cx.props.account);
+    let short_id = identity
+        .as_ref()
+        .map(|identity| identity.short_id().to_string())
+        .unwrap_or_default();
+    let status = identity
+        .as_ref()
+        .and_then(|identity| identity.status_message())
+        .unwrap_or_default();
+    let picture = identity
+        .as_ref()
+        .map(|identity| identity.graphics().profile_picture());
+    let banner = identity
+        .as_ref()
+        .map(|identity| identity.graphics().profile_banner())
+        .filter(|banner| !banner.is_empty());
+    let did_text = did.to_string();
+
+    let friends = cx.props.account.list_friends().unwrap_or_default();
+    let is_self = own.as_ref() == Some(&did);
+    let is_friend = friends.contains(&did);
+    let is_blocked = cx
+        .props
+        .account
+        .block_list()
+        .unwrap_or_default()
+        .contains(&did);
+    let ban = ban_list::check(&did);
+    let mut badges = Vec::new();
+    if is_self {
+        badges.push(("You", "self"));
+    }
+    if is_friend {
+        badges.push(("Friend", "friend"));
+    }
+    if is_blocked {
+        badges.push(("Blocked", "danger"));
+    }
+    if ban.is_some() {
+        badges.push(("Banned", "danger"));
+    }
+    let mutual: Vec<_> = friend_requests::mutual_friends(&state.read(), &friends, &did)
+        .into_iter()
+        .map(|friend| get_username_from_did(friend, &cx.props.account))
+        .collect();
+    let mutual_text = mutual.join(", ");
+
+    let close = move || *viewed.write() = None;
+    // selects the direct conversation with them, starting one when there's none yet
+    let chat = move |call: bool| {
+        let did = match viewed.read().clone() {
+            Some(did) => did,
+            None => return,
+        };
+        let created = warp::async_block_in_place_uncheck(
+            cx.props.messaging.clone().create_conversation(&did),
+        );
+        let conversation = match created {
+            Ok(conversation) => conversation,
+            Err(warp::error::Error::ConversationExist { conversation }) => conversation,
+            Err(e) => {
+                log::error!("failed to chat with {}: {}", did, e);
+                toast
+                    .write()
+                    .popup(ToastInfo::simple("Not possible to start a chat with them."));
+                return;
+            }
+        };
+        state.write().dispatch(Actions::ChatWith(conversation));
+        close();
+        match (call, &cx.props.on_call) {
+            (true, Some(on_call)) => on_call.call(()),
+            _ => cx.props.on_chat.call(()),
+        }
+    };
+    let toggle_block = move |block: bool| {
+        let did = match viewed.read().clone() {
+            Some(did) => did,
+            None => return,
+        };
+        let (result, done, failed) = match block {
+            true => (
+                blocked::block(&cx.props.account, &did, ""),
+                "Blocked.",
+                "Not possible to block them.",
+            ),
+            false => (
+                blocked::unblock(&cx.props.account, &did),
+                "Unblocked.",
+                "Not possible to unblock them.",
+            ),
+        };
+        let message = match result {
+            Ok(_) => done,
+            Err(e) => {
+                log::error!("failed to change whether {} is blocked: {}", did, e);
+                failed
+            }
+        };
+        toast.write().popup(ToastInfo::simple(message));
+        cx.needs_update();
+    };
+    let copy_did = move || {
+        let copied = Clipboard::new().and_then(|mut clipboard| {
+            clipboard.set_text(
+                viewed
+                    .read()
+                    .as_ref()
+                    .map(|did| did.to_string())
+                    .unwrap_or_default(),
+            )
+        });
+        let message = match copied {
+            Ok(_) => "DID copied!",
+            Err(e) => {
+                log::error!("failed to copy the DID: {e}");
+                "Not possible to copy the DID."
+            }
+        };
+        toast.write().popup(ToastInfo::simple(message));
+    };
+    let (block_icon, block_text) = match is_blocked {
+        true => (Shape::LockOpen, "Unblock"),
+        false => (Shape::NoSymbol, "Block"),
+    };
+
+    cx.render(rsx!(
+        Popup {
+            hidden: false,
+            on_dismiss: move |_| close(),
+            children: cx.render(rsx!(
+                div {
+                    class: "profile-modal",
+                    div {
+                        class: "banner",
+                        banner.map(|banner| rsx!(
+                            img {
+                                src: "{banner}",
+                            }
+                        ))
+                    },
+                    div {
+                        class: "profile-picture",
+                        PFP {
+                            src: picture,
+                            size: ui_kit::profile_picture::Size::Large
+                        },
+                    },
+                    h2 {
+                        class: "username",
+                        "{username}",
+                        (!short_id.is_empty()).then(|| rsx!(
+                            span {
+                                class: "short-id",
+                                "#{short_id}"
+                            }
+                        ))
+                    },
+                    ActivityIndicator {
+                        inline: true,
+                        remote_did: did.clone(),
+                        account: cx.props.account.clone(),
+                    },
+                    (!badges.is_empty()).then(|| rsx!(
+                        div {
+                            class: "badges",
+                            badges.iter().map(|(badge, kind)| rsx!(
+                                span {
+                                    key: "{badge}",
+                                    class: "badge {kind}",
+                                    "{badge}"
+                                }
+                            ))
+                        }
+                    )),
+                    (!status.is_empty()).then(|| rsx!(
+                        p {
+                            class: "status",
+                            "{status}"
+                        }
+                    )),
+                    p {
+                        class: "did",
+                        "{did_text}"
+                    },
+                    (!mutual.is_empty()).then(|| rsx!(
+                        div {
+                            class: "section",
+                            label { "Mutual Friends" },
+                            p { "{mutual_text}" }
+                        }
+                    )),
+                    div {
+                        class: "section",
+                        label { "Shared Files" },
+                        match shared_files.value() {
+                            None => rsx!(p { class: "muted", "Looking for shared files…" }),
+                            Some(files) if files.is_empty() => rsx!(p { class: "muted", "Nothing shared yet." }),
+                            Some(files) => rsx!(
+                                ul {
+                                    files.iter().map(|name| rsx!(
+                                        li { "{name}" }
+                                    ))
+                                }
+                            ),
+                        }
+                    },
+                    (!is_self).then(|| rsx!(
+                        div {
+                            class: "profile-actions",
+                            Button {
+                                icon: Shape::ChatBubbleBottomCenterText,
+                                text: String::from("Message"),
+                                disabled: is_blocked,
+                                on_pressed: move |_| chat(false),
+                            },
+                            cx.props.on_call.is_some().then(|| rsx!(
+                                Button {
+                                    icon: Shape::Phone,
+                                    text: String::from("Call"),
+                                    state: button::State::Secondary,
+                                    disabled: is_blocked,
+                                    on_pressed: move |_| chat(true),
+                                }
+                            )),
+                            Button {
+                                icon: block_icon,
+                                text: String::from(block_text),
+                                state: button::State::Danger,
+                                on_pressed: move |_| toggle_block(!is_blocked),
+                            },
+                            Button {
+                                icon: Shape::ClipboardDocument,
+                                text: String::from("Copy DID"),
+                                state: button::State::Secondary,
+                                on_pressed: move |_| copy_did(),
+                            },
+                        }
+                    )),
+                }
+            )),
+        }
+    ))
+}
@@ -0,0 +1,156 @@
+use dioxus::{events::FormEvent, prelude::*};
+use dioxus_heroicons::outline::Shape;
+use dioxus_toast::ToastInfo;
+use ui_kit::{
+    button::{self, Button},
+    input::Input,
+};
+
+use crate::{
+    iutils::profiles::{self, Profile},
+    ProfileAction, TOAST_MANAGER,
+};
+
+#[derive(Clone, PartialEq)]
+enum Editing {
+    None,
+    Renaming(String),
+    // deleting takes a second press
+    Deleting(String),
+}
+
+/// lists the profiles on this device and lets them be created, switched to, renamed and deleted
+#[allow(non_snake_case)]
+pub fn ProfileManager(cx: Scope) -> Element {
+    let list = use_state(&cx, profiles::list);
+    let active = use_state(&cx, profiles::active);
+    let editing = use_state(&cx, || Editing::None);
+    let name = use_state(&cx, String::new);
+    let new_name = use_state(&cx, String::new);
+    let toast = use_atom_ref(&cx, TOAST_MANAGER);
+    let switcher = use_coroutine_handle::<ProfileAction>(&cx);
+
+    let refresh = move || {
+        list.set(profiles::list());
+        active.set(profiles::active());
+    };
+    let report = move |result: std::io::Result<()>| match result {
+        Ok(()) => refresh(),
+        Err(e) => toast.write().popup(ToastInfo::simple(&e.to_string())),
+    };
+    let create = move || {
+        report(profiles::create(new_name.get()).map(|_| ()));
+        new_name.set(String::new());
+    };
+
+    cx.render(rsx! {
+        div {
+            class: "profile-manager",
+            list.iter().map(|profile| {
+                let Profile { id, name: profile_name, .. } = profile.clone();
+                let is_active = id == active.id;
+                let active_class = if is_active { "active" } else { "" };
+                let row = match editing.get() {
+                    Editing::Renaming(editing_id) if *editing_id == id => rsx!(
+                        Input {
+                            placeholder: String::from("Profile name"),
+                            value: name.to_string(),
+                            on_change: move |evt: FormEvent| name.set(evt.value.clone()),
+                            on_enter: move |_| {
+                                report(profiles::rename(&id, name.get()));
+                                editing.set(Editing::None);
+                            },
+                        }
+                    ),
+                    Editing::Deleting(editing_id) if *editing_id == id => rsx!(
+                        span {
+                            class: "name",
+                            "Delete {profile_name} and everything in it?"
+                        },
+                        Button {
+                            icon: Shape::Trash,
+                            text: String::from("Delete"),
+                            state: button::State::Danger,
+                            on_pressed: move |_| {
+                                report(profiles::delete(&id));
+                                editing.set(Editing::None);
+                            },
+                        },
+                        Button {
+                            icon: Shape::XMark,
+                            state: button::State::Secondary,
+                            on_pressed: move |_| editing.set(Editing::None),
+                        },
+                    ),
+                    _ => {
+                        let rename_id = id.clone();
+                        let rename_name = profile_name.clone();
+                        let delete_id = id.clone();
+                        rsx!(
+                            span {
+                                class: "name",
+                                "{profile_name}"
+                            },
+                            is_active.then(|| rsx!(
+                                span {
+                                    class: "badge",
+                                    "In use"
+                                }
+                            )),
+                            (!is_active).then(|| rsx!(
+                                Button {
+                                    icon: Shape::ArrowRight,
+                                    text: String::from("Switch"),
+                                    state: button::State::Secondary,
+                                    on_pressed: move |_| {
+                                        if let Some(switcher) = switcher {
+                                            switcher.send(ProfileAction::Switch(id.clone()));
+                                        }
+                                    },
+                                }
+                            )),
+                            Button {
+                                icon: Shape::PencilSquare,
+                                state: button::State::Secondary,
+                                on_pressed: move |_| {
+                                    name.set(rename_name.clone());
+                                    editing.set(Editing::Renaming(rename_id.clone()));
+                                },
+                            },
+                            (!is_active && !profile.is_root()).then(|| rsx!(
+                                Button {
+                                    icon: Shape::Trash,
+                                    state: button::State::Secondary,
+                                    on_pressed: move |_| editing.set(Editing::Deleting(delete_id.clone())),
+                                }
+                            )),
+                        )
+                    }
+                };
+                rsx!(
+                    div {
+                        key: "{profile.id}",
+                        class: "profile {active_class}",
+                        row
+                    }
+                )
+            }),
+            div {
+                class: "new-profile",
+                Input {
+                    icon: Shape::Plus,
+                    placeholder: String::from("New profile name"),
+                    value: new_name.to_string(),
+                    on_change: move |evt: FormEvent| new_name.set(evt.value.clone()),
+                    on_enter: move |_| create(),
+                },
+                Button {
+                    icon: Shape::Plus,
+                    text: String::from("Create"),
+                    disabled: new_name.trim().is_empty(),
+                    on_pressed: move |_| create(),
+                },
+            }
+        }
+    })
+}
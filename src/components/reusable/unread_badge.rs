@@ -0,0 +1,41 @@
+use dioxus::desktop::use_window;
+use dioxus::prelude::*;
+use state::STATE;
+use utils::notifications::set_badge;
+
+use crate::DEFAULT_WINDOW_NAME;
+
+// keeps the dock/launcher badge and the window title in sync with the unread count.
+// renders nothing; it only needs to be mounted somewhere that lives for as long as the main pages do.
+#[allow(non_snake_case)]
+pub fn UnreadBadge(cx: Scope) -> Element {
+    let state = use_atom_ref(&cx, STATE);
+    let window = use_window(&cx);
+    let count = state.read().total_unreads;
+    let base_title = DEFAULT_WINDOW_NAME.read().clone();
+    // (unread count, title) that was last applied
+    let applied = use_ref(&cx, || None::<(u32, String)>);
+
+    let current = Some((count, base_title.clone()));
+    if *applied.read() != current {
+        *applied.write_silent() = current;
+        let title = match set_badge(count) {
+            Ok(true) => base_title,
+            Ok(false) => title_with_unreads(&base_title, count),
+            Err(e) => {
+                log::debug!("falling back to the window title for the badge: {}", e);
+                title_with_unreads(&base_title, count)
+            }
+        };
+        window.set_title(&title);
+    }
+
+    None
+}
+
+pub fn title_with_unreads(title: &str, count: u32) -> String {
+    match count {
+        0 => title.to_string(),
+        _ => format!("({}) {}", count, title),
+    }
+}
@@ -0,0 +1,254 @@
+// Accounts nobody should have to hear from. The Satellite Global Ban List (SGBL) ships with the app, and
+// each profile keeps a list of its own next to it. The user can trust an account from the global list
+// anyway, which is kept in the profile's list as well. Friend requests, messages and search results from
+// anyone banned are held back.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use warp::{crypto::DID, sync::RwLock};
+
+use crate::DEFAULT_PATH;
+
+const SGBL: &str = include_str!("../../SGBL.toml");
+const GLOBAL_REASON: &str = "Listed in the Satellite Global Ban List";
+
+#[derive(Deserialize, Default)]
+struct Global {
+    #[serde(default)]
+    users: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+struct LocalEntry {
+    reason: String,
+    added: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+struct Local {
+    // entries of the global list the user trusts anyway. Comes first, since TOML wants plain values before
+    // tables.
+    allowed: BTreeSet<String>,
+    users: BTreeMap<String, LocalEntry>,
+}
+
+struct Lists {
+    // the profile these were read for
+    path: PathBuf,
+    global: BTreeMap<String, String>,
+    local: Local,
+}
+
+static LISTS: Lazy<RwLock<Option<Lists>>> = Lazy::new(|| RwLock::new(None));
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Source {
+    Global,
+    Local,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Ban {
+    pub did: String,
+    pub reason: String,
+    pub source: Source,
+    // only known for the profile's own entries
+    pub added: Option<DateTime<Utc>>,
+    // on the global list but trusted by the user
+    pub allowed: bool,
+}
+
+fn local_path() -> PathBuf {
+    DEFAULT_PATH.read().join("ban_list.toml")
+}
+
+// an entry is either the reason on its own or a table with a reason in it
+fn global_entries() -> BTreeMap<String, String> {
+    let global: Global = toml::from_str(SGBL).unwrap_or_else(|e| {
+        log::error!("The Satellite Global Ban List is malformed: {e}");
+        Global::default()
+    });
+    global
+        .users
+        .into_iter()
+        .map(|(did, value)| {
+            let reason = match &value {
+                toml::Value::String(reason) => Some(reason.as_str()),
+                toml::Value::Table(table) => table.get("reason").and_then(|r| r.as_str()),
+                _ => None,
+            };
+            (did, reason.unwrap_or(GLOBAL_REASON).to_string())
+        })
+        .collect()
+}
+
+fn load_local() -> Local {
+    fs::read_to_string(local_path())
+        .ok()
+        .and_then(|contents| toml::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+// the lists are read once for each profile, since they're looked at for every message
+fn with_lists<T>(read: impl FnOnce(&Lists) -> T) -> T {
+    let path = DEFAULT_PATH.read().clone();
+    let mut lists = LISTS.write();
+    if lists.as_ref().map(|lists| &lists.path) != Some(&path) {
+        *lists = Some(Lists {
+            path,
+            global: global_entries(),
+            local: load_local(),
+        });
+    }
+    read(lists.as_ref().expect("the lists were just read"))
+}
+
+fn update_local(change: impl FnOnce(&mut Local)) -> io::Result<()> {
+    let mut local = with_lists(|lists| lists.local.clone());
+    change(&mut local);
+    let contents = toml::to_string(&local).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    fs::write(local_path(), contents)?;
+    if let Some(lists) = LISTS.write().as_mut() {
+        lists.local = local;
+    }
+    Ok(())
+}
+
+fn find(lists: &Lists, did: &str) -> Option<Ban> {
+    if let Some(entry) = lists.local.users.get(did) {
+        return Some(Ban {
+            did: did.to_string(),
+            reason: entry.reason.clone(),
+            source: Source::Local,
+            added: Some(entry.added),
+            allowed: false,
+        });
+    }
+    lists.global.get(did).map(|reason| Ban {
+        did: did.to_string(),
+        reason: reason.clone(),
+        source: Source::Global,
+        added: None,
+        allowed: lists.local.allowed.contains(did),
+    })
+}
+
+/// why `did` is banned, unless it isn't or the user trusts it anyway
+pub fn check(did: &DID) -> Option<Ban> {
+    with_lists(|lists| find(lists, &did.to_string())).filter(|ban| !ban.allowed)
+}
+
+pub fn is_banned(did: &DID) -> bool {
+    check(did).is_some()
+}
+
+/// every entry of both lists, trusted ones included, the profile's own first
+pub fn entries() -> Vec<Ban> {
+    with_lists(|lists| {
+        lists
+            .local
+            .users
+            .keys()
+            .chain(
+                lists
+                    .global
+                    .keys()
+                    .filter(|did| !lists.local.users.contains_key(*did)),
+            )
+            .filter_map(|did| find(lists, did))
+            .collect()
+    })
+}
+
+pub fn ban(did: &DID, reason: &str) -> io::Result<()> {
+    let entry = LocalEntry {
+        reason: reason.trim().to_string(),
+        added: Utc::now(),
+    };
+    update_local(|local| {
+        local.users.insert(did.to_string(), entry);
+    })
+}
+
+/// takes an account off the profile's own list
+pub fn unban(did: &str) -> io::Result<()> {
+    update_local(|local| {
+        local.users.remove(did);
+    })
+}
+
+/// trusts an account on the global list anyway, or stops doing so
+pub fn set_allowed(did: &str, allowed: bool) -> io::Result<()> {
+    update_local(|local| match allowed {
+        true => {
+            local.allowed.insert(did.to_string());
+        }
+        false => {
+            local.allowed.remove(did);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lists() -> Lists {
+        let mut local = Local::default();
+        local.allowed.insert(String::from("did:key:trusted"));
+        local.allowed.insert(String::from("did:key:both"));
+        for did in ["did:key:local", "did:key:both"] {
+            local.users.insert(
+                did.to_string(),
+                LocalEntry {
+                    reason: String::from("spam"),
+                    added: Utc::now(),
+                },
+            );
+        }
+        Lists {
+            path: PathBuf::new(),
+            global: ["did:key:global", "did:key:trusted", "did:key:both"]
+                .into_iter()
+                .map(|did| (did.to_string(), String::from(GLOBAL_REASON)))
+                .collect(),
+            local,
+        }
+    }
+
+    #[test]
+    fn global_entries_can_be_trusted() {
+        let lists = lists();
+        let banned = find(&lists, "did:key:global").unwrap();
+        assert_eq!(banned.source, Source::Global);
+        assert!(!banned.allowed);
+        let trusted = find(&lists, "did:key:trusted").unwrap();
+        assert_eq!(trusted.source, Source::Global);
+        assert!(trusted.allowed);
+    }
+
+    #[test]
+    fn own_entries_come_first_and_cant_be_trusted() {
+        let lists = lists();
+        for did in ["did:key:local", "did:key:both"] {
+            let ban = find(&lists, did).unwrap();
+            assert_eq!(ban.source, Source::Local);
+            assert_eq!(ban.reason, "spam");
+            assert!(ban.added.is_some());
+            assert!(!ban.allowed);
+        }
+    }
+
+    #[test]
+    fn others_arent_banned() {
+        assert_eq!(find(&lists(), "did:key:someone"), None);
+    }
+}
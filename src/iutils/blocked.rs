@@ -0,0 +1,61 @@
+// MultiPass keeps track of who is blocked. When they were blocked and why is only kept on this device,
+// in a file next to the profile's other data.
+
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use warp::crypto::DID;
+
+use crate::{Account, DEFAULT_PATH};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct Record {
+    pub since: DateTime<Utc>,
+    pub reason: String,
+}
+
+fn records_path() -> PathBuf {
+    DEFAULT_PATH.read().join("blocked.json")
+}
+
+/// what's known about each blocked account, by DID
+pub fn records() -> BTreeMap<String, Record> {
+    fs::read(records_path())
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save_records(records: &BTreeMap<String, Record>) -> io::Result<()> {
+    let bytes = serde_json::to_vec_pretty(records)?;
+    fs::write(records_path(), bytes)
+}
+
+/// blocks `did`, friend or not, and notes down when and why
+pub fn block(account: &Account, did: &DID, reason: &str) -> Result<(), warp::error::Error> {
+    account.clone().block(did)?;
+    let mut all = records();
+    all.insert(
+        did.to_string(),
+        Record {
+            since: Utc::now(),
+            reason: reason.trim().to_string(),
+        },
+    );
+    if let Err(e) = save_records(&all) {
+        log::error!("failed to save the reason for blocking {did}: {e}");
+    }
+    Ok(())
+}
+
+pub fn unblock(account: &Account, did: &DID) -> Result<(), warp::error::Error> {
+    account.clone().unblock(did)?;
+    let mut all = records();
+    if all.remove(&did.to_string()).is_some() {
+        if let Err(e) = save_records(&all) {
+            log::error!("failed to forget why {did} was blocked: {e}");
+        }
+    }
+    Ok(())
+}
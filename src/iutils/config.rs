@@ -1,7 +1,12 @@
 use std::fs;
 use std::io::{Error, Write};
+use std::path::PathBuf;
 
 use crate::DEFAULT_PATH;
+use ::utils::{
+    at_rest,
+    sounds::{self, SoundSettings, Sounds},
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -9,14 +14,42 @@ pub struct Config {
     pub general: General,
     pub privacy: Privacy,
     pub audiovideo: AudioVideo,
+    #[serde(default)]
+    pub notifications: Notifications,
     pub extensions: Extensions,
     pub developer: Developer,
+    #[serde(default)]
+    pub storage: StorageQuota,
+    #[serde(default)]
+    pub attachments: Attachments,
+    #[serde(default)]
+    pub friends: Friends,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Privacy {
     pub satellite_sync_nodes: bool,
     pub safer_file_scanning: bool,
+    #[serde(default)]
+    pub link_previews: LinkPreviewPolicy,
+    // the app locks itself after this many minutes without any input. 0 means it never does.
+    #[serde(default = "default_auto_lock_minutes")]
+    pub auto_lock_minutes: u32,
+}
+
+fn default_auto_lock_minutes() -> u32 {
+    15
+}
+
+// which links in messages are fetched to show a preview. fetching a link reveals the user's IP address
+// to whoever hosts it.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkPreviewPolicy {
+    Disabled,
+    OwnLinks,
+    #[default]
+    All,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -31,6 +64,17 @@ pub struct AudioVideo {
     pub call_timer: bool,
 }
 
+// an empty sound path means the built in sound is used
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Notifications {
+    pub sounds: bool,
+    pub volume: f32,
+    pub message_sound: String,
+    pub friend_request_sound: String,
+    pub call_sound: String,
+    pub mention_sound: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Copy, Clone)]
 pub struct Extensions {
     pub enable: bool,
@@ -42,6 +86,119 @@ pub struct Developer {
     pub cache_dir: String,
 }
 
+// a quota of 0 bytes means there's none and Constellation may fill the disk
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
+pub struct StorageQuota {
+    pub quota: u64,
+    pub warn_at_percent: u8,
+}
+
+// images in chat up to this size are fetched as soon as they're shown. 0 means they never are.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
+pub struct Attachments {
+    pub auto_download_limit: u64,
+}
+
+// sent friend requests nobody answered are taken back after this many days. 0 means they never are.
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
+pub struct Friends {
+    pub request_expiry_days: u32,
+}
+
+impl Default for Friends {
+    fn default() -> Self {
+        Self {
+            request_expiry_days: 30,
+        }
+    }
+}
+
+impl Default for Attachments {
+    fn default() -> Self {
+        Self {
+            auto_download_limit: 10 * 1000 * 1000,
+        }
+    }
+}
+
+impl Default for StorageQuota {
+    fn default() -> Self {
+        Self {
+            quota: 0,
+            warn_at_percent: 90,
+        }
+    }
+}
+
+impl LinkPreviewPolicy {
+    pub const ALL: [LinkPreviewPolicy; 3] = [Self::All, Self::OwnLinks, Self::Disabled];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::All => "All links",
+            Self::OwnLinks => "Only links I send",
+            Self::Disabled => "Off",
+        }
+    }
+
+    pub fn allows(&self, remote: bool) -> bool {
+        match self {
+            Self::All => true,
+            Self::OwnLinks => !remote,
+            Self::Disabled => false,
+        }
+    }
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            sounds: true,
+            volume: 1.0,
+            message_sound: String::new(),
+            friend_request_sound: String::new(),
+            call_sound: String::new(),
+            mention_sound: String::new(),
+        }
+    }
+}
+
+impl Notifications {
+    pub fn sound_settings(&self) -> SoundSettings {
+        let files = [
+            (Sounds::Notification, &self.message_sound),
+            (Sounds::FriendReq, &self.friend_request_sound),
+            (Sounds::Call, &self.call_sound),
+            (Sounds::Mention, &self.mention_sound),
+        ]
+        .into_iter()
+        .filter(|(_, path)| !path.is_empty())
+        .map(|(sound, path)| (sound, PathBuf::from(path)))
+        .collect();
+
+        SoundSettings {
+            enabled: self.sounds,
+            volume: self.volume,
+            files,
+        }
+    }
+
+    pub fn sound_path_mut(&mut self, sound: Sounds) -> Option<&mut String> {
+        match sound {
+            Sounds::Notification => Some(&mut self.message_sound),
+            Sounds::FriendReq => Some(&mut self.friend_request_sound),
+            Sounds::Call => Some(&mut self.call_sound),
+            Sounds::Mention => Some(&mut self.mention_sound),
+            Sounds::General => None,
+        }
+    }
+
+    // pushes the current settings to the audio service
+    pub fn apply(&self) {
+        sounds::configure(self.sound_settings());
+    }
+}
+
 // Implementation to create, load and save the config
 impl Config {
     fn default() -> Self {
@@ -53,16 +210,22 @@ impl Config {
             privacy: Privacy {
                 satellite_sync_nodes: true,
                 safer_file_scanning: true,
+                link_previews: LinkPreviewPolicy::default(),
+                auto_lock_minutes: default_auto_lock_minutes(),
             },
             extensions: Extensions { enable: true },
             audiovideo: AudioVideo {
                 noise_suppression: false,
                 call_timer: false,
             },
+            notifications: Notifications::default(),
             developer: Developer {
                 developer_mode: false,
                 cache_dir: String::from(".warp"),
             },
+            storage: StorageQuota::default(),
+            attachments: Attachments::default(),
+            friends: Friends::default(),
         }
     }
 
@@ -76,7 +239,7 @@ impl Config {
     pub fn load_config_or_default() -> Config {
         let binding = DEFAULT_PATH.read().join("Config.toml");
         let config_location = binding.to_str().unwrap();
-        let contents = match fs::read_to_string(config_location) {
+        let contents = match at_rest::read_to_string(&binding) {
             // If successful return the files text as `contents`.
             // `c` is a local variable.
             Ok(c) => c,
@@ -107,11 +270,9 @@ impl Config {
     }
 
     pub fn save(&self) -> Result<(), Error> {
-        let mut file = std::fs::OpenOptions::new()
-            .write(true)
-            .create(true)
-            .open(DEFAULT_PATH.read().join("Config.toml"))?;
-        self.save_to_writer(&mut file)
+        let mut bytes = Vec::new();
+        self.save_to_writer(&mut bytes)?;
+        at_rest::write(&DEFAULT_PATH.read().join("Config.toml"), &bytes)
     }
 
     pub fn save_to_writer<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
@@ -0,0 +1,108 @@
+// What's kept about friend requests besides what MultiPass has: the note the user left on the ones
+// they sent, and how incoming requests can be ordered. A request can't carry anything but the two DIDs,
+// so the note stays on this device as a reminder of who the request went to and why, and is sent to them
+// as a chat message once they've accepted.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs, io,
+    path::PathBuf,
+};
+
+use chrono::{Duration, Utc};
+use warp::{crypto::DID, multipass::identity::FriendRequest};
+
+use crate::{state::PersistedState, Messaging, DEFAULT_PATH};
+
+pub const NOTE_LENGTH: usize = 140;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Order {
+    Newest,
+    Oldest,
+    Name,
+}
+
+impl Order {
+    pub const ALL: [Order; 3] = [Self::Newest, Self::Oldest, Self::Name];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Newest => "Newest first",
+            Self::Oldest => "Oldest first",
+            Self::Name => "By name",
+        }
+    }
+}
+
+fn notes_path() -> PathBuf {
+    DEFAULT_PATH.read().join("request_notes.json")
+}
+
+/// the notes left on sent requests, by the DID they went to
+pub fn notes() -> BTreeMap<String, String> {
+    fs::read(notes_path())
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save_notes(notes: &BTreeMap<String, String>) -> io::Result<()> {
+    let bytes = serde_json::to_vec_pretty(notes)?;
+    fs::write(notes_path(), bytes)
+}
+
+pub fn set_note(to: &DID, note: &str) -> io::Result<()> {
+    let note: String = note.trim().chars().take(NOTE_LENGTH).collect();
+    let mut all = notes();
+    match note.is_empty() {
+        true => all.remove(&to.to_string()),
+        false => all.insert(to.to_string(), note),
+    };
+    save_notes(&all)
+}
+
+/// sends the note on an accepted request to the new friend, in the direct conversation with them
+pub async fn send_note(
+    mut messaging: Messaging,
+    to: DID,
+    note: String,
+) -> Result<(), warp::error::Error> {
+    let note: String = note.trim().chars().take(NOTE_LENGTH).collect();
+    if note.is_empty() {
+        return Ok(());
+    }
+    let conversation = match messaging.create_conversation(&to).await {
+        Ok(conversation) => conversation,
+        Err(warp::error::Error::ConversationExist { conversation }) => conversation,
+        Err(e) => return Err(e),
+    };
+    messaging.send(conversation.id(), None, vec![note]).await
+}
+
+pub fn remove_note(to: &DID) {
+    let mut all = notes();
+    if all.remove(&to.to_string()).is_some() {
+        if let Err(e) = save_notes(&all) {
+            log::error!("failed to remove the note on the request to {to}: {e}");
+        }
+    }
+}
+
+/// whether a sent request has waited longer than `days`, 0 meaning it never expires
+pub fn expired(request: &FriendRequest, days: u32) -> bool {
+    days > 0 && Utc::now() - request.date() > Duration::days(days.into())
+}
+
+/// friends who are in a group chat with `did` as well. Nothing more is known about whom they know.
+pub fn mutual_friends(state: &PersistedState, friends: &[DID], did: &DID) -> HashSet<DID> {
+    state
+        .all_chats
+        .values()
+        .chain(state.active_chats.values())
+        .map(|info| info.conversation.recipients())
+        .filter(|recipients| recipients.contains(did))
+        .flat_map(|recipients| recipients.into_iter())
+        .filter(|recipient| recipient != did && friends.contains(recipient))
+        .collect()
+}
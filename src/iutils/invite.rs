@@ -0,0 +1,122 @@
+// Ways to hand someone a friend code: as a QR code, or as an `uplink://add/<code>` link that opens the
+// app on the Friends page, ready to send the request. A QR code can be read back from an image, too.
+
+use std::path::Path;
+
+use once_cell::sync::Lazy;
+use qrcode::{render::svg, QrCode};
+use warp::{crypto::DID, sync::RwLock};
+
+use crate::iutils::parse_did;
+
+const SCHEME: &str = "uplink://add/";
+
+// an invite the app was launched with, until the Friends page picks it up
+static PENDING: Lazy<RwLock<Option<DID>>> = Lazy::new(|| RwLock::new(None));
+
+/// the DID without its `did:key:` prefix, which is what users share
+pub fn friend_code(did: &DID) -> String {
+    did.to_string().replace("did:key:", "")
+}
+
+pub fn link(did: &DID) -> String {
+    format!("{SCHEME}{}", friend_code(did))
+}
+
+/// accepts an invite link as well as a friend code or a full DID
+pub fn parse(input: &str) -> Option<DID> {
+    let input = input.trim();
+    parse_did(
+        input
+            .strip_prefix(SCHEME)
+            .unwrap_or(input)
+            .trim_end_matches('/'),
+    )
+}
+
+/// `text` as a QR code, ready to be used as the source of an image
+pub fn qr_code(text: &str) -> Option<String> {
+    let code = QrCode::new(text.as_bytes())
+        .map_err(|e| log::error!("failed to make a QR code: {e}"))
+        .ok()?;
+    let image = code
+        .render::<svg::Color>()
+        .min_dimensions(200, 200)
+        .quiet_zone(true)
+        .build();
+    Some(format!(
+        "data:image/svg+xml;base64,{}",
+        base64::encode(image)
+    ))
+}
+
+/// reads the friend code or invite link out of a picture of a QR code
+pub fn decode_qr_code(path: &Path) -> Result<DID, String> {
+    let image = image::open(path)
+        .map_err(|e| format!("Not possible to open that image: {e}"))?
+        .to_luma8();
+    let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(
+        image.width() as _,
+        image.height() as _,
+        |x, y| image.get_pixel(x as _, y as _).0[0],
+    );
+    prepared
+        .detect_grids()
+        .iter()
+        .filter_map(|grid| grid.decode().ok())
+        .find_map(|(_, content)| parse(&content))
+        .ok_or_else(|| String::from("No friend code was found in that image."))
+}
+
+pub fn set_pending(did: DID) {
+    *PENDING.write() = Some(did);
+}
+
+pub fn has_pending() -> bool {
+    PENDING.read().is_some()
+}
+
+pub fn take_pending() -> Option<DID> {
+    PENDING.write().take()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE: &str = "z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK";
+
+    #[test]
+    fn reads_codes_dids_and_links() {
+        let did = parse_did(CODE).expect("a valid DID");
+        for input in [
+            CODE.to_string(),
+            format!("did:key:{CODE}"),
+            format!("uplink://add/{CODE}"),
+            format!("uplink://add/{CODE}/"),
+            format!("  {CODE}\n"),
+        ] {
+            assert_eq!(parse(&input), Some(did.clone()), "{input}");
+        }
+    }
+
+    #[test]
+    fn links_read_back() {
+        let did = parse_did(CODE).expect("a valid DID");
+        assert_eq!(link(&did), format!("uplink://add/{CODE}"));
+        assert_eq!(parse(&link(&did)), Some(did));
+    }
+
+    #[test]
+    fn rejects_anything_else() {
+        for input in [
+            "",
+            "uplink://add/",
+            "uplink://add/not-a-code",
+            "https://example.com/add/z6Mk",
+            "hello",
+        ] {
+            assert_eq!(parse(input), None, "{input}");
+        }
+    }
+}
@@ -0,0 +1,320 @@
+// How the keystore of a profile is opened and what wrong guesses lead to. After a few of them every
+// further guess has to wait twice as long as the one before, and the profile can be set to be erased
+// after too many in a row. This is kept next to the keystore in a file of its own, since it's read
+// before anything is unlocked.
+
+use std::{
+    fs, io,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use warp::tesseract::Tesseract;
+
+use crate::{iutils::recovery, DEFAULT_PATH};
+
+pub const PIN_LENGTH: RangeInclusive<usize> = 4..=6;
+pub const PASSPHRASE_LENGTH: RangeInclusive<usize> = 8..=128;
+// wrong guesses let through before any waiting
+const FREE_ATTEMPTS: u32 = 3;
+const MAX_WAIT: Duration = Duration::from_secs(15 * 60);
+// kept when a profile is erased, since the root folder holds the other profiles
+const KEPT_ON_WIPE: [&str; 2] = ["profiles", "profiles.json"];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Secret {
+    #[default]
+    Pin,
+    Passphrase,
+}
+
+impl Secret {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Pin => "PIN",
+            Self::Passphrase => "Passphrase",
+        }
+    }
+
+    pub fn max_length(&self) -> usize {
+        match self {
+            Self::Pin => *PIN_LENGTH.end(),
+            Self::Passphrase => *PASSPHRASE_LENGTH.end(),
+        }
+    }
+
+    /// whether `secret` is strong enough to lock a new keystore with
+    pub fn validate(&self, secret: &str) -> Result<(), String> {
+        let (range, label) = match self {
+            Self::Pin => (PIN_LENGTH, "A PIN"),
+            Self::Passphrase => (PASSPHRASE_LENGTH, "A passphrase"),
+        };
+        if !range.contains(&secret.chars().count()) {
+            return Err(format!(
+                "{label} is {} to {} characters long.",
+                range.start(),
+                range.end()
+            ));
+        }
+        if *self == Self::Passphrase && secret.trim().is_empty() {
+            return Err(String::from("A passphrase can't be only spaces."));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    Retry,
+    // too many wrong guesses, the profile is to be erased
+    Wipe,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Default)]
+#[serde(default)]
+pub struct Lockout {
+    pub secret: Secret,
+    // the profile is erased after this many wrong guesses in a row, 0 never
+    pub wipe_after: u32,
+    pub failed_attempts: u32,
+    pub last_failure: Option<DateTime<Utc>>,
+}
+
+impl Lockout {
+    fn path() -> PathBuf {
+        DEFAULT_PATH.read().join(".lockout.json")
+    }
+
+    pub fn load() -> Self {
+        fs::read(Self::path())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let bytes = serde_json::to_vec_pretty(self)?;
+        fs::write(Self::path(), bytes)
+    }
+
+    // 1s after the free guesses are used up, doubling with each one after that
+    fn wait(&self) -> Duration {
+        match self.failed_attempts.checked_sub(FREE_ATTEMPTS) {
+            None => Duration::ZERO,
+            Some(extra) => Duration::from_secs(1u64 << extra.min(16)).min(MAX_WAIT),
+        }
+    }
+
+    /// how long until the next guess is taken, if it has to wait
+    pub fn wait_remaining(&self) -> Option<Duration> {
+        let last_failure = self.last_failure?;
+        let wait = chrono::Duration::from_std(self.wait()).ok()?;
+        (last_failure + wait - Utc::now())
+            .to_std()
+            .ok()
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// wrong guesses left before the profile is erased, when that's turned on
+    pub fn attempts_left(&self) -> Option<u32> {
+        (self.wipe_after > 0).then(|| self.wipe_after.saturating_sub(self.failed_attempts))
+    }
+
+    pub fn record_failure(&mut self) -> Outcome {
+        self.failed_attempts += 1;
+        self.last_failure = Some(Utc::now());
+        match self.attempts_left() {
+            Some(0) => Outcome::Wipe,
+            _ => Outcome::Retry,
+        }
+    }
+
+    pub fn record_success(&mut self) {
+        self.failed_attempts = 0;
+        self.last_failure = None;
+    }
+
+    fn update(change: impl FnOnce(&mut Self) -> Outcome) -> Outcome {
+        let mut lockout = Self::load();
+        let outcome = change(&mut lockout);
+        if let Err(e) = lockout.save() {
+            log::error!("Not possible to save failed attempts: {e}");
+        }
+        outcome
+    }
+}
+
+pub fn wait_message(wait: Duration) -> String {
+    format!(
+        "Too many wrong attempts. Try again in {}s.",
+        wait.as_secs().max(1)
+    )
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Refused {
+    Waiting(Duration),
+    Wrong(Outcome),
+}
+
+impl Refused {
+    pub fn message(&self, secret: Secret) -> String {
+        match self {
+            Self::Waiting(wait) => wait_message(*wait),
+            Self::Wrong(_) => format!("Incorrect {} supplied.", secret.label()),
+        }
+    }
+}
+
+/// opens the keystore on disk with `secret` and hands it back unlocked, to be locked once it's been read.
+/// The running Tesseract is already unlocked, so it can't tell whether the secret is right. Wrong guesses
+/// count the same as they do when unlocking.
+pub fn verify(secret: &str) -> Result<Tesseract, Refused> {
+    if let Some(wait) = Lockout::load().wait_remaining() {
+        return Err(Refused::Waiting(wait));
+    }
+    let opened = Tesseract::from_file(recovery::keystore_path()).and_then(|tesseract| {
+        tesseract.unlock(secret.as_bytes())?;
+        Ok(tesseract)
+    });
+    match opened {
+        Ok(tesseract) => {
+            Lockout::update(|lockout| {
+                lockout.record_success();
+                Outcome::Retry
+            });
+            Ok(tesseract)
+        }
+        Err(_) => Err(Refused::Wrong(Lockout::update(Lockout::record_failure))),
+    }
+}
+
+/// locks the keystore on disk with `new` instead of `old`. Nothing else may have it open meanwhile, or it
+/// would be saved over with the old secret.
+pub fn change_secret(old: &str, new: &str, secret: Secret) -> Result<(), warp::error::Error> {
+    let current = Tesseract::from_file(recovery::keystore_path())?;
+    current.unlock(old.as_bytes())?;
+    let keys = current.export()?;
+    current.lock();
+
+    let rekeyed = Tesseract::import(new.as_bytes(), keys)?;
+    rekeyed.to_file(recovery::keystore_path())?;
+    rekeyed.lock();
+
+    let mut lockout = Lockout::load();
+    lockout.secret = secret;
+    lockout.record_success();
+    if let Err(e) = lockout.save() {
+        log::error!("Not possible to save the kind of secret: {e}");
+    }
+    Ok(())
+}
+
+/// erases everything the profile in use keeps on this device: keystore, identity, messages and settings
+pub fn wipe() -> io::Result<()> {
+    let root = DEFAULT_PATH.read().clone();
+    wipe_folder(&root)
+}
+
+fn wipe_folder(root: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(root)?.flatten() {
+        let name = entry.file_name();
+        if KEPT_ON_WIPE.iter().any(|kept| name == *kept) {
+            continue;
+        }
+        let path = entry.path();
+        match path.is_dir() {
+            true => fs::remove_dir_all(&path)?,
+            false => fs::remove_file(&path)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn after_failures(failed_attempts: u32, wipe_after: u32) -> Lockout {
+        Lockout {
+            wipe_after,
+            failed_attempts,
+            last_failure: Some(Utc::now()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn free_attempts_dont_wait() {
+        for failed in 0..FREE_ATTEMPTS {
+            assert_eq!(after_failures(failed, 0).wait(), Duration::ZERO);
+        }
+        assert_eq!(after_failures(FREE_ATTEMPTS - 1, 0).wait_remaining(), None);
+    }
+
+    #[test]
+    fn wait_doubles_up_to_the_limit() {
+        let waits: Vec<u64> = (FREE_ATTEMPTS..FREE_ATTEMPTS + 5)
+            .map(|failed| after_failures(failed, 0).wait().as_secs())
+            .collect();
+        assert_eq!(waits, [1, 2, 4, 8, 16]);
+        assert_eq!(after_failures(FREE_ATTEMPTS + 10, 0).wait(), MAX_WAIT);
+        assert_eq!(after_failures(u32::MAX, 0).wait(), MAX_WAIT);
+        assert!(after_failures(FREE_ATTEMPTS, 0).wait_remaining().is_some());
+    }
+
+    #[test]
+    fn wipes_at_exactly_wipe_after() {
+        let mut lockout = after_failures(0, 5);
+        for left in (1..5).rev() {
+            assert_eq!(lockout.record_failure(), Outcome::Retry);
+            assert_eq!(lockout.attempts_left(), Some(left));
+        }
+        assert_eq!(lockout.record_failure(), Outcome::Wipe);
+        assert_eq!(lockout.attempts_left(), Some(0));
+    }
+
+    #[test]
+    fn never_wipes_when_turned_off() {
+        let mut lockout = Lockout::default();
+        for _ in 0..100 {
+            assert_eq!(lockout.record_failure(), Outcome::Retry);
+        }
+        assert_eq!(lockout.attempts_left(), None);
+    }
+
+    #[test]
+    fn success_starts_over() {
+        let mut lockout = after_failures(4, 5);
+        lockout.record_success();
+        assert_eq!(lockout.attempts_left(), Some(5));
+        assert_eq!(lockout.wait_remaining(), None);
+    }
+
+    #[test]
+    fn wipe_keeps_the_other_profiles() {
+        let root = std::env::temp_dir().join(format!("uplink-wipe-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("profiles/other")).unwrap();
+        fs::create_dir_all(root.join("warp")).unwrap();
+        fs::write(root.join("profiles/other/.keystore"), b"other").unwrap();
+        fs::write(root.join("profiles.json"), b"[]").unwrap();
+        fs::write(root.join(".keystore"), b"root").unwrap();
+        fs::write(root.join(".lockout.json"), b"{}").unwrap();
+
+        wipe_folder(&root).unwrap();
+
+        let mut left: Vec<_> = fs::read_dir(&root)
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["profiles", "profiles.json"]);
+        assert!(root.join("profiles/other/.keystore").exists());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,22 @@
+// Finding mentions lives with the markdown renderer, which highlights them; what's left here helps
+// with typing them.
+
+use ::utils::mentions::is_word_char;
+pub use ::utils::mentions::mentions_user;
+
+/// if the caret (end of `text`) is inside a mention being typed, returns the byte offset of the `@` and
+/// the partial username after it.
+pub fn mention_query(text: &str) -> Option<(usize, &str)> {
+    let at = text.rfind('@')?;
+    let query = &text[at + 1..];
+    let preceded_ok = !matches!(text[..at].chars().next_back(), Some(c) if is_word_char(c));
+    if !preceded_ok || query.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((at, query))
+}
+
+/// replaces the mention being typed at `at` with the full username.
+pub fn complete_mention(text: &str, at: usize, username: &str) -> String {
+    format!("{}@{} ", &text[..at], username)
+}
@@ -0,0 +1,178 @@
+// Profiles let several accounts live side by side. Each one is a folder with its own keystore, state and
+// config, and `DEFAULT_PATH` points at the one in use. The folder chosen with `--path` (or the default
+// one) is the root: it holds the first profile, so data from before profiles existed keeps working,
+// and the others are kept under `profiles/`.
+
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use warp::tesseract::Tesseract;
+
+use crate::{iutils::recovery, DEFAULT_PATH};
+
+// the profile stored in the root folder itself
+const DEFAULT_ID: &str = "default";
+const MAX_NAME_LENGTH: usize = 32;
+
+static ROOT: Lazy<PathBuf> = Lazy::new(|| DEFAULT_PATH.read().clone());
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl Profile {
+    /// whether this is the profile kept in the root folder, which holds the others
+    pub fn is_root(&self) -> bool {
+        self.id == DEFAULT_ID
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Index {
+    active: Option<String>,
+    // names given to profiles, the id is used for those without one
+    names: BTreeMap<String, String>,
+}
+
+fn index_path() -> PathBuf {
+    ROOT.join("profiles.json")
+}
+
+fn load_index() -> Index {
+    fs::read(index_path())
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(index: &Index) -> io::Result<()> {
+    fs::create_dir_all(ROOT.as_path())?;
+    let bytes = serde_json::to_vec_pretty(index)?;
+    fs::write(index_path(), bytes)
+}
+
+fn profile_path(id: &str) -> PathBuf {
+    match id {
+        DEFAULT_ID => ROOT.clone(),
+        id => ROOT.join("profiles").join(id),
+    }
+}
+
+fn profile(index: &Index, id: &str) -> Profile {
+    let name = match (index.names.get(id), id) {
+        (Some(name), _) => name.clone(),
+        (None, DEFAULT_ID) => String::from("Default"),
+        (None, id) => id.to_string(),
+    };
+    Profile {
+        id: id.to_string(),
+        name,
+        path: profile_path(id),
+    }
+}
+
+fn check_name(name: &str) -> io::Result<String> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("A profile name is 1 to {MAX_NAME_LENGTH} characters long"),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// every profile, the first one followed by the others by name
+pub fn list() -> Vec<Profile> {
+    let index = load_index();
+    let mut others: Vec<Profile> = fs::read_dir(ROOT.join("profiles"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| profile(&index, &entry.file_name().to_string_lossy()))
+        .collect();
+    others.sort_by_key(|profile| profile.name.to_lowercase());
+    std::iter::once(profile(&index, DEFAULT_ID))
+        .chain(others)
+        .collect()
+}
+
+pub fn active() -> Profile {
+    let index = load_index();
+    let id = index
+        .active
+        .clone()
+        .filter(|id| profile_path(id).is_dir())
+        .unwrap_or_else(|| DEFAULT_ID.to_string());
+    profile(&index, &id)
+}
+
+/// points `DEFAULT_PATH` at the profile used last. Runs before anything else reads it.
+pub fn restore_active() {
+    Lazy::force(&ROOT);
+    *DEFAULT_PATH.write() = active().path;
+}
+
+/// makes `id` the profile in use, from now on and on the next start
+pub fn set_active(id: &str) -> io::Result<Profile> {
+    let mut index = load_index();
+    let profile = profile(&index, id);
+    fs::create_dir_all(&profile.path)?;
+    index.active = Some(profile.id.clone());
+    save_index(&index)?;
+    *DEFAULT_PATH.write() = profile.path.clone();
+    Ok(profile)
+}
+
+pub fn create(name: &str) -> io::Result<Profile> {
+    let name = check_name(name)?;
+    let id = Uuid::new_v4().to_string();
+    fs::create_dir_all(profile_path(&id))?;
+    let mut index = load_index();
+    index.names.insert(id.clone(), name);
+    save_index(&index)?;
+    Ok(profile(&index, &id))
+}
+
+pub fn rename(id: &str, name: &str) -> io::Result<()> {
+    let name = check_name(name)?;
+    let mut index = load_index();
+    index.names.insert(id.to_string(), name);
+    save_index(&index)
+}
+
+/// removes a profile and everything in it. The first profile holds the others, and the one in use is
+/// still open, so neither can be deleted.
+pub fn delete(id: &str) -> io::Result<()> {
+    if profile(&load_index(), id).is_root() || id == active().id {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "This profile can't be deleted",
+        ));
+    }
+    fs::remove_dir_all(profile_path(id))?;
+    let mut index = load_index();
+    index.names.remove(id);
+    save_index(&index)
+}
+
+/// the keystore of the profile in use, created when it's new
+pub fn open_keystore() -> Tesseract {
+    let path = recovery::keystore_path();
+    match Tesseract::from_file(&path) {
+        Ok(tess) => tess,
+        Err(_) => {
+            //doesnt exist so its set
+            let tess = Tesseract::default();
+            tess.set_file(path);
+            tess.set_autosave();
+            tess
+        }
+    }
+}
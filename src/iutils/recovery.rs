@@ -0,0 +1,43 @@
+// The recovery phrase an identity's keypair is derived from. MultiPass keeps it in Tesseract when the
+// account is created, so it can be shown again later; typing it in on a new device brings the same
+// identity back.
+
+use std::path::PathBuf;
+
+use warp::tesseract::Tesseract;
+
+use crate::DEFAULT_PATH;
+
+// where MultiPass leaves the phrase in Tesseract
+const PHRASE_KEY: &str = "mnemonic";
+const WORD_COUNTS: [usize; 2] = [12, 24];
+
+pub fn keystore_path() -> PathBuf {
+    DEFAULT_PATH.read().join(".keystore")
+}
+
+/// tidies up a phrase as it was typed or pasted: lowercase words separated by single spaces
+pub fn normalize_phrase(input: &str) -> Result<String, &'static str> {
+    let words: Vec<String> = input
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect();
+    if !WORD_COUNTS.contains(&words.len()) {
+        return Err("A recovery phrase is made of 12 or 24 words");
+    }
+    if words
+        .iter()
+        .any(|word| !word.chars().all(|c| c.is_ascii_lowercase()))
+    {
+        return Err("A recovery phrase only has letters in it");
+    }
+    Ok(words.join(" "))
+}
+
+/// reads the phrase from a keystore opened by `lockout::verify`
+pub fn recovery_phrase(keystore: &Tesseract) -> Result<Option<String>, warp::error::Error> {
+    match keystore.exist(PHRASE_KEY) {
+        true => Ok(Some(keystore.retrieve(PHRASE_KEY)?)),
+        false => Ok(None),
+    }
+}
@@ -0,0 +1,246 @@
+// Storage accounting. Works out how much of Constellation is in use and what by, how much space the
+// app keeps on this device and whether the user is getting close to their quota.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use warp::constellation::{directory::Directory, item::ItemType};
+
+use ::utils::link_preview;
+
+use crate::{
+    components::{
+        main::{
+            compose::attachments,
+            files::{sharing, thumbnails},
+        },
+        reusable::file_preview,
+    },
+    iutils::config::StorageQuota,
+    Storage, DEFAULT_PATH,
+};
+
+const KINDS: [(&str, &[&str]); 6] = [
+    (
+        "Images",
+        &[
+            "png", "jpg", "jpeg", "gif", "webp", "bmp", "ico", "tif", "tiff", "svg", "heic",
+        ],
+    ),
+    (
+        "Videos",
+        &["mp4", "m4v", "mov", "mkv", "webm", "avi", "wmv", "ogv"],
+    ),
+    (
+        "Audio",
+        &["mp3", "wav", "ogg", "oga", "opus", "flac", "m4a", "aac"],
+    ),
+    (
+        "Documents",
+        &[
+            "pdf", "txt", "md", "markdown", "doc", "docx", "odt", "rtf", "xls", "xlsx", "ods",
+            "csv", "ppt", "pptx", "odp",
+        ],
+    ),
+    (
+        "Archives",
+        &["zip", "tar", "gz", "tgz", "bz2", "xz", "7z", "rar"],
+    ),
+    (
+        "Code",
+        &[
+            "rs", "js", "ts", "jsx", "tsx", "py", "go", "c", "h", "cpp", "hpp", "java", "kt",
+            "swift", "rb", "sh", "json", "toml", "yaml", "yml", "html", "css", "scss",
+        ],
+    ),
+];
+const OTHER_KIND: &str = "Other";
+// files directly in the root folder are counted under this name in the breakdown by folder
+const ROOT_FOLDER: &str = "Home";
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum QuotaWarning {
+    Ok,
+    NearlyFull,
+    Full,
+}
+
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct ConstellationUsage {
+    pub used: u64,
+    // the quota when one is set, otherwise what's used plus the free space left on the disk
+    pub max: u64,
+    // sizes by kind of file and by top level folder, biggest first
+    pub by_kind: Vec<(String, u64)>,
+    pub by_folder: Vec<(String, u64)>,
+}
+
+impl ConstellationUsage {
+    pub fn available(&self) -> u64 {
+        self.max.saturating_sub(self.used)
+    }
+
+    pub fn percent_used(&self) -> u16 {
+        percent(self.used, self.max)
+    }
+
+    pub fn warning(&self, quota: &StorageQuota) -> QuotaWarning {
+        if self.max > 0 && self.used >= self.max {
+            QuotaWarning::Full
+        } else if self.percent_used() >= u16::from(quota.warn_at_percent) {
+            QuotaWarning::NearlyFull
+        } else {
+            QuotaWarning::Ok
+        }
+    }
+}
+
+/// how much of `total` `part` is, from 0 to 100
+pub fn percent(part: u64, total: u64) -> u16 {
+    if total == 0 {
+        return 0;
+    }
+    (part.min(total) as f64 / total as f64 * 100.0).round() as u16
+}
+
+fn kind(name: &str) -> &'static str {
+    let extension = Path::new(name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    KINDS
+        .iter()
+        .find(|(_, extensions)| extensions.contains(&extension.as_str()))
+        .map(|(kind, _)| *kind)
+        .unwrap_or(OTHER_KIND)
+}
+
+fn add_files(directory: &Directory, by_kind: &mut HashMap<&'static str, u64>) -> u64 {
+    directory
+        .get_items()
+        .iter()
+        .map(|item| match item.item_type() {
+            ItemType::DirectoryItem => item
+                .get_directory()
+                .map(|directory| add_files(&directory, by_kind))
+                .unwrap_or_default(),
+            _ => {
+                let size = item.size() as u64;
+                *by_kind.entry(kind(&item.name())).or_default() += size;
+                size
+            }
+        })
+        .sum()
+}
+
+fn sorted(sizes: impl IntoIterator<Item = (String, u64)>) -> Vec<(String, u64)> {
+    let mut sizes: Vec<_> = sizes.into_iter().filter(|(_, size)| *size > 0).collect();
+    sizes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    sizes
+}
+
+/// what's stored in Constellation. Walks the whole tree, which Constellation keeps in memory.
+pub fn constellation_usage(storage: &Storage, quota: &StorageQuota) -> ConstellationUsage {
+    let mut by_kind = HashMap::new();
+    let mut by_folder: HashMap<String, u64> = HashMap::new();
+    for item in storage.root_directory().get_items() {
+        match item.item_type() {
+            ItemType::DirectoryItem => {
+                let size = item
+                    .get_directory()
+                    .map(|directory| add_files(&directory, &mut by_kind))
+                    .unwrap_or_default();
+                *by_folder.entry(item.name()).or_default() += size;
+            }
+            _ => {
+                let size = item.size() as u64;
+                *by_kind.entry(kind(&item.name())).or_default() += size;
+                *by_folder.entry(String::from(ROOT_FOLDER)).or_default() += size;
+            }
+        }
+    }
+
+    let used = by_folder.values().sum();
+    let max = match quota.quota {
+        0 => used + fs2::available_space(&*DEFAULT_PATH.read()).unwrap_or_default(),
+        quota => quota,
+    };
+    ConstellationUsage {
+        used,
+        max,
+        by_kind: sorted(
+            by_kind
+                .into_iter()
+                .map(|(kind, size)| (kind.to_string(), size)),
+        ),
+        by_folder: sorted(by_folder),
+    }
+}
+
+/// the size of everything under `path`. Symlinks aren't followed.
+pub fn dir_size(path: &Path) -> u64 {
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => dir_size(&entry.path()),
+            Ok(metadata) if !metadata.file_type().is_symlink() => metadata.len(),
+            _ => 0,
+        })
+        .sum()
+}
+
+// folders under DEFAULT_PATH the app can always rebuild
+fn cache_dirs() -> [PathBuf; 3] {
+    let path = DEFAULT_PATH.read();
+    [
+        path.join(thumbnails::CACHE_DIR),
+        path.join(file_preview::CACHE_DIR),
+        path.join(link_preview::CACHE_DIR),
+    ]
+}
+
+// folders under DEFAULT_PATH with copies of files waiting to be sent
+fn staging_dirs() -> [PathBuf; 2] {
+    let path = DEFAULT_PATH.read();
+    [
+        path.join(attachments::STAGING_DIR),
+        path.join(sharing::STAGING_DIR),
+    ]
+}
+
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct LocalUsage {
+    // everything the app keeps on this device, Constellation and RayGun data included
+    pub total: u64,
+    // thumbnails, previewed attachments and link previews
+    pub caches: u64,
+    // copies of attachments and shared files waiting to be sent
+    pub staged: u64,
+}
+
+/// what the app keeps under `DEFAULT_PATH`. Reads the disk, so it's best run off the UI thread.
+pub fn local_usage() -> LocalUsage {
+    LocalUsage {
+        total: dir_size(&DEFAULT_PATH.read()),
+        caches: cache_dirs().iter().map(|dir| dir_size(dir)).sum(),
+        staged: staging_dirs().iter().map(|dir| dir_size(dir)).sum(),
+    }
+}
+
+/// removes the thumbnail, preview and link preview caches, they're made again when needed
+pub fn clear_caches() -> std::io::Result<()> {
+    for dir in cache_dirs() {
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+    }
+    Ok(())
+}
@@ -3,13 +3,15 @@ use crate::iutils::config::Config;
 use ::utils::Account;
 use clap::Parser;
 use core::time;
+use dioxus::core::to_owned;
 use dioxus::desktop::tao;
 use dioxus::desktop::wry::webview::FileDropEvent;
 use dioxus::router::{Route, Router};
 use dioxus::{desktop::tao::dpi::LogicalSize, prelude::*};
 use dioxus_heroicons::outline::Shape;
-use dioxus_toast::ToastManager;
+use dioxus_toast::{ToastInfo, ToastManager};
 use fluent::{FluentBundle, FluentResource};
+use futures::StreamExt;
 use language::{AvailableLanguages, Language};
 use once_cell::sync::Lazy;
 use sir::AppStyle;
@@ -17,14 +19,18 @@ use std::{
     fs,
     ops::{Deref, DerefMut},
     path::PathBuf,
+    sync::{Arc, Mutex},
     thread,
 };
 use themes::Theme;
 use tracing::metadata::LevelFilter;
 use tracing_subscriber::EnvFilter;
-use ui_kit::context_menu::{ContextItem, ContextMenu};
+use ui_kit::{
+    context_menu::{ContextItem, ContextMenu},
+    loader::Loader,
+};
 use unic_langid::LanguageIdentifier;
-use utils::{Storage, DEFAULT_PATH};
+use utils::{at_rest, markdown, Storage, DEFAULT_PATH};
 use warp::{
     constellation::Constellation, multipass::MultiPass, raygun::RayGun, sync::RwLock,
     tesseract::Tesseract,
@@ -35,7 +41,8 @@ use warp_rg_ipfs::config::RgIpfsConfig;
 use warp_rg_ipfs::Persistent;
 
 use crate::components::main;
-use crate::components::prelude::{auth, loading, unlock};
+use crate::components::prelude::{auth, loading, restore, unlock};
+use crate::components::reusable::auto_lock::AutoLock;
 
 pub mod components;
 pub mod iutils;
@@ -49,6 +56,8 @@ use tao::menu::{MenuBar as Menu, MenuItem};
 
 static TOAST_MANAGER: AtomRef<ToastManager> = |_| ToastManager::default();
 static LANGUAGE: AtomRef<Language> = |_| Language::by_locale(AvailableLanguages::EnUS);
+// whose profile is shown in the profile modal, if anyone's
+static PROFILE_MODAL: AtomRef<Option<warp::crypto::DID>> = |_| None;
 
 pub const WINDOW_SUFFIX_NAME: &str = "Uplink";
 
@@ -58,13 +67,44 @@ static DEFAULT_WINDOW_NAME: Lazy<RwLock<String>> =
 static DRAG_FILE_EVENT: Lazy<RwLock<FileDropEvent>> =
     Lazy::new(|| RwLock::new(FileDropEvent::Cancelled));
 
-#[derive(PartialEq, Props)]
-pub struct State {
+// the warp services of the profile in use
+#[derive(Clone, PartialEq)]
+pub struct Services {
     tesseract: Tesseract,
     account: Account,
     messaging: Messaging,
     storage: Storage,
 }
+
+// App takes the services out when it starts, so it holds the only copy and can drop them when switching
+// to another profile
+#[derive(Clone)]
+pub struct Launch(Arc<Mutex<Option<Services>>>);
+
+impl PartialEq for Launch {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[derive(PartialEq, Props)]
+pub struct State {
+    launch: Launch,
+    experimental_node: bool,
+}
+
+pub enum ProfileAction {
+    // tears down the services of the profile in use and starts those of another
+    Switch(String),
+    // locks the keystore with a new secret, which can only be done once nothing else has it open
+    ChangeSecret {
+        old: String,
+        new: String,
+        secret: iutils::lockout::Secret,
+    },
+    // erases the profile in use after too many wrong guesses and starts it again empty
+    Wipe,
+}
 #[derive(Debug, Parser)]
 #[clap(name = "")]
 struct Opt {
@@ -74,6 +114,8 @@ struct Opt {
     title: Option<String>,
     #[clap(long)]
     experimental_node: bool,
+    // an `uplink://add/<code>` link the app was opened with
+    invite: Option<String>,
 }
 
 fn main() {
@@ -133,6 +175,7 @@ fn main() {
     main_menu.add_submenu("Window", true, window_menu);
 
     let opt = Opt::parse();
+    iutils::profiles::restore_active();
 
     let file_appender =
         tracing_appender::rolling::hourly(DEFAULT_PATH.read().join("logs"), "warp-gui.log");
@@ -146,20 +189,19 @@ fn main() {
         )
         .init();
 
+    if let Some(link) = &opt.invite {
+        match iutils::invite::parse(link) {
+            Some(did) => iutils::invite::set_pending(did),
+            None => log::warn!("ignoring an invite link that isn't valid: {link}"),
+        }
+    }
+
     if let Some(title) = opt.title {
         *DEFAULT_WINDOW_NAME.write() = title;
     }
 
-    let tesseract = match Tesseract::from_file(DEFAULT_PATH.read().join(".keystore")) {
-        Ok(tess) => tess,
-        Err(_) => {
-            //doesnt exist so its set
-            let tess = Tesseract::default();
-            tess.set_file(DEFAULT_PATH.read().join(".keystore"));
-            tess.set_autosave();
-            tess
-        }
-    };
+    components::main::compose::attachments::clear_staged();
+    let tesseract = iutils::profiles::open_keystore();
 
     let (account, messaging, storage) = match warp::async_block_in_place_uncheck(initialization(
         DEFAULT_PATH.read().clone(),
@@ -169,6 +211,12 @@ fn main() {
         Ok((i, c, s)) => (Account(i.clone()), Messaging(c.clone()), Storage(s.clone())),
         Err(_e) => todo!(),
     };
+    let launch = Launch(Arc::new(Mutex::new(Some(Services {
+        tesseract,
+        account,
+        messaging,
+        storage,
+    }))));
 
     let window = WindowBuilder::new()
         .with_title(DEFAULT_WINDOW_NAME.read().clone())
@@ -180,10 +228,8 @@ fn main() {
     dioxus::desktop::launch_with_props(
         App,
         State {
-            tesseract,
-            account,
-            messaging,
-            storage,
+            launch,
+            experimental_node: opt.experimental_node,
         },
         |c| {
             c.with_window(|_| window.with_menu(main_menu))
@@ -198,10 +244,8 @@ fn main() {
     dioxus::desktop::launch_with_props(
         App,
         State {
-            tesseract,
-            account,
-            messaging,
-            storage,
+            launch,
+            experimental_node: opt.experimental_node,
         },
         |c| {
             c.with_window(|_| window)
@@ -218,7 +262,9 @@ async fn initialization(
     tesseract: Tesseract,
     experimental: bool,
 ) -> Result<(Box<dyn MultiPass>, Box<dyn RayGun>, Box<dyn Constellation>), warp::error::Error> {
-    let config = MpIpfsConfig::production(&path, experimental);
+    let mut config = MpIpfsConfig::production(&path, experimental);
+    // kept so the recovery phrase can be shown in settings
+    config.save_phrase = true;
 
     let account = warp_mp_ipfs::ipfs_identity_persistent(config, tesseract, None)
         .await
@@ -249,21 +295,110 @@ fn App(cx: Scope<State>) -> Element {
     std::fs::create_dir_all(DEFAULT_PATH.read().clone()).expect("Error creating directory");
     Config::new_file();
 
+    let services = use_ref(&cx, || {
+        cx.props
+            .launch
+            .0
+            .lock()
+            .ok()
+            .and_then(|mut services| services.take())
+    });
     cx.use_hook(|_| {
-        cx.provide_context(cx.props.messaging.clone());
+        Config::load_config_or_default().notifications.apply();
     });
     // Loads the styles for all of our UIKit elements.
     let theme_colors = Theme::load_or_default().rosetta();
     let toast = use_atom_ref(&cx, TOAST_MANAGER);
+    let state = use_atom_ref(&cx, STATE);
+
+    let profile_modal = use_atom_ref(&cx, PROFILE_MODAL);
+
+    let experimental_node = cx.props.experimental_node;
+    use_coroutine(&cx, |mut rx: UnboundedReceiver<ProfileAction>| {
+        to_owned![services, state, toast, profile_modal];
+        async move {
+            while let Some(action) = rx.next().await {
+                let previous = iutils::profiles::active().id;
+                // the router is unmounted while there are no services, which lets go of every copy of them
+                if let Some(old) = services.write().take() {
+                    old.tesseract.lock();
+                    at_rest::lock();
+                }
+                // as do the globals which outlive the routes
+                main::files::reset();
+                *profile_modal.write() = None;
+                let id = match action {
+                    ProfileAction::Switch(id) => id,
+                    ProfileAction::ChangeSecret { old, new, secret } => {
+                        let message = match iutils::lockout::change_secret(&old, &new, secret) {
+                            Ok(()) => {
+                                format!("{} changed, unlock with the new one.", secret.label())
+                            }
+                            Err(e) => {
+                                log::error!("Not possible to change the {}: {e}", secret.label());
+                                format!("Not possible to change the {}.", secret.label())
+                            }
+                        };
+                        toast.write().popup(ToastInfo::simple(&message));
+                        previous.clone()
+                    }
+                    ProfileAction::Wipe => {
+                        if let Err(e) = iutils::lockout::wipe() {
+                            log::error!("Not possible to erase profile {previous}: {e}");
+                        }
+                        previous.clone()
+                    }
+                };
+                for id in [id, previous] {
+                    if let Err(e) = iutils::profiles::set_active(&id) {
+                        log::error!("Not possible to open profile {id}: {e}");
+                        continue;
+                    }
+                    components::main::compose::attachments::clear_staged();
+                    let tesseract = iutils::profiles::open_keystore();
+                    let started = initialization(
+                        DEFAULT_PATH.read().clone(),
+                        tesseract.clone(),
+                        experimental_node,
+                    )
+                    .await;
+                    match started {
+                        Ok((i, c, s)) => {
+                            *state.write() = state::PersistedState::load_or_initial();
+                            Config::new_file();
+                            Config::load_config_or_default().notifications.apply();
+                            *DEFAULT_WINDOW_NAME.write() = String::from(WINDOW_SUFFIX_NAME);
+                            *services.write() = Some(Services {
+                                tesseract,
+                                account: Account(i),
+                                messaging: Messaging(c),
+                                storage: Storage(s),
+                            });
+                            break;
+                        }
+                        Err(e) => {
+                            log::error!("Not possible to start profile {id}: {e}");
+                            toast
+                                .write()
+                                .popup(ToastInfo::simple("Not possible to open that profile."));
+                        }
+                    }
+                }
+            }
+        }
+    });
+    let current = services.read().clone();
 
     let css = include_str!(".styles.css");
+    let highlight_css = cx.use_hook(|_| markdown::highlight_css()).clone();
 
     thread::sleep(time::Duration::from_millis(16)); // 60 Hz
 
     cx.render(rsx!(
         style {
             "{theme_colors}",
-            "{css}"
+            "{css}",
+            "{highlight_css}"
         },
         dioxus_toast::ToastFrame {
             manager: toast,
@@ -283,28 +418,55 @@ fn App(cx: Scope<State>) -> Element {
                     }
                 })
             },
-            Router {
-                Route { to: "/", unlock::Unlock { tesseract: cx.props.tesseract.clone() } }
-                Route { to: "/loading", loading::Loading { account: cx.props.account.clone() } },
-                Route { to: "/auth", auth::Auth { account: cx.props.account.clone() } },
-                Route { to: "/main/files", main::files::Files { account: cx.props.account.clone(), storage: cx.props.storage.clone(), messaging: cx.props.messaging.clone() } },
-                Route { to: "/main/friends", main::friends::Friends { account: cx.props.account.clone(), messaging: cx.props.messaging.clone() } },
-                Route { to: "/main/settings", main::settings::Settings {
-                    account: cx.props.account.clone(),
-                    page_to_open: main::settings::sidebar::nav::Route::General,
-                    messaging: cx.props.messaging.clone()
-                }},
-                Route { to: "/main/settings/profile", main::settings::Settings {
-                    account: cx.props.account.clone(),
-                    page_to_open: main::settings::sidebar::nav::Route::Profile,
-                    messaging: cx.props.messaging.clone()
-                }},
-                Route { to: "/main", main::Main { account: cx.props.account.clone(), messaging: cx.props.messaging.clone() } },
+            match current {
+                Some(services) => rsx!(ProvideMessaging {
+                    messaging: services.messaging.clone(),
+                    Router {
+                        AutoLock {
+                            tesseract: services.tesseract.clone(),
+                            Route { to: "/", unlock::Unlock { tesseract: services.tesseract.clone() } }
+                            Route { to: "/loading", loading::Loading { account: services.account.clone() } },
+                            Route { to: "/auth", auth::Auth { account: services.account.clone() } },
+                            Route { to: "/restore", restore::Restore { account: services.account.clone() } },
+                            Route { to: "/main/files", main::files::Files { account: services.account.clone(), storage: services.storage.clone(), messaging: services.messaging.clone() } },
+                            Route { to: "/main/friends", main::friends::Friends { account: services.account.clone(), messaging: services.messaging.clone() } },
+                            Route { to: "/main/settings", main::settings::Settings {
+                                account: services.account.clone(),
+                                page_to_open: main::settings::sidebar::nav::Route::General,
+                                messaging: services.messaging.clone(),
+                                storage: services.storage.clone(),
+                            }},
+                            Route { to: "/main/settings/profile", main::settings::Settings {
+                                account: services.account.clone(),
+                                page_to_open: main::settings::sidebar::nav::Route::Profile,
+                                messaging: services.messaging.clone(),
+                                storage: services.storage.clone(),
+                            }},
+                            Route { to: "/main", main::Main { account: services.account.clone(), messaging: services.messaging.clone(), storage: services.storage.clone() } },
+                        }
+                    }
+                }),
+                None => rsx!(Loader {
+                    text: String::from("Opening profile..."),
+                }),
             }
         }
     ))
 }
 
+#[derive(Props)]
+struct ProvideMessagingProps<'a> {
+    messaging: Messaging,
+    children: Element<'a>,
+}
+
+// the routes reach RayGun through the context, which goes with this scope when the profile is switched
+#[allow(non_snake_case)]
+fn ProvideMessaging<'a>(cx: Scope<'a, ProvideMessagingProps<'a>>) -> Element<'a> {
+    cx.provide_context(cx.props.messaging.clone());
+    cx.render(rsx!(&cx.props.children))
+}
+
 #[derive(Clone)]
 pub struct Messaging(Box<dyn RayGun>);
 
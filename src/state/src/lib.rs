@@ -8,7 +8,7 @@ use std::{
 use uuid::Uuid;
 use warp::raygun::Conversation;
 
-use utils::DEFAULT_PATH;
+use utils::{at_rest, DEFAULT_PATH};
 
 pub static STATE: AtomRef<PersistedState> = |_| PersistedState::load_or_initial();
 
@@ -26,6 +26,8 @@ pub enum Actions {
     ChatWith(Conversation),
     UpdateConversation(ConversationInfo),
     UpdateFavorites(HashSet<Uuid>),
+    // muted chats don't notify, except for mentions
+    SetMuted(Uuid, bool),
     HideSidebar(bool),
     //DeselectChat,
     SetShowPrerelaseNotice(bool),
@@ -53,6 +55,8 @@ pub struct PersistedState {
     pub show_prerelease_notice: bool,
     pub send_typing: bool,
     pub enabled_extensions: Vec<String>,
+    #[serde(default)]
+    pub muted_chats: HashSet<Uuid>,
 }
 
 #[derive(Serialize, Deserialize, Default, Clone, Eq, PartialEq)]
@@ -68,6 +72,9 @@ pub struct ConversationInfo {
     /// the uuid of the last message read.
     /// used to determine the number of unread messages
     pub num_unread_messages: u32,
+    /// unread messages which mention the local user. shown separately from the unread count
+    #[serde(default)]
+    pub num_unread_mentions: u32,
     /// the first two lines of the last message sent
     pub last_msg_sent: Option<LastMsgSent>,
     /// the first unread message set when the chat is not active
@@ -110,8 +117,9 @@ pub fn total_notifications(s: &PersistedState) -> u32 {
 }
 
 impl PersistedState {
+    /// the saved state. It's encrypted, so the initial state is returned until the keystore is unlocked.
     pub fn load_or_initial() -> Self {
-        if let Ok(b) = std::fs::read(DEFAULT_PATH.read().join(".uplink.state.json")) {
+        if let Ok(b) = at_rest::read(&DEFAULT_PATH.read().join(".uplink.state.json")) {
             // if a field is added to the state, parsing will fail. in that case, want to return the same struct that is created by default.
             // todo: add versioning to PersistedState
             if let Ok(c) = serde_json::from_slice::<PersistedState>(&b) {
@@ -128,7 +136,7 @@ impl PersistedState {
     pub fn save(&self) {
         match serde_json::to_vec(self) {
             Ok(bytes) => {
-                match std::fs::write(DEFAULT_PATH.read().join(".uplink.state.json"), &bytes) {
+                match at_rest::write(&DEFAULT_PATH.read().join(".uplink.state.json"), &bytes) {
                     Ok(_) => {}
                     Err(e) => eprintln!("error saving: {}", e),
                 }
@@ -137,6 +145,19 @@ impl PersistedState {
         }
     }
 
+    /// forgets the previews of the last messages, so they aren't held in memory while the app is locked.
+    /// the conversations themselves are still tracked.
+    pub fn clear_previews(&mut self) {
+        self.selected_chat = None;
+        for info in self
+            .active_chats
+            .values_mut()
+            .chain(self.all_chats.values_mut())
+        {
+            info.last_msg_sent = None;
+        }
+    }
+
     pub fn dispatch(&mut self, action: Actions) {
         match action {
             Actions::AddConversation(conversation) => {
@@ -159,6 +180,7 @@ impl PersistedState {
                     self.selected_chat = None;
                 }
                 self.all_chats.remove(&conversation_id);
+                self.muted_chats.remove(&conversation_id);
 
                 let favorites = self
                     .favorites
@@ -241,6 +263,13 @@ impl PersistedState {
                 log::debug!("PersistedState: UpdateFavorites");
                 self.favorites = favorites;
             }
+            Actions::SetMuted(conversation_id, muted) => {
+                log::debug!("PersistedState: SetMuted {}: {}", conversation_id, muted);
+                match muted {
+                    true => self.muted_chats.insert(conversation_id),
+                    false => self.muted_chats.remove(&conversation_id),
+                };
+            }
             Actions::HideSidebar(slide_bar_bool) => {
                 log::debug!("PersistedState: HideSidebar");
                 self.hide_sidebar = slide_bar_bool;
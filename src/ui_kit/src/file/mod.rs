@@ -1,28 +1,32 @@
 use std::{ffi::OsStr, path::PathBuf};
 
-use dioxus::{core::to_owned, prelude::*};
+use dioxus::prelude::*;
 use dioxus_elements::KeyCode;
 use dioxus_heroicons::{outline::Shape, Icon};
-use utils::Storage;
 
 use super::folder::State;
 use crate::context_menu::{ContextItem, ContextMenu};
-use rfd::FileDialog;
 
-// Remember: owned props must implement PartialEq!
-#[derive(PartialEq, Props)]
-pub struct Props {
+#[derive(Props)]
+pub struct Props<'a> {
     name: String,
     state: State,
     id: String,
     kind: String,
     size: usize,
     thumbnail: String,
-    storage: Storage,
+    // deleting, copying and duplicating are left to the browser, which handles whole selections.
+    // Renaming and downloading go through it too, as they need the folder the file is in.
+    on_rename: EventHandler<'a, String>,
+    on_download: EventHandler<'a, ()>,
+    on_delete: EventHandler<'a, ()>,
+    on_copy: EventHandler<'a, ()>,
+    on_duplicate: EventHandler<'a, ()>,
+    on_share: EventHandler<'a, ()>,
 }
 
 #[allow(non_snake_case)]
-pub fn File(cx: Scope<Props>) -> Element {
+pub fn File<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
     let class = match cx.props.state {
         State::Primary => "primary",
         State::Secondary => "secondary",
@@ -30,11 +34,9 @@ pub fn File(cx: Scope<Props>) -> Element {
 
     let file_id = cx.props.id.clone();
 
-    let file_name_fmt = format_file_name_to_show(cx.props.name.clone(), cx.props.kind.clone());
-
-    let file_name_formatted_state = use_state(&cx, || file_name_fmt);
-
-    let file_name_complete_ref = use_ref(&cx, || cx.props.name.clone());
+    // the name comes from the listing, which picks up a rename once it's done
+    let file_name_formatted =
+        format_file_name_to_show(cx.props.name.clone(), cx.props.kind.clone());
 
     let file_size = format_file_size(cx.props.size);
     let file_thumb = &cx.props.thumbnail.clone();
@@ -73,42 +75,40 @@ pub fn File(cx: Scope<Props>) -> Element {
                                 ContextItem {
                                     icon: Shape::DocumentArrowDown,
                                     onpressed: move |_| {
-                                        hide_edit_name_element(cx);
-                                        let file_storage = cx.props.storage.clone();
-                                        let file_name = &*file_name_complete_ref.read();
-                                        let file_extension = cx.props.kind.clone();
-                                        cx.spawn({
-                                            to_owned![file_storage, file_name, file_extension];
-                                            async move {
-                                                let file_path_buff = match FileDialog::new().set_directory(".").set_file_name(&file_name).add_filter("", &[&file_extension]).save_file() {
-                                                    Some(path) => path,
-                                                    None => return,
-                                                };
-                                                let file_path_str = file_path_buff.to_string_lossy().to_string();
-                                                match file_storage.get(&file_name, &file_path_str).await {
-                                                    Ok(_) => log::info!("{file_name} downloaded."),
-                                                    Err(error) => log::error!("Error downloading file {}: {error}", &file_name),
-                                                };
-                                            }
-                                        });
+                                        hide_edit_name_element(&cx, &cx.props.id);
+                                        cx.props.on_download.call(());
                                     },
                                     text: String::from("Download")
                                 },
+                                ContextItem {
+                                    icon: Shape::DocumentDuplicate,
+                                    onpressed: move |_| {
+                                        hide_edit_name_element(&cx, &cx.props.id);
+                                        cx.props.on_duplicate.call(());
+                                    },
+                                    text: String::from("Duplicate")
+                                },
+                                ContextItem {
+                                    icon: Shape::Share,
+                                    onpressed: move |_| {
+                                        hide_edit_name_element(&cx, &cx.props.id);
+                                        cx.props.on_share.call(());
+                                    },
+                                    text: String::from("Share")
+                                },
+                                ContextItem {
+                                    icon: Shape::ClipboardDocument,
+                                    onpressed: move |_| {
+                                        hide_edit_name_element(&cx, &cx.props.id);
+                                        cx.props.on_copy.call(());
+                                    },
+                                    text: String::from("Copy")
+                                },
                                 hr {},
                                 ContextItem {
                                     onpressed: move |_| {
-                                        hide_edit_name_element(cx);
-                                        let file_storage = cx.props.storage.clone();
-                                        let file_name = &*file_name_complete_ref.read();
-                                        cx.spawn({
-                                            to_owned![file_storage, file_name];
-                                            async move {
-                                                match file_storage.remove(&file_name, true).await {
-                                                    Ok(_) => log::info!("{file_name} was deleted."),
-                                                    Err(error) => log::error!("Error deleting file: {error}"),
-                                                };
-                                            }
-                                        });
+                                        hide_edit_name_element(&cx, &cx.props.id);
+                                        cx.props.on_delete.call(());
                                     },
                                     icon: Shape::Trash,
                                     danger: true,
@@ -126,12 +126,12 @@ pub fn File(cx: Scope<Props>) -> Element {
                         }
                         {
                         let val = use_ref(&cx, String::new);
-                        let complete_file_name = file_name_complete_ref.read();
+                        let complete_file_name = &cx.props.name;
                         let file_id = file_id.clone();
                         rsx! {
                             p {
                                 id: "{file_id}-name-normal",
-                                "{file_name_formatted_state}" }
+                                "{file_name_formatted}" }
                             input {
                             id: "{file_id}-input",
                             display: "none",
@@ -142,34 +142,11 @@ pub fn File(cx: Scope<Props>) -> Element {
                             },
                             onkeyup: move |evt| {
                                 if evt.key_code == KeyCode::Enter {
-                                    let file_storage = cx.props.storage.clone();
-                                    let old_file_name = &*file_name_complete_ref.read();
-                                    let file_extension = cx.props.kind.clone();
-                                    let new_file_name = val.read();
-                                    hide_edit_name_element(cx);
-
-                                    if !new_file_name.trim().is_empty() {
-                                        cx.spawn({
-                                            to_owned![file_storage, old_file_name, new_file_name, file_extension, file_name_formatted_state, file_name_complete_ref];
-                                            async move {
-                                                let new_file_name_with_extension = format_args!("{}.{}", new_file_name.trim(), file_extension.clone()).to_string();
-
-                                                match file_storage.rename(&old_file_name, &new_file_name_with_extension).await {
-                                                    Ok(_) => {
-                                                    let new_file_name_fmt =
-                                                        format_file_name_to_show(new_file_name_with_extension.clone(), file_extension);
-
-                                                        *file_name_complete_ref.write_silent() = new_file_name_with_extension.clone();
-                                                        file_name_formatted_state.set(new_file_name_fmt);
-
-
-                                                        log::info!("{old_file_name} renamed to {new_file_name_with_extension}");
-                                                    },
-                                                    Err(error) => log::error!("Error renaming file: {error}"),
-                                                };
-                                            }
-                                        });
+                                    let new_file_name = val.read().trim().to_string();
+                                    hide_edit_name_element(&cx, &cx.props.id);
 
+                                    if !new_file_name.is_empty() {
+                                        cx.props.on_rename.call(format!("{}.{}", new_file_name, cx.props.kind));
                                     }
 
                                 }
@@ -186,12 +163,11 @@ pub fn File(cx: Scope<Props>) -> Element {
     })
 }
 
-fn hide_edit_name_element(cx: Scope<Props>) {
+fn hide_edit_name_element(cx: &ScopeState, id: &str) {
     //TODO(File): Investigate in a way to replace use_eval in the future
     // Use js script to hide edit file name element
-    let hide_edit_name_script =
-        include_str!("./hide_edit_name.js").replace("file_id", &cx.props.id.clone());
-    use_eval(&cx)(&hide_edit_name_script);
+    let hide_edit_name_script = include_str!("./hide_edit_name.js").replace("file_id", id);
+    use_eval(cx)(&hide_edit_name_script);
 }
 
 fn format_file_size(file_size: usize) -> String {
@@ -23,4 +23,3 @@ pub mod switch;
 pub mod textarea;
 pub mod tooltip;
 pub mod typing_indicator;
-pub mod utils;
@@ -4,14 +4,16 @@ use dioxus_html::KeyCode;
 
 use super::folder::State;
 
-// Remember: owned props must implement PartialEq!
-#[derive(PartialEq, Eq, Props)]
-pub struct Props {
+#[derive(Props)]
+pub struct Props<'a> {
     state: State,
+    // called with the trimmed name when Enter is pressed
+    on_create: EventHandler<'a, String>,
+    on_cancel: EventHandler<'a, ()>,
 }
 
 #[allow(non_snake_case)]
-pub fn NewFolder(cx: Scope<Props>) -> Element {
+pub fn NewFolder<'a>(cx: Scope<'a, Props<'a>>) -> Element<'a> {
     let class = match cx.props.state {
         State::Primary => "primary",
         State::Secondary => "secondary",
@@ -32,7 +34,12 @@ pub fn NewFolder(cx: Scope<Props>) -> Element {
                 },
                 onkeyup: |evt| {
                     if evt.key_code == KeyCode::Enter {
-                        println!("Create new folder: {}", folder_name.clone());
+                        let name = folder_name.trim();
+                        if !name.is_empty() {
+                            cx.props.on_create.call(name.to_string());
+                        }
+                    } else if evt.key_code == KeyCode::Escape {
+                        cx.props.on_cancel.call(());
                     }
                 }
             }
@@ -1,9 +1,7 @@
-use crate::{
-    context_menu::{ContextItem, ContextMenu},
-    utils,
-};
+use crate::context_menu::{ContextItem, ContextMenu};
 use dioxus::prelude::*;
 use dioxus_html::KeyCode;
+use utils::markdown;
 
 // for more information about this, see here: https://github.com/DioxusLabs/dioxus/issues/611
 // `text` is passed in this way because it is lifted. This allows for a 'send' button to clear the text
@@ -23,7 +21,7 @@ pub fn TextArea<'a>(
     log::debug!("rendering ui_kit/TextArea");
 
     let clear_disabled = &*cx.use_hook(|_| std::cell::Cell::new(true));
-    let formatted = utils::wrap_in_markdown(text.as_ref());
+    let formatted = markdown::render_preview(text.as_ref());
     let mut inner_html = cx.use_hook(|_| " ").clone();
 
     if !inner_html.is_empty() && text.is_empty() && !clear_disabled.get() {
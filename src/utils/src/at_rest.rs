@@ -0,0 +1,126 @@
+// Files which say something about the user, like the state of the chats and the settings, are encrypted
+// at rest. The key is kept in Tesseract, so it's only known once the keystore is unlocked. Files written
+// before encryption are read as they are and encrypted the next time they're saved.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use once_cell::sync::Lazy;
+use warp::{crypto::cipher::Cipher, sync::RwLock, tesseract::Tesseract};
+
+use crate::DEFAULT_PATH;
+
+// the files in the app's folder which are encrypted
+const ENCRYPTED_FILES: [&str; 2] = ["Config.toml", ".uplink.state.json"];
+// where the key is kept in Tesseract
+const KEY_NAME: &str = "local_data_key";
+const KEY_LENGTH: usize = 32;
+// starts every encrypted file, which tells it apart from one written before encryption
+const HEADER: &[u8] = b"UPLINK-ENCRYPTED-1\n";
+
+static KEY: Lazy<RwLock<Option<Vec<u8>>>> = Lazy::new(|| RwLock::new(None));
+
+fn locked() -> io::Error {
+    io::Error::new(io::ErrorKind::PermissionDenied, "local data is locked")
+}
+
+fn other(error: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, error.to_string())
+}
+
+fn encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn decode(text: &str) -> Option<Vec<u8>> {
+    (0..text.len())
+        .step_by(2)
+        .map(|i| {
+            text.get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+        })
+        .collect()
+}
+
+/// takes the key from `tesseract`, which has to be unlocked. One is made the first time.
+pub fn unlock(tesseract: &Tesseract) -> io::Result<()> {
+    let key = match tesseract.exist(KEY_NAME) {
+        true => tesseract
+            .retrieve(KEY_NAME)
+            .map_err(other)
+            .and_then(|text| {
+                decode(&text).ok_or_else(|| other("the local data key is malformed"))
+            })?,
+        false => {
+            let key = warp::crypto::generate(KEY_LENGTH);
+            tesseract.set(KEY_NAME, &encode(&key)).map_err(other)?;
+            key
+        }
+    };
+    *KEY.write() = Some(key);
+    Ok(())
+}
+
+/// forgets the key until the keystore is unlocked again
+pub fn lock() {
+    *KEY.write() = None;
+}
+
+pub fn is_encrypted(path: &Path) -> bool {
+    fs::read(path)
+        .map(|bytes| bytes.starts_with(HEADER))
+        .unwrap_or(false)
+}
+
+/// the contents of `path`, decrypted when they need to be
+pub fn read(path: &Path) -> io::Result<Vec<u8>> {
+    let bytes = fs::read(path)?;
+    match bytes.strip_prefix(HEADER) {
+        None => Ok(bytes),
+        Some(sealed) => {
+            let key = KEY.read().clone().ok_or_else(locked)?;
+            Cipher::direct_decrypt(sealed, &key).map_err(other)
+        }
+    }
+}
+
+pub fn read_to_string(path: &Path) -> io::Result<String> {
+    String::from_utf8(read(path)?).map_err(other)
+}
+
+/// writes `bytes` to `path`, encrypted once the key is known. An encrypted file isn't replaced by a plain
+/// one before then, it's saved again after unlocking.
+pub fn write(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let key = KEY.read().clone();
+    match key {
+        Some(key) => {
+            let mut sealed = HEADER.to_vec();
+            sealed.extend(Cipher::direct_encrypt(bytes, &key).map_err(other)?);
+            fs::write(path, sealed)
+        }
+        None if is_encrypted(path) => Err(locked()),
+        None => fs::write(path, bytes),
+    }
+}
+
+/// writes decrypted copies of the encrypted files to a folder of their own, for debugging. Returns the
+/// folder.
+pub fn export_decrypted() -> io::Result<PathBuf> {
+    let root = DEFAULT_PATH.read().clone();
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or_default();
+    let directory = root.join("decrypted").join(seconds.to_string());
+    fs::create_dir_all(&directory)?;
+    for name in ENCRYPTED_FILES {
+        let path = root.join(name);
+        if path.exists() {
+            fs::write(directory.join(name), read(&path)?)?;
+        }
+    }
+    Ok(directory)
+}
@@ -1,4 +1,8 @@
+pub mod at_rest;
 pub mod extensions;
+pub mod link_preview;
+pub mod markdown;
+pub mod mentions;
 pub mod notifications;
 pub mod sounds;
 
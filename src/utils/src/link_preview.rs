@@ -0,0 +1,413 @@
+// Fetches the title, description, image and icon for links in messages. Requests are bounded by a
+// timeout and a cap on how much of the page is read, and results are cached on disk so a link is only
+// fetched once no matter how often its message is rendered. Links are sent by other people, so hosts
+// on the loopback or a private network are never contacted, including when a redirect points there,
+// and connections go to the addresses that were checked. The image and icon are fetched the same way
+// and kept in the preview as data URLs, so showing a preview never makes the webview load anything.
+
+use anyhow::{bail, Result};
+use once_cell::sync::Lazy;
+use reqwest::{header, redirect, Client, Response, Url};
+use select::{document::Document, predicate::Name};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use warp::{logging::tracing::warn, sync::RwLock};
+
+use crate::DEFAULT_PATH;
+
+// under DEFAULT_PATH
+pub const CACHE_DIR: &str = "link_previews";
+const TIMEOUT: Duration = Duration::from_secs(5);
+// the head of a page is all that's needed and it comes first
+const MAX_BODY_BYTES: usize = 512 * 1024;
+// bigger images and icons are left out of the preview
+const MAX_IMAGE_BYTES: usize = 1024 * 1024;
+const MAX_REDIRECTS: usize = 5;
+const CACHE_LIFETIME: Duration = Duration::from_secs(60 * 60 * 24 * 7);
+const USER_AGENT: &str = concat!("Uplink/", env!("CARGO_PKG_VERSION"));
+
+// the cache is in the folder of the profile in use, so there's a service for each profile
+static LINK_PREVIEWS: Lazy<RwLock<Option<Arc<LinkPreviews>>>> = Lazy::new(|| RwLock::new(None));
+
+pub fn instance() -> Arc<LinkPreviews> {
+    let cache_dir = DEFAULT_PATH.read().join(CACHE_DIR);
+    let mut previews = LINK_PREVIEWS.write();
+    match previews.as_ref() {
+        Some(previews) if previews.cache_dir == cache_dir => previews.clone(),
+        _ => {
+            let created = Arc::new(LinkPreviews::new(cache_dir));
+            created.prune();
+            *previews = Some(created.clone());
+            created
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SiteMeta {
+    pub title: String,
+    pub description: String,
+    // data URLs, empty when there's none or it couldn't be fetched. `parse` leaves them as links.
+    pub icon: String,
+    pub image: String,
+    pub site_name: String,
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    // seconds since the unix epoch
+    fetched: u64,
+    meta: SiteMeta,
+}
+
+pub struct LinkPreviews {
+    timeout: Duration,
+    cache_dir: PathBuf,
+    max_body_bytes: usize,
+    allow_local: bool,
+}
+
+impl LinkPreviews {
+    pub fn new(cache_dir: PathBuf) -> Self {
+        Self::with_limits(cache_dir, TIMEOUT, MAX_BODY_BYTES)
+    }
+
+    pub fn with_limits(cache_dir: PathBuf, timeout: Duration, max_body_bytes: usize) -> Self {
+        Self {
+            timeout,
+            cache_dir,
+            max_body_bytes,
+            allow_local: false,
+        }
+    }
+
+    /// lets previews be fetched from loopback and private addresses. only meant for tests against a
+    /// local server.
+    pub fn allow_local_addresses(mut self) -> Self {
+        self.allow_local = true;
+        self
+    }
+
+    /// returns the preview for `url`, from the cache when possible. Pages which can't be previewed (no
+    /// title, not HTML) are cached as an empty preview; network errors are not cached.
+    pub async fn get(&self, url: &str) -> Result<SiteMeta> {
+        if let Some(meta) = self.cached(url) {
+            return Ok(meta);
+        }
+        let meta = self.fetch(url).await?;
+        self.store(url, &meta);
+        Ok(meta)
+    }
+
+    async fn fetch(&self, url: &str) -> Result<SiteMeta> {
+        let mut response = self.open(Url::parse(url)?).await?;
+        let is_html = match response.headers().get(header::CONTENT_TYPE) {
+            Some(content_type) => matches!(content_type.to_str(), Ok(t) if t.contains("html")),
+            None => true,
+        };
+        if !is_html {
+            return Ok(SiteMeta {
+                url: url.to_string(),
+                ..Default::default()
+            });
+        }
+
+        // relative links on the page are relative to wherever the redirects ended up
+        let base = response.url().clone();
+        let (body, _) = read(&mut response, self.max_body_bytes).await?;
+        let meta = parse(&String::from_utf8_lossy(&body), &base);
+        Ok(SiteMeta {
+            icon: self.embed(&meta.icon).await,
+            image: self.embed(&meta.image).await,
+            url: url.to_string(),
+            ..meta
+        })
+    }
+
+    // the image at `url` as a data URL, or nothing when it can't be fetched or is too big
+    async fn embed(&self, url: &str) -> String {
+        if url.is_empty() {
+            return String::new();
+        }
+        let fetched = async {
+            let mut response = self.open(Url::parse(url)?).await?;
+            // without parameters, which don't belong in a data URL
+            let content_type = match response.headers().get(header::CONTENT_TYPE) {
+                Some(content_type) => content_type
+                    .to_str()?
+                    .split(';')
+                    .next()
+                    .unwrap_or("")
+                    .trim()
+                    .to_string(),
+                None => bail!("no content type"),
+            };
+            if !content_type.starts_with("image/") {
+                bail!("not an image: {}", content_type);
+            }
+            let (bytes, complete) = read(&mut response, MAX_IMAGE_BYTES).await?;
+            if !complete {
+                bail!("image is too big");
+            }
+            Ok(format!(
+                "data:{};base64,{}",
+                content_type,
+                base64::encode(bytes)
+            ))
+        };
+        fetched.await.unwrap_or_else(|e: anyhow::Error| {
+            warn!("left {} out of a link preview: {}", url, e);
+            String::new()
+        })
+    }
+
+    // follows redirects by hand, so every hop is checked and connects to the addresses checked
+    async fn open(&self, url: Url) -> Result<Response> {
+        let mut next = url;
+        let mut redirects = 0;
+        loop {
+            let response = self.client(&next).await?.get(next.clone()).send().await?;
+            if !response.status().is_redirection() {
+                return Ok(response.error_for_status()?);
+            }
+            redirects += 1;
+            if redirects > MAX_REDIRECTS {
+                bail!("too many redirects");
+            }
+            let location = match response.headers().get(header::LOCATION) {
+                Some(location) => location.to_str()?,
+                None => bail!("redirect without a location"),
+            };
+            next = next.join(location)?;
+        }
+    }
+
+    // only web links to public addresses are fetched. a host name is resolved here, every address it
+    // resolves to has to be public, and the client only connects to those, so the name can't be made to
+    // point somewhere else in between.
+    async fn client(&self, url: &Url) -> Result<Client> {
+        if !matches!(url.scheme(), "http" | "https") {
+            bail!("can't preview a {} link", url.scheme());
+        }
+        let host = match url.host_str() {
+            // IPv6 hosts are bracketed
+            Some(host) => host.trim_start_matches('[').trim_end_matches(']'),
+            None => bail!("link has no host"),
+        };
+        let port = url.port_or_known_default().unwrap_or(80);
+        let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host, port)).await?.collect();
+        if addresses.is_empty() {
+            bail!("{} has no address", host);
+        }
+        if !self.allow_local && !addresses.iter().all(|address| is_public(&address.ip())) {
+            bail!("won't preview a link to a local address");
+        }
+        Client::builder()
+            .timeout(self.timeout)
+            .connect_timeout(self.timeout)
+            .redirect(redirect::Policy::none())
+            .user_agent(USER_AGENT)
+            .resolve_to_addrs(host, &addresses)
+            .build()
+            .map_err(|e| {
+                warn!("not possible to build a client for link previews: {}", e);
+                anyhow::Error::from(e)
+            })
+    }
+
+    fn cache_path(&self, url: &str) -> PathBuf {
+        let mut hasher = DefaultHasher::new();
+        url.hash(&mut hasher);
+        self.cache_dir
+            .join(format!("{:016x}.json", hasher.finish()))
+    }
+
+    fn cached(&self, url: &str) -> Option<SiteMeta> {
+        let bytes = fs::read(self.cache_path(url)).ok()?;
+        let entry: CacheEntry = serde_json::from_slice(&bytes).ok()?;
+        let age = now().saturating_sub(entry.fetched);
+        // the url is checked in case of a hash collision. older versions kept links to the image and icon,
+        // those entries are fetched again.
+        let embedded = |value: &str| value.is_empty() || value.starts_with("data:");
+        (age < CACHE_LIFETIME.as_secs()
+            && entry.meta.url == url
+            && embedded(&entry.meta.icon)
+            && embedded(&entry.meta.image))
+        .then_some(entry.meta)
+    }
+
+    fn store(&self, url: &str, meta: &SiteMeta) {
+        let entry = CacheEntry {
+            fetched: now(),
+            meta: meta.clone(),
+        };
+        let result = fs::create_dir_all(&self.cache_dir)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| Ok(serde_json::to_vec(&entry)?))
+            .and_then(|bytes| Ok(fs::write(self.cache_path(url), bytes)?));
+        if let Err(e) = result {
+            warn!("failed to cache link preview for {}: {}", url, e);
+        }
+    }
+
+    /// removes the cached previews which are too old to be used
+    pub fn prune(&self) {
+        let entries = match fs::read_dir(&self.cache_dir) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let entry = fs::read(&path)
+                .ok()
+                .and_then(|bytes| serde_json::from_slice::<CacheEntry>(&bytes).ok());
+            // entries which can't be read are of no use either
+            let expired = match entry {
+                Some(entry) => now().saturating_sub(entry.fetched) >= CACHE_LIFETIME.as_secs(),
+                None => true,
+            };
+            if expired {
+                if let Err(e) = fs::remove_file(&path) {
+                    warn!("failed to remove the old link preview {:?}: {}", path, e);
+                }
+            }
+        }
+    }
+
+    /// removes every cached preview
+    pub fn clear_cache(&self) -> Result<()> {
+        if self.cache_dir.exists() {
+            fs::remove_dir_all(&self.cache_dir)?;
+        }
+        Ok(())
+    }
+}
+
+// reads at most `limit` bytes of the body, and whether that was all of it
+async fn read(response: &mut Response, limit: usize) -> Result<(Vec<u8>, bool)> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        let remaining = limit - body.len();
+        if chunk.len() > remaining {
+            body.extend_from_slice(&chunk[..remaining]);
+            return Ok((body, false));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok((body, true))
+}
+
+fn is_public(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_v4(&ip),
+            None => is_public_v6(ip),
+        },
+    }
+}
+
+fn is_public_v4(ip: &Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        // "this network" and carrier-grade NAT
+        || a == 0
+        || (a == 100 && (64..128).contains(&b)))
+}
+
+fn is_public_v6(ip: &Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // unique local (fc00::/7) and link local (fe80::/10)
+        || (first & 0xfe00) == 0xfc00
+        || (first & 0xffc0) == 0xfe80)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+// resolves `href` against the page it was found on. only web links are kept.
+fn resolve(base: &Url, href: &str) -> Option<String> {
+    let url = base.join(href.trim()).ok()?;
+    matches!(url.scheme(), "http" | "https").then(|| url.to_string())
+}
+
+/// extracts the preview from a page, preferring OpenGraph and Twitter card tags over plain HTML.
+/// `base` is the address of the page and is used to resolve relative links.
+pub fn parse(html: &str, base: &Url) -> SiteMeta {
+    let doc = Document::from(html);
+
+    // OpenGraph uses `property`, Twitter cards and plain HTML use `name`
+    let meta = |keys: &[&str]| -> Option<String> {
+        keys.iter().find_map(|key| {
+            doc.find(Name("meta"))
+                .find(|n| {
+                    matches!(n.attr("property").or_else(|| n.attr("name")), Some(k) if k.eq_ignore_ascii_case(key))
+                })
+                .and_then(|n| n.attr("content"))
+                .map(|content| content.trim().to_string())
+                .filter(|content| !content.is_empty())
+        })
+    };
+
+    let title = meta(&["og:title", "twitter:title"])
+        .or_else(|| {
+            doc.find(Name("title"))
+                .next()
+                .map(|n| n.text().trim().to_string())
+        })
+        .unwrap_or_default();
+    let description =
+        meta(&["og:description", "twitter:description", "description"]).unwrap_or_default();
+    let site_name = meta(&["og:site_name", "application-name"])
+        .or_else(|| base.host_str().map(String::from))
+        .unwrap_or_default();
+    let image = meta(&[
+        "og:image",
+        "og:image:url",
+        "twitter:image",
+        "twitter:image:src",
+    ])
+    .and_then(|src| resolve(base, &src))
+    .unwrap_or_default();
+
+    let icon = doc
+        .find(Name("link"))
+        .find(|n| {
+            matches!(n.attr("rel"), Some(rel) if rel.split_whitespace().any(|r| r.eq_ignore_ascii_case("icon")))
+        })
+        .and_then(|n| n.attr("href"))
+        .and_then(|href| resolve(base, href))
+        .or_else(|| resolve(base, "/favicon.ico"))
+        .unwrap_or_default();
+
+    SiteMeta {
+        title,
+        description,
+        icon,
+        image,
+        site_name,
+        url: base.to_string(),
+    }
+}
@@ -0,0 +1,378 @@
+// Markdown rendering shared by the compose preview and message display.
+//
+// Messages come from remote peers and end up in the webview, so everything produced here goes through
+// an allowlist sanitizer before it is returned. Raw HTML in the source is never interpreted; it is shown
+// as typed.
+
+use ammonia::{Builder, UrlRelative};
+use once_cell::sync::Lazy;
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Options, Parser, Tag};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+use syntect::{
+    highlighting::ThemeSet,
+    html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+use crate::mentions::find_mentions;
+
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+const HIGHLIGHT_THEME: &str = "base16-ocean.dark";
+const SPOILER: &str = "||";
+
+// classes produced by this module
+const ALLOWED_CLASSES: [&str; 5] = ["code-block", "delimiter", "spoiler", "mention", "self"];
+
+static SYNTAXES: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+static SANITIZER: Lazy<Builder<'static>> = Lazy::new(sanitizer);
+
+fn options() -> Options {
+    // Strikethroughs, tables and task lists are not part of the CommonMark standard
+    // and we therefore must enable them explicitly.
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options
+}
+
+fn sanitizer() -> Builder<'static> {
+    let tags = HashSet::from([
+        "a",
+        "b",
+        "blockquote",
+        "br",
+        "code",
+        "del",
+        "em",
+        "h1",
+        "h2",
+        "h3",
+        "h4",
+        "h5",
+        "h6",
+        "hr",
+        "i",
+        "input",
+        "li",
+        "ol",
+        "p",
+        "pre",
+        "span",
+        "strike",
+        "strong",
+        "table",
+        "tbody",
+        "td",
+        "th",
+        "thead",
+        "tr",
+        "ul",
+    ]);
+    let tag_attributes = HashMap::from([
+        ("a", HashSet::from(["href", "title"])),
+        ("code", HashSet::from(["class"])),
+        ("input", HashSet::from(["checked", "disabled", "type"])),
+        ("ol", HashSet::from(["start"])),
+        ("pre", HashSet::from(["class"])),
+        ("span", HashSet::from(["class"])),
+    ]);
+
+    let mut builder = Builder::default();
+    builder
+        .tags(tags)
+        .tag_attributes(tag_attributes)
+        .generic_attributes(HashSet::new())
+        .url_schemes(HashSet::from(["http", "https", "mailto"]))
+        .url_relative(UrlRelative::Deny)
+        .link_rel(Some("noopener noreferrer nofollow"))
+        .attribute_filter(|_element, attribute, value| match attribute {
+            "class" => {
+                let classes: Vec<&str> = value
+                    .split_whitespace()
+                    .filter(|c| c.starts_with("hl-") || ALLOWED_CLASSES.contains(c))
+                    .collect();
+                (!classes.is_empty()).then(|| Cow::Owned(classes.join(" ")))
+            }
+            // task list markers are the only inputs markdown produces
+            "type" => (value == "checkbox").then_some(Cow::Borrowed(value)),
+            _ => Some(Cow::Borrowed(value)),
+        });
+    builder
+}
+
+fn sanitize(html: &str) -> String {
+    SANITIZER.clean(html).to_string()
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#x27;")
+}
+
+// pairs of `||` offsets in `text`. an unmatched trailing `||` is left alone.
+fn find_spoilers(text: &str) -> Vec<(usize, usize)> {
+    let delimiters: Vec<usize> = text.match_indices(SPOILER).map(|(i, _)| i).collect();
+    delimiters
+        .chunks_exact(2)
+        .map(|pair| (pair[0], pair[1]))
+        .collect()
+}
+
+// escapes `text` into `html`, wrapping mentions of `username` in a highlight span
+fn push_text(html: &mut String, text: &str, username: &str) {
+    let mut last = 0;
+    for (begin, end) in find_mentions(text, username) {
+        html.push_str(&escape_html(&text[last..begin]));
+        html.push_str("<span class=\"mention self\">");
+        html.push_str(&escape_html(&text[begin..end]));
+        html.push_str("</span>");
+        last = end;
+    }
+    html.push_str(&escape_html(&text[last..]));
+}
+
+fn render_text(text: &str, username: &str) -> Event<'static> {
+    let spoilers = find_spoilers(text);
+    if spoilers.is_empty() && find_mentions(text, username).is_empty() {
+        return Event::Text(CowStr::from(text.to_string()));
+    }
+
+    let mut html = String::with_capacity(text.len() * 2);
+    let mut last = 0;
+    for (open, close) in spoilers {
+        push_text(&mut html, &text[last..open], username);
+        html.push_str("<span class=\"spoiler\">");
+        push_text(&mut html, &text[open + SPOILER.len()..close], username);
+        html.push_str("</span>");
+        last = close + SPOILER.len();
+    }
+    push_text(&mut html, &text[last..], username);
+    Event::Html(CowStr::from(html))
+}
+
+fn highlight_code(code: &str, info: &str) -> String {
+    // the info string may carry more than the language, e.g. "rust,ignore"
+    let token = info
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .next()
+        .unwrap_or_default();
+    let syntax = SYNTAXES
+        .find_syntax_by_token(token)
+        .unwrap_or_else(|| SYNTAXES.find_syntax_plain_text());
+
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, &SYNTAXES, CLASS_STYLE);
+    let highlighted = LinesWithEndings::from(code)
+        .try_for_each(|line| generator.parse_html_for_line_which_includes_newline(line))
+        .map(|_| generator.finalize())
+        .unwrap_or_else(|_| escape_html(code));
+
+    format!(
+        "<pre class=\"code-block\"><code>{}</code></pre>",
+        highlighted
+    )
+}
+
+/// highlights a whole file as code, `language` is a language name or a file extension.
+pub fn highlight(code: &str, language: &str) -> String {
+    sanitize(&highlight_code(code, language))
+}
+
+/// renders markdown to sanitized HTML.
+pub fn render(text: &str) -> String {
+    render_message(text, "")
+}
+
+/// renders a message to sanitized HTML, highlighting mentions of `username`. Mentions are only looked
+/// for in text, never in code, links' targets or titles, so the highlight can't end up inside markup.
+pub fn render_message(text: &str, username: &str) -> String {
+    let mut events: Vec<Event> = Vec::new();
+    // pulldown_cmark may split a run of text into several events, which would break up spoilers
+    let mut pending_text = String::new();
+    let mut code_block: Option<(String, String)> = None;
+
+    for event in Parser::new_ext(text, options()) {
+        if let Some((_, code)) = code_block.as_mut() {
+            match event {
+                Event::Text(t) => code.push_str(&t),
+                Event::End(Tag::CodeBlock(_)) => {
+                    if let Some((info, code)) = code_block.take() {
+                        events.push(Event::Html(highlight_code(&code, &info).into()));
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        if let Event::Text(t) = &event {
+            pending_text.push_str(t);
+            continue;
+        }
+        if !pending_text.is_empty() {
+            events.push(render_text(&pending_text, username));
+            pending_text.clear();
+        }
+
+        match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let info = match kind {
+                    CodeBlockKind::Fenced(info) => info.to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code_block = Some((info, String::new()));
+            }
+            // raw HTML from a peer is shown as typed
+            Event::Html(html) => events.push(Event::Text(html)),
+            // remote images would leak the reader's IP address, so they are shown as links instead
+            Event::Start(Tag::Image(link_type, url, title)) => {
+                events.push(Event::Start(Tag::Link(link_type, url, title)))
+            }
+            Event::End(Tag::Image(link_type, url, title)) => {
+                events.push(Event::End(Tag::Link(link_type, url, title)))
+            }
+            event => events.push(event),
+        }
+    }
+    if !pending_text.is_empty() {
+        events.push(render_text(&pending_text, username));
+    }
+
+    let mut html_output = String::with_capacity(text.len() * 3 / 2);
+    pulldown_cmark::html::push_html(&mut html_output, events.into_iter());
+    sanitize(&html_output)
+}
+
+// opening and closing HTML for inline markdown shown in the compose preview, along with the maximum
+// length of its delimiter
+fn preview_tag(tag: &Tag) -> Option<(&'static str, &'static str, usize)> {
+    match tag {
+        Tag::Strong => Some(("<b>", "</b>", 2)),
+        Tag::Emphasis => Some(("<i>", "</i>", 1)),
+        Tag::Strikethrough => Some(("<strike>", "</strike>", 2)),
+        _ => None,
+    }
+}
+
+fn delimiter_len(text: &str, at: usize, max: usize) -> usize {
+    let delimiter = match text[at..].chars().next() {
+        Some(c) => c,
+        None => return 0,
+    };
+    text[at..]
+        .chars()
+        .take_while(|c| *c == delimiter)
+        .take(max)
+        .count()
+}
+
+// markers inserted into the source text, by byte offset
+type Marks = Vec<(usize, &'static str)>;
+
+fn mark_delimited(marks: &mut Marks, range: Range<usize>, len: usize, open: &'static str) {
+    marks.push((range.start, "<span class=\"delimiter\">"));
+    marks.push((range.start + len, "</span>"));
+    marks.push((range.start + len, open));
+}
+
+fn mark_delimited_end(marks: &mut Marks, range: Range<usize>, len: usize, close: &'static str) {
+    marks.push((range.end - len, close));
+    marks.push((range.end - len, "<span class=\"delimiter\">"));
+    marks.push((range.end, "</span>"));
+}
+
+fn mark_spoilers(marks: &mut Marks, text: &str, range: Range<usize>) {
+    for (open, close) in find_spoilers(&text[range.clone()]) {
+        let (open, close) = (range.start + open, range.start + close);
+        mark_delimited(
+            marks,
+            open..close + SPOILER.len(),
+            SPOILER.len(),
+            "<span class=\"spoiler\">",
+        );
+        mark_delimited_end(marks, open..close + SPOILER.len(), SPOILER.len(), "</span>");
+    }
+}
+
+/// renders the text being composed. Unlike `render`, every character typed stays visible (delimiters are
+/// styled rather than removed) so the preview lines up with the editable text underneath it.
+pub fn render_preview(text: &str) -> String {
+    let mut marks: Marks = Vec::new();
+    let mut pending_text: Option<Range<usize>> = None;
+    let mut in_code_block = false;
+
+    for (event, range) in Parser::new_ext(text, options()).into_offset_iter() {
+        if let Event::Text(_) = event {
+            if in_code_block {
+                continue;
+            }
+            pending_text = match pending_text.take() {
+                Some(pending) if pending.end == range.start => Some(pending.start..range.end),
+                Some(pending) => {
+                    mark_spoilers(&mut marks, text, pending);
+                    Some(range)
+                }
+                None => Some(range),
+            };
+            continue;
+        }
+        if let Some(pending) = pending_text.take() {
+            mark_spoilers(&mut marks, text, pending);
+        }
+
+        match event {
+            Event::Start(Tag::CodeBlock(_)) => in_code_block = true,
+            Event::End(Tag::CodeBlock(_)) => in_code_block = false,
+            Event::Start(tag) => {
+                if let Some((open, _, max)) = preview_tag(&tag) {
+                    let len = delimiter_len(text, range.start, max);
+                    mark_delimited(&mut marks, range, len, open);
+                }
+            }
+            Event::End(tag) => {
+                if let Some((_, close, max)) = preview_tag(&tag) {
+                    let len = delimiter_len(text, range.start, max);
+                    mark_delimited_end(&mut marks, range, len, close);
+                }
+            }
+            Event::Code(_) => {
+                let len = delimiter_len(text, range.start, usize::MAX);
+                mark_delimited(&mut marks, range.clone(), len, "<code>");
+                mark_delimited_end(&mut marks, range, len, "</code>");
+            }
+            _ => {}
+        }
+    }
+    if let Some(pending) = pending_text.take() {
+        mark_spoilers(&mut marks, text, pending);
+    }
+
+    // markers at the same offset must keep the order they were emitted in, so the sort has to be stable
+    marks.sort_by_key(|(offset, _)| *offset);
+    let mut html_output = String::with_capacity(text.len() * 2);
+    let mut last = 0;
+    for (offset, mark) in marks {
+        html_output.push_str(&escape_html(&text[last..offset]).replace('\n', "<br />"));
+        html_output.push_str(mark);
+        last = offset;
+    }
+    html_output.push_str(&escape_html(&text[last..]).replace('\n', "<br />"));
+    sanitize(&html_output)
+}
+
+/// stylesheet for the classes used by highlighted code blocks
+pub fn highlight_css() -> String {
+    ThemeSet::load_defaults()
+        .themes
+        .get(HIGHLIGHT_THEME)
+        .and_then(|theme| css_for_theme_with_class_style(theme, CLASS_STYLE).ok())
+        .unwrap_or_default()
+}
@@ -0,0 +1,39 @@
+// Mentions are written as `@username`. Usernames may contain spaces, so a mention can't be found by
+// splitting on whitespace; instead a known username is searched for and must not be followed by more
+// of a word.
+
+pub fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// byte offsets of every `@username` in `text`, matched case-insensitively
+pub fn find_mentions(text: &str, username: &str) -> Vec<(usize, usize)> {
+    let mut found = Vec::new();
+    if username.is_empty() {
+        return found;
+    }
+    let needle = format!("@{}", username).to_lowercase();
+    let haystack = text.to_lowercase();
+    // lowercasing can change byte lengths for some scripts; only search if offsets still line up.
+    if haystack.len() != text.len() {
+        return found;
+    }
+
+    let mut start = 0;
+    while let Some(pos) = haystack[start..].find(&needle) {
+        let begin = start + pos;
+        let end = begin + needle.len();
+        let preceded_ok =
+            !matches!(haystack[..begin].chars().next_back(), Some(c) if is_word_char(c));
+        let followed_ok = !matches!(haystack[end..].chars().next(), Some(c) if is_word_char(c));
+        if preceded_ok && followed_ok {
+            found.push((begin, end));
+        }
+        start = end;
+    }
+    found
+}
+
+pub fn mentions_user(text: &str, username: &str) -> bool {
+    !find_mentions(text, username).is_empty()
+}
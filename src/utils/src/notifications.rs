@@ -4,6 +4,25 @@ use notify_rust::Notification;
 #[cfg(target_os = "macos")]
 use objc::{msg_send, sel, sel_impl};
 
+#[cfg(target_os = "linux")]
+use once_cell::sync::Lazy;
+#[cfg(target_os = "linux")]
+use std::collections::HashMap;
+
+// matches the desktop file shipped in extra/linux
+#[cfg(target_os = "linux")]
+const DESKTOP_ENTRY_URI: &str = "application://im.satellite.uplink.desktop";
+
+#[cfg(target_os = "linux")]
+static SESSION_BUS: Lazy<Option<zbus::blocking::Connection>> =
+    Lazy::new(|| match zbus::blocking::Connection::session() {
+        Ok(conn) => Some(conn),
+        Err(e) => {
+            warp::logging::tracing::warn!("no D-Bus session bus, launcher badge disabled: {}", e);
+            None
+        }
+    });
+
 // Implementation to create and push new notifications
 #[allow(non_snake_case)]
 pub fn PushNotification(title: String, content: String, notification_sound: Sounds) {
@@ -16,10 +35,10 @@ pub fn PushNotification(title: String, content: String, notification_sound: Soun
     Play(notification_sound);
 }
 
-pub fn set_badge(count: u32) -> Result<(), String> {
-    #[cfg(not(target_os = "macos"))]
-    let _ = count;
-    #[cfg(target_os = "macos")]
+// returns whether the badge is known to be shown, or an error if the platform has no way of showing one. The
+// caller falls back to the window title unless it's known to be shown.
+#[cfg(target_os = "macos")]
+pub fn set_badge(count: u32) -> Result<bool, String> {
     unsafe {
         use cocoa::{appkit::NSApp, base::nil, foundation::NSString};
 
@@ -31,5 +50,31 @@ pub fn set_badge(count: u32) -> Result<(), String> {
         let dock_tile: cocoa::base::id = msg_send![NSApp(), dockTile];
         let _: cocoa::base::id = msg_send![dock_tile, setBadgeLabel: label];
     }
-    Ok(())
+    Ok(true)
+}
+
+// Unity LauncherEntry API. also understood by KDE, Plank and Dash to Dock. The update is a broadcast signal
+// and listeners don't own a well-known name, so there's no telling whether any launcher showed it.
+#[cfg(target_os = "linux")]
+pub fn set_badge(count: u32) -> Result<bool, String> {
+    let conn = SESSION_BUS
+        .as_ref()
+        .ok_or_else(|| String::from("no D-Bus session bus"))?;
+    let mut props: HashMap<&str, zbus::zvariant::Value> = HashMap::new();
+    props.insert("count", zbus::zvariant::Value::from(count as i64));
+    props.insert("count-visible", zbus::zvariant::Value::from(count > 0));
+    conn.emit_signal(
+        None::<&str>,
+        "/im/satellite/uplink",
+        "com.canonical.Unity.LauncherEntry",
+        "Update",
+        &(DESKTOP_ENTRY_URI, props),
+    )
+    .map(|_| false)
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub fn set_badge(_count: u32) -> Result<bool, String> {
+    Err(String::from("badges are not supported on this platform"))
 }
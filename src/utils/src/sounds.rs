@@ -1,32 +1,160 @@
+use once_cell::sync::Lazy;
 use soloud::*;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+use std::thread;
+use warp::logging::tracing::{error, info, warn};
 
+static AUDIO_SERVICE: Lazy<AudioService> = Lazy::new(AudioService::spawn);
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
 pub enum Sounds {
+    // a new message was received
     Notification,
     FriendReq,
+    Mention,
+    Call,
     General,
 }
 
-#[allow(non_snake_case)]
-pub fn Play(sound: Sounds) {
-    // Create a Soloud instance
-    let sl = Soloud::default().unwrap();
-    // Create a Wav instance
+/// user configurable sound settings. Sounds without an entry in `files` use the built in sound.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SoundSettings {
+    pub enabled: bool,
+    /// between 0.0 and 1.0
+    pub volume: f32,
+    pub files: HashMap<Sounds, PathBuf>,
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            volume: 1.0,
+            files: HashMap::new(),
+        }
+    }
+}
+
+enum Command {
+    Play(Sounds),
+    Configure(SoundSettings),
+}
+
+/// owns a single `Soloud` instance on its own thread. Sounds are requested over a channel so
+/// callers never block while a sound is playing.
+pub struct AudioService {
+    tx: Mutex<Sender<Command>>,
+}
+
+impl AudioService {
+    fn spawn() -> Self {
+        let (tx, rx) = mpsc::channel();
+        if let Err(e) = thread::Builder::new()
+            .name("uplink-audio".into())
+            .spawn(move || run(rx))
+        {
+            error!("failed to spawn audio thread: {}", e);
+        }
+        Self { tx: Mutex::new(tx) }
+    }
+
+    pub fn instance() -> &'static AudioService {
+        Lazy::force(&AUDIO_SERVICE)
+    }
+
+    fn send(&self, cmd: Command) {
+        let tx = match self.tx.lock() {
+            Ok(tx) => tx,
+            Err(e) => e.into_inner(),
+        };
+        // the receiver only goes away if the audio thread failed to start.
+        let _ = tx.send(cmd);
+    }
+}
+
+fn run(rx: Receiver<Command>) {
+    let mut settings = SoundSettings::default();
+    // if there isn't an output device (headless test runs, CI) keep draining the channel so senders don't notice.
+    let mut sl = match Soloud::default() {
+        Ok(sl) => Some(sl),
+        Err(e) => {
+            warn!("no audio device available, sounds are disabled: {}", e);
+            None
+        }
+    };
+    // a `Wav` must outlive its playback, so loaded sounds are kept here for the lifetime of the thread.
+    let mut loaded: HashMap<Sounds, audio::Wav> = HashMap::new();
+
+    while let Ok(cmd) = rx.recv() {
+        match cmd {
+            Command::Configure(new_settings) => {
+                if new_settings.files != settings.files {
+                    loaded.clear();
+                }
+                settings = new_settings;
+                if let Some(sl) = sl.as_mut() {
+                    sl.set_global_volume(settings.volume.clamp(0.0, 1.0));
+                }
+            }
+            Command::Play(sound) => {
+                let sl = match &sl {
+                    Some(sl) if settings.enabled => sl,
+                    _ => continue,
+                };
+                if !loaded.contains_key(&sound) {
+                    match load(sound, &settings) {
+                        Ok(wav) => {
+                            loaded.insert(sound, wav);
+                        }
+                        Err(e) => {
+                            error!("failed to load sound {:?}: {}", sound, e);
+                            continue;
+                        }
+                    }
+                }
+                if let Some(wav) = loaded.get(&sound) {
+                    sl.play(wav);
+                }
+            }
+        }
+    }
+    info!("audio service stopped");
+}
+
+fn load(sound: Sounds, settings: &SoundSettings) -> Result<audio::Wav, SoloudError> {
     let mut wav = audio::Wav::default();
-    // Load the appropriate sound file based on the `sound` argument
+    if let Some(path) = settings.files.get(&sound) {
+        match wav.load(path) {
+            Ok(_) => return Ok(wav),
+            // fall back to the built in sound if the custom file went missing
+            Err(e) => warn!("failed to load custom sound {:?}: {}", path, e),
+        }
+    }
+    wav.load_mem(default_sound(sound))?;
+    Ok(wav)
+}
+
+fn default_sound(sound: Sounds) -> &'static [u8] {
     match sound {
-        Sounds::Notification => wav
-            .load_mem(include_bytes!("../../../extra/assets/sounds/Ponderous.ogg"))
-            .unwrap(),
-        Sounds::FriendReq => wav
-            .load_mem(include_bytes!("../../../extra/assets/sounds/Success.ogg"))
-            .unwrap(),
-        // The `General` case is not handled
-        Sounds::General => {}
-    };
-    // Play the sound
-    sl.play(&wav);
-    // Wait until the sound finishes playing
-    while sl.voice_count() > 0 {
-        std::thread::sleep(std::time::Duration::from_millis(100));
+        Sounds::Notification | Sounds::Mention => {
+            include_bytes!("../../../extra/assets/sounds/Ponderous.ogg")
+        }
+        Sounds::FriendReq | Sounds::Call => {
+            include_bytes!("../../../extra/assets/sounds/Success.ogg")
+        }
+        Sounds::General => include_bytes!("../../../extra/assets/sounds/Error.ogg"),
     }
 }
+
+/// queues a sound on the audio service and returns immediately.
+#[allow(non_snake_case)]
+pub fn Play(sound: Sounds) {
+    AudioService::instance().send(Command::Play(sound));
+}
+
+pub fn configure(settings: SoundSettings) {
+    AudioService::instance().send(Command::Configure(settings));
+}
@@ -0,0 +1,320 @@
+// Runs the link preview service against a small HTTP stub on localhost.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+use utils::link_preview::LinkPreviews;
+
+const ARTICLE: &str = r#"<html><head>
+    <title>Plain title</title>
+    <meta property="og:title" content="OpenGraph title">
+    <meta property="og:site_name" content="Example News">
+    <meta name="twitter:description" content="Twitter description">
+    <meta name="twitter:image" content="images/cover.png">
+    <link rel="shortcut icon" href="/static/icon.png">
+</head><body></body></html>"#;
+
+const BIG_IMAGE: &str = r#"<html><head>
+    <title>Big image</title>
+    <meta property="og:image" content="/huge.png">
+    <link rel="icon" href="/plain">
+</head></html>"#;
+
+const COVER: &[u8] = b"\x89PNG cover";
+const ICON: &[u8] = b"\x89PNG icon";
+
+const PLAIN: &str = r#"<html><head>
+    <title> Plain page </title>
+    <meta name="description" content="Just HTML">
+</head></html>"#;
+
+struct Stub {
+    address: String,
+    requests: Arc<AtomicUsize>,
+}
+
+fn respond(stream: &mut TcpStream, status: &str, headers: &str, body: &[u8]) {
+    let head = format!(
+        "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        headers,
+        body.len()
+    );
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(body);
+}
+
+fn handle(mut stream: TcpStream) {
+    let mut request_line = String::new();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    reader.read_line(&mut request_line).unwrap();
+    // skip the headers
+    let mut line = String::new();
+    while reader.read_line(&mut line).unwrap() > 2 {
+        line.clear();
+    }
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let html = "Content-Type: text/html; charset=utf-8\r\n";
+    match path {
+        "/articles/1" => respond(&mut stream, "200 OK", html, ARTICLE.as_bytes()),
+        "/plain" => respond(&mut stream, "200 OK", html, PLAIN.as_bytes()),
+        "/big-image" => respond(&mut stream, "200 OK", html, BIG_IMAGE.as_bytes()),
+        "/articles/images/cover.png" => {
+            respond(&mut stream, "200 OK", "Content-Type: image/png\r\n", COVER)
+        }
+        "/static/icon.png" => respond(
+            &mut stream,
+            "200 OK",
+            "Content-Type: image/png; charset=binary\r\n",
+            ICON,
+        ),
+        "/huge.png" => respond(
+            &mut stream,
+            "200 OK",
+            "Content-Type: image/png\r\n",
+            &vec![0; 4 * 1024 * 1024],
+        ),
+        "/moved" => respond(
+            &mut stream,
+            "301 Moved Permanently",
+            "Location: /articles/1\r\n",
+            b"",
+        ),
+        "/file.zip" => respond(
+            &mut stream,
+            "200 OK",
+            "Content-Type: application/zip\r\n",
+            b"PK",
+        ),
+        "/slow" => {
+            thread::sleep(Duration::from_secs(3));
+            respond(&mut stream, "200 OK", html, PLAIN.as_bytes());
+        }
+        "/huge" => {
+            let mut body = PLAIN.as_bytes().to_vec();
+            body.resize(8 * 1024 * 1024, b' ');
+            respond(&mut stream, "200 OK", html, &body);
+        }
+        _ => respond(&mut stream, "404 Not Found", html, b""),
+    }
+}
+
+fn stub() -> Stub {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            counter.fetch_add(1, Ordering::SeqCst);
+            thread::spawn(move || handle(stream));
+        }
+    });
+    Stub { address, requests }
+}
+
+fn cache_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "uplink-link-preview-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn data_url(bytes: &[u8]) -> String {
+    format!("data:image/png;base64,{}", base64::encode(bytes))
+}
+
+fn previews(name: &str) -> LinkPreviews {
+    LinkPreviews::with_limits(cache_dir(name), Duration::from_secs(1), 64 * 1024)
+        .allow_local_addresses()
+}
+
+#[tokio::test]
+async fn prefers_opengraph_and_twitter_cards() {
+    let stub = stub();
+    let url = format!("{}/articles/1", stub.address);
+    let meta = previews("opengraph").get(&url).await.unwrap();
+
+    assert_eq!(meta.title, "OpenGraph title");
+    assert_eq!(meta.description, "Twitter description");
+    assert_eq!(meta.site_name, "Example News");
+    assert_eq!(meta.image, data_url(COVER));
+    assert_eq!(meta.icon, data_url(ICON));
+    assert_eq!(meta.url, url);
+}
+
+#[tokio::test]
+async fn falls_back_to_plain_html() {
+    let stub = stub();
+    let meta = previews("plain")
+        .get(&format!("{}/plain", stub.address))
+        .await
+        .unwrap();
+
+    assert_eq!(meta.title, "Plain page");
+    assert_eq!(meta.description, "Just HTML");
+    assert_eq!(meta.image, "");
+    // there's no /favicon.ico
+    assert_eq!(meta.icon, "");
+    assert_eq!(meta.site_name, "127.0.0.1");
+}
+
+#[tokio::test]
+async fn resolves_links_after_redirects() {
+    let stub = stub();
+    let url = format!("{}/moved", stub.address);
+    let meta = previews("redirect").get(&url).await.unwrap();
+
+    assert_eq!(meta.title, "OpenGraph title");
+    assert_eq!(meta.image, data_url(COVER));
+    assert_eq!(meta.url, url);
+}
+
+#[tokio::test]
+async fn caches_on_disk() {
+    let stub = stub();
+    let url = format!("{}/plain", stub.address);
+    let dir = cache_dir("cache");
+
+    let first = LinkPreviews::new(dir.clone())
+        .allow_local_addresses()
+        .get(&url)
+        .await
+        .unwrap();
+    let requests = stub.requests.load(Ordering::SeqCst);
+    // a new service reading the same directory must not go back to the network
+    let second = LinkPreviews::new(dir)
+        .allow_local_addresses()
+        .get(&url)
+        .await
+        .unwrap();
+
+    assert_eq!(first, second);
+    assert_eq!(stub.requests.load(Ordering::SeqCst), requests);
+}
+
+#[tokio::test]
+async fn times_out() {
+    let stub = stub();
+    let result = previews("timeout")
+        .get(&format!("{}/slow", stub.address))
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn stops_reading_large_pages() {
+    let stub = stub();
+    let meta = previews("huge")
+        .get(&format!("{}/huge", stub.address))
+        .await
+        .unwrap();
+    assert_eq!(meta.title, "Plain page");
+}
+
+#[tokio::test]
+async fn skips_pages_that_are_not_html() {
+    let stub = stub();
+    let meta = previews("zip")
+        .get(&format!("{}/file.zip", stub.address))
+        .await
+        .unwrap();
+    assert!(meta.title.is_empty());
+}
+
+#[tokio::test]
+async fn rejects_other_schemes() {
+    assert!(previews("scheme").get("file:///etc/passwd").await.is_err());
+    assert!(previews("scheme").get("javascript:alert(1)").await.is_err());
+}
+
+#[tokio::test]
+async fn refuses_local_addresses() {
+    let stub = stub();
+    let previews = LinkPreviews::new(cache_dir("local"));
+    let port = stub.address.rsplit(':').next().unwrap();
+
+    assert!(previews
+        .get(&format!("{}/plain", stub.address))
+        .await
+        .is_err());
+    assert!(previews
+        .get(&format!("http://localhost:{}/plain", port))
+        .await
+        .is_err());
+    assert!(previews
+        .get(&format!("http://[::1]:{}/plain", port))
+        .await
+        .is_err());
+    assert!(previews.get("http://10.0.0.1/").await.is_err());
+    assert!(previews.get("http://169.254.169.254/").await.is_err());
+    assert_eq!(stub.requests.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn leaves_out_big_images_and_other_files() {
+    let stub = stub();
+    let meta = previews("big-image")
+        .get(&format!("{}/big-image", stub.address))
+        .await
+        .unwrap();
+    assert_eq!(meta.title, "Big image");
+    assert_eq!(meta.image, "");
+    // the icon link points at a page
+    assert_eq!(meta.icon, "");
+}
+
+#[tokio::test]
+async fn prunes_old_previews() {
+    let stub = stub();
+    let dir = cache_dir("prune");
+    let previews = LinkPreviews::new(dir.clone()).allow_local_addresses();
+    previews
+        .get(&format!("{}/plain", stub.address))
+        .await
+        .unwrap();
+    previews
+        .get(&format!("{}/articles/1", stub.address))
+        .await
+        .unwrap();
+
+    // one of them is made to look like it was fetched long ago
+    let mut files: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    files.sort();
+    let old = &files[0];
+    let mut entry: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(old).unwrap()).unwrap();
+    entry["fetched"] = 0.into();
+    std::fs::write(old, serde_json::to_vec(&entry).unwrap()).unwrap();
+
+    previews.prune();
+    assert!(!old.exists());
+    assert!(files[1].exists());
+}
+
+#[tokio::test]
+async fn connects_to_the_addresses_it_checked() {
+    let stub = stub();
+    let port = stub.address.rsplit(':').next().unwrap();
+    let meta = previews("resolved")
+        .get(&format!("http://localhost:{}/plain", port))
+        .await
+        .unwrap();
+    assert_eq!(meta.title, "Plain page");
+}
@@ -0,0 +1,227 @@
+// Messages are rendered into the webview, so none of these may produce anything that can run script,
+// load remote content or escape the message bubble.
+
+use utils::markdown::{render, render_message, render_preview};
+
+const CORPUS: &[&str] = &[
+    "<script>alert(1)</script>",
+    "<SCRIPT SRC=//evil.example/xss.js></SCRIPT>",
+    "<img src=x onerror=alert(1)>",
+    "<svg/onload=alert(1)>",
+    "<iframe src=\"javascript:alert(1)\"></iframe>",
+    "<a href=\"javascript:alert(1)\">click</a>",
+    "<div style=\"background:url(https://evil.example/track)\">x</div>",
+    "<style>body { display: none }</style>",
+    "<object data=\"data:text/html;base64,PHNjcmlwdD5hbGVydCgxKTwvc2NyaXB0Pg==\"></object>",
+    "<math><mtext><table><mglyph><style><img src=x onerror=alert(1)>",
+    "<!--<img src=x onerror=alert(1)>-->",
+    "<details open ontoggle=alert(1)>",
+    "<input autofocus onfocus=alert(1)>",
+    "[click](javascript:alert(1))",
+    "[click](JaVaScRiPt:alert(1))",
+    "[click](&#106;avascript:alert(1))",
+    "[click](data:text/html,<script>alert(1)</script>)",
+    "[click](vbscript:msgbox(1))",
+    "[click](https://example.com \"\\\" onmouseover=alert(1) x=\\\"\")",
+    "![x](https://evil.example/track.png)",
+    "![x\" onerror=\"alert(1)](x)",
+    "<https://example.com/\" onmouseover=\"alert(1)>",
+    "**<img src=x onerror=alert(1)>**",
+    "||<script>alert(1)</script>||",
+    "`<script>alert(1)</script>`",
+    "```html\n<script>alert(1)</script>\n```",
+    "```\"><script>alert(1)</script>\ncode\n```",
+    "| a | b |\n|---|---|\n| <script>alert(1)</script> | <img src=x onerror=alert(1)> |",
+    "- [x] <img src=x onerror=alert(1)>",
+    "<p class=\"x\" onclick=\"alert(1)\">hi</p>",
+    "<span class=\"mention self\" onclick=\"alert(1)\">@me</span>",
+];
+
+// the reader's own username is "me"; a highlight added in the wrong place could open up markup
+const MENTION_CORPUS: &[&str] = &[
+    "[x](https://a \"@me <img src=x onerror=alert(1)>\")",
+    "[x](https://a '@me\" onmouseover=\"alert(1)')",
+    "[@me](https://a \"@me\" onclick=\"alert(1)\")",
+    "![@me\" onerror=\"alert(1)](https://a/@me \"@me\")",
+    "<https://a/@me\" onmouseover=\"alert(1)>",
+    "<a title=\"@me\" onclick=\"alert(1)\">@me</a>",
+    "`@me\" onclick=\"alert(1)`",
+    "```@me\" onclick=\"alert(1)\n@me <img src=x onerror=alert(1)>\n```",
+    "||@me <script>alert(1)</script>||",
+    "@me<img src=x onerror=alert(1)>",
+    "@me&quot; onclick=&quot;alert(1)",
+];
+
+// tag and attribute names that must never make it into the markup
+const FORBIDDEN_MARKUP: &[&str] = &[
+    "script",
+    "img",
+    "svg",
+    "iframe",
+    "object",
+    "style",
+    "div",
+    "details",
+    "math",
+    "onerror",
+    "onload",
+    "onclick",
+    "onfocus",
+    "ontoggle",
+    "onmouseover",
+    "src",
+];
+
+const FORBIDDEN_SCHEMES: &[&str] = &["javascript:", "vbscript:", "data:"];
+
+// splits every tag in `output` into its markup and its quoted attribute values. text is escaped, so
+// any `<` outside a tag starts a real one; inside a tag, `"` delimits a value, which may hold `<` or `>`.
+fn tags(output: &str) -> Vec<(String, Vec<String>)> {
+    let mut tags = Vec::new();
+    let mut current: Option<(String, Vec<String>)> = None;
+    let mut value: Option<String> = None;
+    for c in output.chars() {
+        match (current.as_mut(), value.as_mut()) {
+            (None, _) if c == '<' => current = Some((String::new(), Vec::new())),
+            (None, _) => {}
+            (Some((_, values)), Some(v)) if c == '"' => {
+                values.push(v.to_lowercase());
+                value = None;
+            }
+            (Some(_), Some(v)) => v.push(c),
+            (Some(_), None) if c == '"' => value = Some(String::new()),
+            (Some(_), None) if c == '>' => tags.extend(current.take()),
+            (Some((markup, _)), None) => markup.extend(c.to_lowercase()),
+        }
+    }
+    tags
+}
+
+fn assert_safe(input: &str, output: &str) {
+    for (markup, values) in tags(output) {
+        for forbidden in FORBIDDEN_MARKUP {
+            assert!(
+                !markup.contains(forbidden),
+                "{:?} rendered to {:?}, which contains {:?}",
+                input,
+                output,
+                forbidden
+            );
+        }
+        for scheme in FORBIDDEN_SCHEMES {
+            assert!(
+                !values.iter().any(|v| v.trim_start().starts_with(scheme)),
+                "{:?} rendered to {:?}, which links to {:?}",
+                input,
+                output,
+                scheme
+            );
+        }
+    }
+}
+
+#[test]
+fn render_is_safe() {
+    for input in CORPUS {
+        assert_safe(input, &render(input));
+    }
+}
+
+#[test]
+fn messages_are_safe() {
+    for input in CORPUS.iter().chain(MENTION_CORPUS) {
+        assert_safe(input, &render_message(input, "me"));
+    }
+}
+
+#[test]
+fn render_preview_is_safe() {
+    for input in CORPUS {
+        assert_safe(input, &render_preview(input));
+    }
+}
+
+#[test]
+fn raw_html_is_shown_as_typed() {
+    let output = render("<b>hi</b>");
+    assert!(output.contains("&lt;b&gt;hi&lt;/b&gt;"), "{}", output);
+}
+
+#[test]
+fn links_open_safely() {
+    let output = render("[site](https://example.com)");
+    assert!(
+        output.contains("href=\"https://example.com\""),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("rel=\"noopener noreferrer nofollow\""),
+        "{}",
+        output
+    );
+}
+
+#[test]
+fn supported_markdown() {
+    let output = render("**bold** _italic_ ~~gone~~ `code` ||secret||");
+    assert!(output.contains("<strong>bold</strong>"), "{}", output);
+    assert!(output.contains("<em>italic</em>"), "{}", output);
+    assert!(output.contains("<del>gone</del>"), "{}", output);
+    assert!(output.contains("<code>code</code>"), "{}", output);
+    assert!(
+        output.contains("<span class=\"spoiler\">secret</span>"),
+        "{}",
+        output
+    );
+
+    let output = render("| a | b |\n|---|---|\n| 1 | 2 |");
+    assert!(output.contains("<table>"), "{}", output);
+
+    let output = render("- [x] done\n- [ ] todo");
+    assert!(output.contains("type=\"checkbox\""), "{}", output);
+
+    let output = render("```rust\nfn main() {}\n```");
+    assert!(output.contains("<pre class=\"code-block\">"), "{}", output);
+    assert!(output.contains("class=\"hl-"), "{}", output);
+}
+
+#[test]
+fn preview_keeps_every_character() {
+    let output = render_preview("**bold** and ||secret||");
+    assert_eq!(
+        output,
+        "<span class=\"delimiter\">**</span><b>bold</b><span class=\"delimiter\">**</span> and \
+         <span class=\"delimiter\">||</span><span class=\"spoiler\">secret</span><span class=\"delimiter\">||</span>"
+    );
+}
+
+#[test]
+fn mentions_are_highlighted_in_text_only() {
+    let output = render_message("hi @Me, ||@me||", "me");
+    assert!(
+        output.contains("hi <span class=\"mention self\">@Me</span>,"),
+        "{}",
+        output
+    );
+    assert!(
+        output.contains("<span class=\"spoiler\"><span class=\"mention self\">@me</span></span>"),
+        "{}",
+        output
+    );
+
+    for input in [
+        "`@me`",
+        "```\n@me\n```",
+        "[x](https://a \"@me\")",
+        "[x](https://a/@me)",
+    ] {
+        let output = render_message(input, "me");
+        assert!(
+            !output.contains("mention"),
+            "{:?} rendered to {:?}",
+            input,
+            output
+        );
+    }
+}